@@ -1,89 +1,610 @@
-use crate::{lexer, parser::{Node, EvalVal, ValType}};
- 
+//the eval functions are named like the node classes they evaluate
+#![allow(non_snake_case)]
 
-pub fn Add(node: Node) -> Option<EvalVal> {
-    let num1 = node.token_values[0].eval();
-    let num2 = node.token_values[2].eval();
+use crate::{builtins, lexer, operators, parser::Node};
+use crate::bigint::BigInt;
+use crate::rational::Rational;
+use crate::value::{self, Function, Value};
+use std::cell::RefCell;
+use std::io::{BufRead, Write};
+use std::rc::Rc;
+use crate::environment::{Control, Environment, Scope};
+use crate::error::{ErrorKind, RuntimeError};
+use crate::operators::OperatorResult;
 
-    let output = num1.unwrap().num.unwrap() + num2.unwrap().num.unwrap();
 
-    Some(
-        EvalVal {  
-            num: Some(output),  
-            valtype: ValType::NUM,
-            ..Default::default()
+//evaluates both sides of a binary operator
+fn operands(node: &Node, env: &mut Environment) -> Result<(Value, Value), RuntimeError> {
+    Ok((node.token_values[0].eval(env)?, node.token_values[2].eval(env)?))
+}
+
+//gives the error of an operator the location of the node it came from
+fn located<T>(node: &Node, result: OperatorResult<T>) -> Result<T, RuntimeError> {
+    result.map_err(|(kind, message)| RuntimeError::new(kind, &message, node))
+}
+
+pub fn Add(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let (value1, value2) = operands(node, env)?;
+    located(node, operators::add(value1, value2))
+}
+
+pub fn Minus(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let (value1, value2) = operands(node, env)?;
+    located(node, operators::minus(value1, value2))
+}
+
+pub fn Mul(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let (value1, value2) = operands(node, env)?;
+    located(node, operators::mul(value1, value2))
+}
+
+pub fn Div(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let (value1, value2) = operands(node, env)?;
+    located(node, operators::div(value1, value2))
+}
+
+pub fn FloorDiv(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let (value1, value2) = operands(node, env)?;
+    located(node, operators::floor_div(value1, value2))
+}
+
+pub fn Mod(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let (value1, value2) = operands(node, env)?;
+    located(node, operators::modulo(value1, value2))
+}
+
+pub fn Pow(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let (value1, value2) = operands(node, env)?;
+    located(node, operators::pow(value1, value2))
+}
+
+pub fn Bitwise(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let (value1, value2) = operands(node, env)?;
+    located(node, operators::bitwise(&node.class_name, value1, value2))
+}
+
+pub fn Invert(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let value = node.token_values[1].eval(env)?;
+    located(node, operators::invert(value))
+}
+
+//a < b < c is a < b and b < c with b evaluated once, the rules nest to the left so
+//the chain is every comparison on the left that isnt in parentheses
+pub fn Compare(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let mut links = vec![node];
+    loop {
+        let first: &Node = links[0];
+        let left = &first.token_values[0];
+        if !operators::COMPARISONS.contains(&left.class_name.as_str()) || left.parenthesized() {
+            break;
+        }
+        links.insert(0, left);
+    }
+
+    let mut value1 = links[0].token_values[0].eval(env)?;
+    for link in links {
+        let value2 = link.token_values[2].eval(env)?;
+        if !located(link, operators::compare(&link.class_name, &value1, &value2))? {
+            return Ok(Value::Bool(false));
         }
-    )
+        value1 = value2;
+    }
+
+    Ok(Value::Bool(true))
+}
+
+pub fn Bool(node: &Node, _env: &mut Environment) -> Result<Value, RuntimeError> {
+    Ok(Value::Bool(node.token_values[0].token == Some(lexer::Token::TRUE)))
+}
+
+//and and or give back the operand that decided, the right one is only evaluated if needed
+pub fn And(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let value1 = node.token_values[0].eval(env)?;
+    if !value1.truthy() {
+        return Ok(value1);
+    }
+
+    node.token_values[2].eval(env)
 }
 
-pub fn Minus(node: Node) -> Option<EvalVal> {
-    let num1 = node.token_values[0].eval();
-    let num2 = node.token_values[2].eval();
+pub fn Or(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let value1 = node.token_values[0].eval(env)?;
+    if value1.truthy() {
+        return Ok(value1);
+    }
 
-    let output = num1.unwrap().num.unwrap() - num2.unwrap().num.unwrap();
+    node.token_values[2].eval(env)
+}
+
+pub fn Not(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    Ok(Value::Bool(!node.token_values[1].eval(env)?.truthy()))
+}
 
-    Some(
-        EvalVal {  
-            num: Some(output),
-            valtype: ValType::NUM,
-            ..Default::default()
+pub fn Index(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let (target, index) = (node.token_values[0].eval(env)?, node.token_values[2].eval(env)?);
+    located(node, operators::item(&target, &index))
+}
+
+//target[start:end:step] where all of them can be left out
+pub fn Slice(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let target = node.token_values[0].eval(env)?;
+
+    let mut bounds: Vec<Value> = vec![Value::Null];
+    for (name, value) in node.token_names[2..node.token_names.len() - 1].iter().zip(&node.token_values[2..]) {
+        match name.as_str() {
+            "COLON" => bounds.push(Value::Null),
+            _ => *bounds.last_mut().unwrap() = value.eval(env)?
         }
-    )
+    }
+    let step = bounds.get(2).cloned().unwrap_or(Value::Null);
+
+    located(node, operators::slice(&target, &bounds[0], &bounds[1], &step))
 }
 
-pub fn Mul(node: Node) -> Option<EvalVal> {
-    let num1 = node.token_values[0].eval();
-    let num2 = node.token_values[2].eval();
+//[a, b, c], every literal makes a new list
+pub fn List(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let values = match node.token_values.len() {
+        2 => vec![],
+        _ => arguments(&node.token_values[1], env)?
+    };
 
-    let output = num1.unwrap().num.unwrap() * num2.unwrap().num.unwrap();
+    Ok(Value::list(values))
+}
+
+fn attribute_name(node: &Node) -> Result<String, RuntimeError> {
+    let field = &node.token_values[2];
+    if field.class_name != "Name" {
+        return Err(RuntimeError::new(ErrorKind::SyntaxError, "expected a name after .", field));
+    }
+
+    Ok(field.token_values[0].token.as_ref().unwrap().value())
+}
+
+pub fn Attribute(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let name = attribute_name(node)?;
+    let value = node.token_values[0].eval(env)?;
+    located(node, operators::attribute(&value, &name))
+}
+
+//a bare name as a key is the string, like in {a: 1}, any other expression is evaluated
+fn map_key(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let key = match node.class_name.as_str() {
+        "Name" if !node.parenthesized() => Value::Str(node.token_values[0].token.as_ref().unwrap().value()),
+        _ => node.eval(env)?
+    };
+
+    located(node, builtins::hashable(&key))?;
+    Ok(key)
+}
+
+//Entries nest to the left, the key and value are always the last three nodes
+fn entries(node: &Node, env: &mut Environment, map: &mut value::Map) -> Result<(), RuntimeError> {
+    if node.token_names[0] == "entries" {
+        entries(&node.token_values[0], env, map)?;
+    }
+
+    let length = node.token_values.len();
+    let key = map_key(&node.token_values[length - 3], env)?;
+    let value = node.token_values[length - 1].eval(env)?;
+    map.insert(key, value);
+
+    Ok(())
+}
+
+//{key: value, ...}, later entries overwrite earlier ones with the same key
+pub fn Map(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let mut map = value::Map::new();
+    if node.token_names[0] == "entries" {
+        entries(&node.token_values[0], env, &mut map)?;
+    }
+
+    Ok(Value::map(map))
+}
+
+pub fn Num(node: &Node, _env: &mut Environment) -> Result<Value, RuntimeError> {
+    Ok(number(node.token_values[0].token.as_ref().unwrap()))
+}
+
+//the value of a NUM token, the lexer already checked that it is a number
+pub fn number(token: &lexer::Token) -> Value {
+    let text = token.value();
 
-    Some(
-        EvalVal {  
-            num: Some(output),
-            valtype: ValType::NUM,
-            ..Default::default()
+    match token.get_num_type() {
+        Some(lexer::NumType::INT) => match text.parse::<i64>() {
+            Ok(int) => Value::Int(int),
+            Err(_) => Value::BigInt(BigInt::parse(&text).unwrap())
+        },
+        Some(lexer::NumType::RATIONAL) => Value::Rational(Rational::parse(text.trim_end_matches('r')).unwrap()),
+        _ => Value::Float(text.parse::<f64>().unwrap())
+    }
+}
+
+pub fn String(node: &Node, _env: &mut Environment) -> Result<Value, RuntimeError> {
+    let output = node.token_values[0].token.as_ref().unwrap().value();
+
+    Ok(Value::Str(output))
+}
+
+pub fn Program(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    node.token_values[0].eval(env)
+}
+
+//statements one after another, a break or continue skips the rest
+//the parser joins a statement list into one node, so this runs them in a loop instead of recursing per statement
+pub fn Stmts(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let mut value = Value::Null;
+    for stmt in &node.token_values {
+        value = stmt.eval(env)?;
+        if env.control != Control::Normal {
+            break;
         }
-    )
+    }
+
+    Ok(value)
 }
 
-pub fn Div(node: Node) -> Option<EvalVal> {
-    let num1 = node.token_values[0].eval();
-    let num2 = node.token_values[2].eval();
+//blocks dont get their own scope, a variable set in an if or a loop is still there after it
+pub fn Block(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    match node.token_names[1].as_str() {
+        "stmts" => node.token_values[1].eval(env),
+        _ => Ok(Value::Null)
+    }
+}
+
+//if and elif: the condition, the block and maybe an elif or else after it
+pub fn If(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    if node.token_values[1].eval(env)?.truthy() {
+        return node.token_values[2].eval(env);
+    }
 
-    let output = num1.unwrap().num.unwrap() / num2.unwrap().num.unwrap();
+    match node.token_names.get(3).map(|name| name.as_str()) {
+        Some("orelse") => node.token_values[3].eval(env),
+        _ => Ok(Value::Null)
+    }
+}
+
+pub fn Else(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    node.token_values[1].eval(env)
+}
 
-    Some(
-        EvalVal {  
-            num: Some(output),
-            valtype: ValType::NUM,
-            ..Default::default()
+//runs the body once, returns whether the loop goes on
+fn loop_body(body: &Node, env: &mut Environment) -> Result<bool, RuntimeError> {
+    env.loops += 1;
+    let result = body.eval(env);
+    env.loops -= 1;
+    result?;
+
+    match env.control {
+        Control::Normal => Ok(true),
+        Control::Continue => {
+            env.control = Control::Normal;
+            Ok(true)
+        }
+        Control::Break => {
+            env.control = Control::Normal;
+            Ok(false)
         }
-    )
+        //left for the call to pick up
+        Control::Return(_) => Ok(false)
+    }
 }
 
-pub fn Num(node: Node) -> Option<EvalVal> {
-    let output = node.token_values[0].token.as_ref().unwrap().value().parse::<f32>().unwrap();
+pub fn While(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    while node.token_values[1].eval(env)?.truthy() {
+        if !loop_body(&node.token_values[2], env)? {
+            break;
+        }
+    }
 
-    Some(
-        EvalVal {  
-            num: Some(output),
-            valtype: ValType::NUM,
-            ..Default::default()
+    Ok(Value::Null)
+}
+
+//for name in iterable, the name is set in the surrounding scope like with an assignment.
+//for a, b in iterable unpacks every item, like the lists from map.items()
+pub fn For(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let targets = argument_nodes(&node.token_values[1]);
+    let mut names = vec![];
+    for target in &targets {
+        if target.class_name != "Name" {
+            return Err(RuntimeError::new(ErrorKind::TypeError, &format!("cant assign to {:?}", target), target));
+        }
+        names.push(target.token_values[0].token.as_ref().unwrap().value());
+    }
+
+    let iterable = node.token_values[3].eval(env)?;
+    for item in located(&node.token_values[3], operators::items(&iterable))? {
+        if names.len() == 1 {
+            env.set(&names[0], item);
+        } else {
+            let values = located(&node.token_values[3], operators::items(&item))?;
+            let values = located(&node.token_values[1], operators::unpack(values, names.len()))?;
+            for (name, value) in names.iter().zip(values) {
+                env.set(name, value);
+            }
+        }
+
+        if !loop_body(&node.token_values[4], env)? {
+            break;
         }
-    )
+    }
+
+    Ok(Value::Null)
 }
 
+pub fn Break(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    if env.loops == 0 {
+        return Err(RuntimeError::new(ErrorKind::SyntaxError, "break outside of a loop", node));
+    }
 
+    env.control = Control::Break;
+    Ok(Value::Null)
+}
 
-pub fn String(node: Node) -> Option<EvalVal> {
-    let output = node.token_values[0].token.as_ref().unwrap().value();
+pub fn Continue(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    if env.loops == 0 {
+        return Err(RuntimeError::new(ErrorKind::SyntaxError, "continue outside of a loop", node));
+    }
+
+    env.control = Control::Continue;
+    Ok(Value::Null)
+}
 
-    Some(
-        EvalVal {  
-            string: Some(output),
-            valtype: ValType::STRING,
-            ..Default::default()
+pub fn ExprStmt(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    node.token_values[0].eval(env)
+}
+
+pub fn Name(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let name = node.token_values[0].token.as_ref().unwrap().value();
+
+    match env.get(&name) {
+        Some(value) => Ok(value),
+        None => Err(RuntimeError::new(ErrorKind::UndefinedName, &format!("name {name} is not defined"), node))
+    }
+}
+
+//where an assignment writes to, the container and the index or name are evaluated once
+enum Target {
+    Name(String),
+    Item(Value, Value),
+    Field(Value, String)
+}
+
+fn target(node: &Node, env: &mut Environment) -> Result<Target, RuntimeError> {
+    match node.class_name.as_str() {
+        "Name" => Ok(Target::Name(node.token_values[0].token.as_ref().unwrap().value())),
+        "Index" => Ok(Target::Item(node.token_values[0].eval(env)?, node.token_values[2].eval(env)?)),
+        "Attribute" => {
+            let name = attribute_name(node)?;
+            Ok(Target::Field(node.token_values[0].eval(env)?, name))
         }
-    )
+        _ => Err(RuntimeError::new(ErrorKind::TypeError, &format!("cant assign to {:?}", node), node))
+    }
+}
+
+fn load(node: &Node, target: &Target, env: &mut Environment) -> Result<Value, RuntimeError> {
+    match target {
+        Target::Name(name) => match env.get(name) {
+            Some(value) => Ok(value),
+            None => Err(RuntimeError::new(ErrorKind::UndefinedName, &format!("name {name} is not defined"), node))
+        },
+        Target::Item(container, index) => located(node, operators::item(container, index)),
+        Target::Field(value, name) => located(node, operators::attribute(value, name))
+    }
 }
 
+fn store(node: &Node, target: Target, value: Value, env: &mut Environment) -> Result<(), RuntimeError> {
+    match target {
+        Target::Name(name) => {
+            env.set(&name, value);
+            Ok(())
+        }
+        Target::Item(container, index) => located(node, operators::store_item(&container, index, value)),
+        Target::Field(container, name) => located(node, operators::store_attribute(&container, &name, value))
+    }
+}
+
+//name = value, container[index] = value or value.name = value, the value is evaluated before the target
+pub fn Assign(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let target_node = &node.token_values[0];
+    let value = node.token_values[2].eval(env)?;
+    let target = target(target_node, env)?;
+    store(target_node, target, value, env)?;
+
+    Ok(Value::Null)
+}
+
+//target op= value, the target is evaluated before the value like in python
+pub fn AugAssign(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let target_node = &node.token_values[0];
+    let target = target(target_node, env)?;
+    let current = load(target_node, &target, env)?;
+    let value = node.token_values[2].eval(env)?;
+
+    let result = located(node, operators::augmented(augmented_operator(&node.token_names[1]), current, value))?;
+    store(target_node, target, result, env)?;
+
+    Ok(Value::Null)
+}
+
+//the binary operator of an augmented assignment token, += is Add
+pub fn augmented_operator(token_name: &str) -> &'static str {
+    match token_name {
+        "PLUSEQUAL" => "Add",
+        "MINEQUAL" => "Minus",
+        "STAREQUAL" => "Mul",
+        "SLASHEQUAL" => "Div",
+        "DOUBLESLASHEQUAL" => "FloorDiv",
+        "PERCENTEQUAL" => "Mod",
+        "DOUBLESTAREQUAL" => "Pow",
+        "LEFTSHIFTEQUAL" => "LeftShift",
+        "RIGHTSHIFTEQUAL" => "RightShift",
+        "AMPEREQUAL" => "BitAnd",
+        "CIRCUMFLEXEQUAL" => "BitXor",
+        _ => "BitOr"
+    }
+}
+
+//(name := value) sets the name and gives back the value
+pub fn NamedExpr(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let target = &node.token_values[0];
+    if target.class_name != "Name" {
+        return Err(RuntimeError::new(ErrorKind::SyntaxError, "only a name can be assigned with :=", target));
+    }
+
+    let value = node.token_values[2].eval(env)?;
+    env.set(&target.token_values[0].token.as_ref().unwrap().value(), value.clone());
+
+    Ok(value)
+}
+
+//the nodes of an argument list, Args nodes nest to the left
+pub fn argument_nodes(node: &Node) -> Vec<&Node> {
+    if node.class_name != "Args" {
+        return vec![node];
+    }
+
+    let mut nodes = argument_nodes(&node.token_values[0]);
+    nodes.push(&node.token_values[2]);
+    nodes
+}
+
+fn arguments(node: &Node, env: &mut Environment) -> Result<Vec<Value>, RuntimeError> {
+    argument_nodes(node).into_iter().map(|argument| argument.eval(env)).collect()
+}
+
+pub fn Print(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let values = match node.token_values.len() {
+        3 => vec![],
+        _ => arguments(&node.token_values[2], env)?
+    };
+    let text: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+
+    writeln!(env.writer, "{}", text.join(" ")).map_err(|error| RuntimeError::new(ErrorKind::IoError, &format!("couldnt print: {error}"), node))?;
+
+    Ok(Value::Null)
+}
+
+pub fn Input(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    if node.token_values.len() == 4 {
+        let prompt = node.token_values[2].eval(env)?;
+        write!(env.writer, "{prompt}").and_then(|_| env.writer.flush()).map_err(|error| RuntimeError::new(ErrorKind::IoError, &format!("couldnt print: {error}"), node))?;
+    }
+
+    let mut line = String::new();
+    env.reader.read_line(&mut line).map_err(|error| RuntimeError::new(ErrorKind::IoError, &format!("couldnt read input: {error}"), node))?;
+
+    Ok(Value::Str(line.trim_end_matches(['\n', '\r']).to_string()))
+}
+
+//calls nested deeper than this are stopped with a RecursionError
+pub const MAX_DEPTH: usize = 1000;
+
+//fn name(a, b) { ... }, the signature is parsed as a call
+pub fn FunctionDef(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let signature = &node.token_values[1];
+    if signature.class_name != "Call" || signature.token_values[0].class_name != "Name" {
+        return Err(RuntimeError::new(ErrorKind::SyntaxError, "expected a name and parameters after fn", signature));
+    }
+
+    let parameters = match signature.token_values.len() {
+        3 => vec![],
+        _ => argument_nodes(&signature.token_values[2])
+    };
+
+    let mut params: Vec<String> = vec![];
+    for parameter in parameters {
+        if parameter.class_name != "Name" {
+            return Err(RuntimeError::new(ErrorKind::SyntaxError, "parameters have to be names", parameter));
+        }
+
+        let param = parameter.token_values[0].token.as_ref().unwrap().value();
+        if params.contains(&param) {
+            return Err(RuntimeError::new(ErrorKind::SyntaxError, &format!("duplicate parameter {param}"), parameter));
+        }
+        params.push(param);
+    }
+
+    let name = signature.token_values[0].token_values[0].token.as_ref().unwrap().value();
+    let function = Function {
+        name: name.clone(),
+        params,
+        body: node.token_values[node.token_values.len() - 2].clone(),
+        closure: env.scope.clone()
+    };
+    env.set(&name, Value::Function(Rc::new(function)));
+
+    Ok(Value::Null)
+}
+
+pub fn Call(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let callee = node.token_values[0].eval(env)?;
+    let values = match node.token_values.len() {
+        3 => vec![],
+        _ => arguments(&node.token_values[2], env)?
+    };
+
+    call(node, callee, values, env)
+}
+
+//runs a function in a new scope inside the one it was defined in
+pub fn call(node: &Node, callee: Value, values: Vec<Value>, env: &mut Environment) -> Result<Value, RuntimeError> {
+    let function = match callee {
+        Value::Function(function) => function,
+        Value::NativeFunction(function) => {
+            let mut values = values;
+            if let Some(receiver) = function.receiver {
+                values.insert(0, *receiver);
+            }
+            return located(node, (function.function)(env, values));
+        }
+        other => return Err(RuntimeError::new(ErrorKind::TypeError, &format!("{} is not callable", other.type_name()), node))
+    };
+
+    if values.len() != function.params.len() {
+        return Err(RuntimeError::new(
+            ErrorKind::TypeError,
+            &format!("{}() takes {} arguments but {} were given", function.name, function.params.len(), values.len()),
+            node
+        ));
+    }
+    if env.depth >= MAX_DEPTH {
+        return Err(RuntimeError::new(ErrorKind::RecursionError, &format!("maximum recursion depth of {MAX_DEPTH} exceeded"), node));
+    }
+
+    let mut scope = Scope::new(Some(function.closure.clone()));
+    for (param, value) in function.params.iter().zip(values) {
+        scope.set(param, value);
+    }
+
+    //break and continue cant reach the loops of the caller
+    let caller = std::mem::replace(&mut env.scope, Rc::new(RefCell::new(scope)));
+    let loops = std::mem::replace(&mut env.loops, 0);
+    env.depth += 1;
+
+    let result = function.body.eval(env);
+
+    env.depth -= 1;
+    env.loops = loops;
+    env.scope = caller;
+    let control = std::mem::replace(&mut env.control, Control::Normal);
+    result?;
+
+    match control {
+        Control::Return(value) => Ok(value),
+        _ => Ok(Value::Null)
+    }
+}
+
+pub fn Return(node: &Node, env: &mut Environment) -> Result<Value, RuntimeError> {
+    if env.depth == 0 {
+        return Err(RuntimeError::new(ErrorKind::SyntaxError, "return outside of a function", node));
+    }
+
+    let value = match node.token_values.len() {
+        3 => node.token_values[1].eval(env)?,
+        _ => Value::Null
+    };
+    env.control = Control::Return(value);
+
+    Ok(Value::Null)
+}
@@ -0,0 +1,443 @@
+use std::cmp::Ordering;
+use std::fmt::Display;
+
+//an int of any size: the sign and the magnitude in base 2^32 digits, the lowest digit first.
+//there are no leading zero digits and zero has no digits and is never negative, so equal
+//numbers always look the same
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    digits: Vec<u32>
+}
+
+fn trim(digits: &mut Vec<u32>) {
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+}
+
+fn compare_magnitude(digits1: &[u32], digits2: &[u32]) -> Ordering {
+    digits1.len().cmp(&digits2.len()).then_with(|| digits1.iter().rev().cmp(digits2.iter().rev()))
+}
+
+fn add_magnitude(digits1: &[u32], digits2: &[u32]) -> Vec<u32> {
+    let mut digits = vec![];
+    let mut carry = 0u64;
+    for index in 0..digits1.len().max(digits2.len()) {
+        let sum = *digits1.get(index).unwrap_or(&0) as u64 + *digits2.get(index).unwrap_or(&0) as u64 + carry;
+        digits.push(sum as u32);
+        carry = sum >> 32;
+    }
+    if carry > 0 {
+        digits.push(carry as u32);
+    }
+
+    digits
+}
+
+//digits1 has to be at least as large as digits2
+fn sub_magnitude(digits1: &[u32], digits2: &[u32]) -> Vec<u32> {
+    let mut digits = vec![];
+    let mut borrow = 0i64;
+    for (index, digit) in digits1.iter().enumerate() {
+        let mut difference = *digit as i64 - *digits2.get(index).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if difference < 0 {
+            difference += 1 << 32;
+            borrow = 1;
+        }
+        digits.push(difference as u32);
+    }
+    trim(&mut digits);
+
+    digits
+}
+
+fn mul_magnitude(digits1: &[u32], digits2: &[u32]) -> Vec<u32> {
+    let mut digits = vec![0u32; digits1.len() + digits2.len()];
+    for (index1, digit1) in digits1.iter().enumerate() {
+        let mut carry = 0u64;
+        for (index2, digit2) in digits2.iter().enumerate() {
+            let product = *digit1 as u64 * *digit2 as u64 + digits[index1 + index2] as u64 + carry;
+            digits[index1 + index2] = product as u32;
+            carry = product >> 32;
+        }
+        digits[index1 + digits2.len()] = carry as u32;
+    }
+    trim(&mut digits);
+
+    digits
+}
+
+fn div_rem_small(digits: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; digits.len()];
+    let mut remainder = 0u64;
+    for index in (0..digits.len()).rev() {
+        let current = (remainder << 32) | digits[index] as u64;
+        quotient[index] = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+    trim(&mut quotient);
+
+    (quotient, remainder as u32)
+}
+
+fn shl_magnitude(digits: &[u32], bits: usize) -> Vec<u32> {
+    if digits.is_empty() {
+        return vec![];
+    }
+
+    let mut shifted = vec![0u32; bits / 32];
+    let mut carry = 0u32;
+    for digit in digits {
+        shifted.push((digit << (bits % 32)) | carry);
+        carry = if bits.is_multiple_of(32) { 0 } else { digit >> (32 - bits % 32) };
+    }
+    shifted.push(carry);
+    trim(&mut shifted);
+
+    shifted
+}
+
+fn shr_magnitude(digits: &[u32], bits: usize) -> Vec<u32> {
+    if bits / 32 >= digits.len() {
+        return vec![];
+    }
+
+    let mut shifted = vec![];
+    for index in bits / 32..digits.len() {
+        let high = if bits.is_multiple_of(32) { 0 } else { digits.get(index + 1).map(|digit| digit << (32 - bits % 32)).unwrap_or(0) };
+        shifted.push((digits[index] >> (bits % 32)) | high);
+    }
+    trim(&mut shifted);
+
+    shifted
+}
+
+//schoolbook long division one bit at a time, slow for huge numbers but simple
+fn div_rem_magnitude(digits1: &[u32], digits2: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if digits2.len() == 1 {
+        let (quotient, remainder) = div_rem_small(digits1, digits2[0]);
+        let mut remainder = vec![remainder];
+        trim(&mut remainder);
+        return (quotient, remainder);
+    }
+    if compare_magnitude(digits1, digits2) == Ordering::Less {
+        return (vec![], digits1.to_vec());
+    }
+
+    let mut quotient = vec![0u32; digits1.len()];
+    let mut remainder: Vec<u32> = vec![];
+    for bit in (0..digits1.len() * 32).rev() {
+        remainder = shl_magnitude(&remainder, 1);
+        if digits1[bit / 32] >> (bit % 32) & 1 == 1 {
+            if remainder.is_empty() {
+                remainder.push(1);
+            } else {
+                remainder[0] |= 1;
+            }
+        }
+        if compare_magnitude(&remainder, digits2) != Ordering::Less {
+            remainder = sub_magnitude(&remainder, digits2);
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    trim(&mut quotient);
+
+    (quotient, remainder)
+}
+
+impl BigInt {
+    fn new(negative: bool, mut digits: Vec<u32>) -> BigInt {
+        trim(&mut digits);
+        BigInt {
+            negative: negative && !digits.is_empty(),
+            digits
+        }
+    }
+
+    pub fn from_i64(int: i64) -> BigInt {
+        let magnitude = int.unsigned_abs();
+        BigInt::new(int < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+
+    //the exact value of a float without a fraction
+    pub fn from_f64(float: f64) -> BigInt {
+        if float.abs() < 9.2e18 {
+            return BigInt::from_i64(float as i64);
+        }
+
+        let bits = float.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as usize - 1075;
+        let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+        let magnitude = BigInt::from_i64(mantissa as i64).shl(exponent);
+
+        if float < 0.0 { magnitude.neg() } else { magnitude }
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.digits.len() > 2 {
+            return None;
+        }
+
+        let magnitude = self.digits.iter().rev().fold(0u64, |magnitude, digit| (magnitude << 32) | *digit as u64);
+        if self.negative {
+            0i64.checked_sub_unsigned(magnitude)
+        } else {
+            i64::try_from(magnitude).ok()
+        }
+    }
+
+    //rounds if the int has more than 53 bits, too large ones are infinite
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self.digits.iter().rev().fold(0.0, |magnitude, digit| magnitude * 4294967296.0 + *digit as f64);
+        if self.negative { -magnitude } else { magnitude }
+    }
+
+    //decimal digits with an optional - in front
+    pub fn parse(text: &str) -> Option<BigInt> {
+        let (negative, text) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text)
+        };
+        if text.is_empty() || !text.chars().all(|char| char.is_ascii_digit()) {
+            return None;
+        }
+
+        let mut digits = vec![];
+        for char in text.chars() {
+            digits = add_magnitude(&mul_magnitude(&digits, &[10]), &[char.to_digit(10).unwrap()]);
+        }
+
+        Some(BigInt::new(negative, digits))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn is_one(&self) -> bool {
+        !self.negative && self.digits == [1]
+    }
+
+    pub fn neg(&self) -> BigInt {
+        BigInt::new(!self.negative, self.digits.clone())
+    }
+
+    pub fn abs(&self) -> BigInt {
+        BigInt::new(false, self.digits.clone())
+    }
+
+    pub fn add(&self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, add_magnitude(&self.digits, &other.digits));
+        }
+
+        match compare_magnitude(&self.digits, &other.digits) {
+            Ordering::Less => BigInt::new(other.negative, sub_magnitude(&other.digits, &self.digits)),
+            _ => BigInt::new(self.negative, sub_magnitude(&self.digits, &other.digits))
+        }
+    }
+
+    pub fn sub(&self, other: &BigInt) -> BigInt {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &BigInt) -> BigInt {
+        BigInt::new(self.negative != other.negative, mul_magnitude(&self.digits, &other.digits))
+    }
+
+    //rounds towards zero like rust, the remainder has the sign of self. other cant be zero
+    pub fn div_rem(&self, other: &BigInt) -> (BigInt, BigInt) {
+        let (quotient, remainder) = div_rem_magnitude(&self.digits, &other.digits);
+        (BigInt::new(self.negative != other.negative, quotient), BigInt::new(self.negative, remainder))
+    }
+
+    //rounds towards negative infinity like python, the remainder has the sign of other
+    pub fn div_floor(&self, other: &BigInt) -> (BigInt, BigInt) {
+        let (quotient, remainder) = self.div_rem(other);
+        if !remainder.is_zero() && remainder.negative != other.negative {
+            return (quotient.sub(&BigInt::from_i64(1)), remainder.add(other));
+        }
+
+        (quotient, remainder)
+    }
+
+    pub fn pow(&self, exponent: u32) -> BigInt {
+        let mut result = BigInt::from_i64(1);
+        let mut base = self.clone();
+        let mut exponent = exponent;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul(&base);
+            }
+            base = base.mul(&base);
+            exponent >>= 1;
+        }
+
+        result
+    }
+
+    pub fn gcd(&self, other: &BigInt) -> BigInt {
+        let (mut a, mut b) = (self.abs(), other.abs());
+        while !b.is_zero() {
+            let remainder = a.div_rem(&b).1;
+            a = b;
+            b = remainder;
+        }
+
+        a
+    }
+
+    pub fn shl(&self, bits: usize) -> BigInt {
+        BigInt::new(self.negative, shl_magnitude(&self.digits, bits))
+    }
+
+    //rounds towards negative infinity, so -1 >> n stays -1
+    pub fn shr(&self, bits: usize) -> BigInt {
+        if !self.negative {
+            return BigInt::new(false, shr_magnitude(&self.digits, bits));
+        }
+
+        let one = BigInt::from_i64(1);
+        BigInt::new(false, shr_magnitude(&self.abs().sub(&one).digits, bits)).add(&one).neg()
+    }
+
+    //the lowest length digits of the two's complement, negative numbers are infinitely many ones on the left
+    fn twos_complement(&self, length: usize) -> Vec<u32> {
+        let magnitude = if self.negative { self.abs().sub(&BigInt::from_i64(1)).digits } else { self.digits.clone() };
+        (0..length).map(|index| {
+            let digit = *magnitude.get(index).unwrap_or(&0);
+            if self.negative { !digit } else { digit }
+        }).collect()
+    }
+
+    fn from_twos_complement(digits: Vec<u32>) -> BigInt {
+        if digits.last().is_some_and(|digit| digit >> 31 == 1) {
+            let inverted: Vec<u32> = digits.iter().map(|digit| !digit).collect();
+            return BigInt::new(true, add_magnitude(&inverted, &[1]));
+        }
+
+        BigInt::new(false, digits)
+    }
+
+    //& | and ^ digit by digit, as if both were in two's complement
+    pub fn bitwise(&self, other: &BigInt, operator: fn(u32, u32) -> u32) -> BigInt {
+        let length = self.digits.len().max(other.digits.len()) + 1;
+        let digits = self.twos_complement(length).into_iter().zip(other.twos_complement(length)).map(|(digit1, digit2)| operator(digit1, digit2)).collect();
+
+        BigInt::from_twos_complement(digits)
+    }
+
+    //~x is -(x + 1)
+    pub fn invert(&self) -> BigInt {
+        self.add(&BigInt::from_i64(1)).neg()
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitude(&self.digits, &other.digits),
+            (true, true) => compare_magnitude(&other.digits, &self.digits)
+        }
+    }
+}
+
+//converted nine decimal digits at a time
+impl Display for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        let mut chunks = vec![];
+        let mut digits = self.digits.clone();
+        while !digits.is_empty() {
+            let (quotient, remainder) = div_rem_small(&digits, 1_000_000_000);
+            chunks.push(remainder);
+            digits = quotient;
+        }
+
+        let mut text = if self.negative { "-".to_string() } else { "".to_string() };
+        text += &chunks.pop().unwrap().to_string();
+        for chunk in chunks.iter().rev() {
+            text += &format!("{:09}", chunk);
+        }
+
+        write!(f, "{}", text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(text: &str) -> BigInt {
+        BigInt::parse(text).unwrap()
+    }
+
+    #[test]
+    fn carries() {
+        let max = BigInt::from_i64(u32::MAX as i64);
+        assert_eq!(max.add(&BigInt::from_i64(1)).digits, [0, 1]);
+        assert_eq!(int("18446744073709551615").add(&int("1")).to_string(), "18446744073709551616");
+        assert_eq!(int("18446744073709551616").sub(&int("1")).to_string(), "18446744073709551615");
+        assert_eq!(max.mul(&max).to_string(), "18446744065119617025");
+        assert_eq!(int("340282366920938463463374607431768211456").div_rem(&int("18446744073709551617")).0.to_string(), "18446744073709551615");
+        assert_eq!(int("2").pow(100).to_string(), "1267650600228229401496703205376");
+    }
+
+    #[test]
+    fn signs() {
+        assert_eq!(int("-5").add(&int("3")).to_string(), "-2");
+        assert_eq!(int("5").add(&int("-8")).to_string(), "-3");
+        assert_eq!(int("-5").mul(&int("-3")).to_string(), "15");
+
+        //rust rounds towards zero, python towards negative infinity
+        let (quotient, remainder) = int("-7").div_rem(&int("2"));
+        assert_eq!((quotient.to_string(), remainder.to_string()), ("-3".to_string(), "-1".to_string()));
+        let (quotient, remainder) = int("-7").div_floor(&int("2"));
+        assert_eq!((quotient.to_string(), remainder.to_string()), ("-4".to_string(), "1".to_string()));
+        let (quotient, remainder) = int("7").div_floor(&int("-2"));
+        assert_eq!((quotient.to_string(), remainder.to_string()), ("-4".to_string(), "-1".to_string()));
+
+        assert_eq!(int("-1").shr(10).to_string(), "-1");
+        assert_eq!(int("-5").bitwise(&int("3"), |a, b| a & b).to_string(), "3");
+        assert_eq!(int("5").invert().to_string(), "-6");
+        assert!(int("-100000000000000000000") < int("-1"));
+        assert!(int("-1") < int("100000000000000000000"));
+    }
+
+    //zero is never negative and there are no leading zero digits, so equal numbers are equal
+    #[test]
+    fn normalization() {
+        assert_eq!(int("-0"), int("0"));
+        assert!(!int("-0").is_negative());
+        assert_eq!(int("5").sub(&int("5")), BigInt::from_i64(0));
+        assert_eq!(int("18446744073709551616").sub(&int("18446744073709551615")).digits, [1]);
+        assert_eq!(int("000123"), int("123"));
+    }
+
+    #[test]
+    fn i64_bounds() {
+        assert_eq!(BigInt::from_i64(i64::MIN).to_string(), "-9223372036854775808");
+        assert_eq!(BigInt::from_i64(i64::MIN).to_i64(), Some(i64::MIN));
+        assert_eq!(int("9223372036854775807").to_i64(), Some(i64::MAX));
+        assert_eq!(int("9223372036854775808").to_i64(), None);
+        assert_eq!(int("-9223372036854775809").to_i64(), None);
+        assert_eq!(BigInt::from_f64(1e20).to_string(), "100000000000000000000");
+    }
+}
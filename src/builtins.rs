@@ -0,0 +1,226 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::rc::Rc;
+use crate::environment::Environment;
+use crate::error::ErrorKind;
+use crate::rational::Rational;
+use crate::value::{Map, NativeFunction, Value};
+
+pub type NativeResult = Result<Value, (ErrorKind, String)>;
+
+//the container a method was called on and the arguments after it
+type Receiver<T> = (Rc<RefCell<T>>, Vec<Value>);
+
+fn native(name: &str, function: fn(&mut Environment, Vec<Value>) -> NativeResult, receiver: Option<Value>) -> NativeFunction {
+    NativeFunction {
+        name: name.to_string(),
+        function,
+        receiver: receiver.map(Box::new)
+    }
+}
+
+//the functions every program starts with
+pub fn globals() -> Vec<NativeFunction> {
+    vec![
+        native("len", len, None),
+        native("rational", rational, None)
+    ]
+}
+
+//value.name for the built in types, the value is passed to the function before the arguments
+pub fn method(value: &Value, name: &str) -> Option<NativeFunction> {
+    let function = match (value, name) {
+        (Value::List(_), "append") => list_append,
+        (Value::List(_), "pop") => list_pop,
+        (Value::List(_), "insert") => list_insert,
+        (Value::List(_), "sort") => list_sort,
+        (Value::Map(_), "keys") => map_keys,
+        (Value::Map(_), "values") => map_values,
+        (Value::Map(_), "items") => map_items,
+        (Value::Map(_), "get") => map_get,
+        _ => return None
+    };
+
+    Some(native(name, function, Some(value.clone())))
+}
+
+fn expect_arguments(name: &str, arguments: &[Value], min: usize, max: usize) -> Result<(), (ErrorKind, String)> {
+    if arguments.len() >= min && arguments.len() <= max {
+        return Ok(());
+    }
+
+    let expected = if min == max { min.to_string() } else { format!("{min} to {max}") };
+    Err((ErrorKind::TypeError, format!("{name}() takes {expected} arguments but {} were given", arguments.len())))
+}
+
+//python style, a negative index counts from the end
+pub fn position(index: &Value, length: usize) -> Result<usize, (ErrorKind, String)> {
+    let index = match index {
+        Value::Int(int) => *int,
+        Value::BigInt(_) => return Err((ErrorKind::IndexError, format!("index {index} out of range for length {length}"))),
+        other => return Err((ErrorKind::TypeError, format!("indices must be int, not {}", other.type_name())))
+    };
+    let position = if index < 0 { index + length as i64 } else { index };
+
+    if position < 0 || position >= length as i64 {
+        return Err((ErrorKind::IndexError, format!("index {index} out of range for length {length}")));
+    }
+
+    Ok(position as usize)
+}
+
+//lists and maps can change, so they cant be keys, and nan isnt equal to itself so it
+//could never be found again
+pub fn hashable(key: &Value) -> Result<(), (ErrorKind, String)> {
+    match key {
+        Value::List(_) | Value::Map(_) => Err((ErrorKind::TypeError, format!("{} cant be used as a key", key.type_name()))),
+        Value::Float(float) if float.is_nan() => Err((ErrorKind::ValueError, "nan cant be used as a key".to_string())),
+        _ => Ok(())
+    }
+}
+
+fn len(_env: &mut Environment, arguments: Vec<Value>) -> NativeResult {
+    expect_arguments("len", &arguments, 1, 1)?;
+
+    match &arguments[0] {
+        Value::Str(string) => Ok(Value::Int(string.chars().count() as i64)),
+        Value::List(list) => Ok(Value::Int(list.borrow().len() as i64)),
+        Value::Map(map) => Ok(Value::Int(map.borrow().len() as i64)),
+        other => Err((ErrorKind::TypeError, format!("{} has no length", other.type_name())))
+    }
+}
+
+//rational(3), rational(1, 3), rational("0.1") or rational("1/3"), a float is converted exactly
+fn rational(_env: &mut Environment, arguments: Vec<Value>) -> NativeResult {
+    expect_arguments("rational", &arguments, 1, 2)?;
+
+    let numerator = match &arguments[0] {
+        Value::Str(text) => Rational::parse(text.trim()).ok_or_else(|| (ErrorKind::ValueError, format!("invalid rational {}", arguments[0].repr())))?,
+        Value::Float(float) => Rational::from_f64(*float).ok_or_else(|| (ErrorKind::ValueError, format!("{float} cant be a rational")))?,
+        other => other.rational().ok_or_else(|| (ErrorKind::TypeError, format!("rational() needs a number or a string, not {}", other.type_name())))?
+    };
+    if arguments.len() == 1 {
+        return Ok(Value::Rational(numerator));
+    }
+
+    let denominator = arguments[1].rational().ok_or_else(|| (ErrorKind::TypeError, format!("the denominator must be an int or a rational, not {}", arguments[1].type_name())))?;
+    match numerator.div(&denominator) {
+        Some(quotient) => Ok(Value::Rational(quotient)),
+        None => Err((ErrorKind::ZeroDivision, "rational with a zero denominator".to_string()))
+    }
+}
+
+//the list of a list method and the arguments after it
+fn list_arguments(name: &str, mut arguments: Vec<Value>, min: usize, max: usize) -> Result<Receiver<Vec<Value>>, (ErrorKind, String)> {
+    let list = match arguments.remove(0) {
+        Value::List(list) => list,
+        other => return Err((ErrorKind::TypeError, format!("{name}() needs a list, not {}", other.type_name())))
+    };
+    expect_arguments(name, &arguments, min, max)?;
+
+    Ok((list, arguments))
+}
+
+fn list_append(_env: &mut Environment, arguments: Vec<Value>) -> NativeResult {
+    let (list, mut arguments) = list_arguments("append", arguments, 1, 1)?;
+    list.borrow_mut().push(arguments.remove(0));
+
+    Ok(Value::Null)
+}
+
+//removes and returns the last item, or the one at the given index
+fn list_pop(_env: &mut Environment, arguments: Vec<Value>) -> NativeResult {
+    let (list, arguments) = list_arguments("pop", arguments, 0, 1)?;
+    let mut list = list.borrow_mut();
+
+    if list.is_empty() {
+        return Err((ErrorKind::IndexError, "pop from empty list".to_string()));
+    }
+
+    let index = match arguments.first() {
+        Some(index) => position(index, list.len())?,
+        None => list.len() - 1
+    };
+
+    Ok(list.remove(index))
+}
+
+//inserts before the index, out of range indices insert at the start or the end
+fn list_insert(_env: &mut Environment, arguments: Vec<Value>) -> NativeResult {
+    let (list, mut arguments) = list_arguments("insert", arguments, 2, 2)?;
+    let mut list = list.borrow_mut();
+
+    let length = list.len() as i64;
+    let index = match &arguments[0] {
+        Value::Int(int) if *int < 0 => (int + length).max(0),
+        Value::Int(int) => (*int).min(length),
+        other => return Err((ErrorKind::TypeError, format!("indices must be int, not {}", other.type_name())))
+    };
+
+    list.insert(index as usize, arguments.remove(1));
+    Ok(Value::Null)
+}
+
+//sorts in place, the items have to be comparable with each other
+fn list_sort(_env: &mut Environment, arguments: Vec<Value>) -> NativeResult {
+    let (list, _) = list_arguments("sort", arguments, 0, 0)?;
+    let mut list = list.borrow_mut();
+
+    let mut error = None;
+    list.sort_by(|value1, value2| match value1.order(value2) {
+        Some(ordering) => ordering,
+        None => {
+            error.get_or_insert((ErrorKind::TypeError, format!("cant compare {} and {}", value1.type_name(), value2.type_name())));
+            Ordering::Equal
+        }
+    });
+
+    match error {
+        Some(error) => Err(error),
+        None => Ok(Value::Null)
+    }
+}
+
+//the map of a map method and the arguments after it
+fn map_arguments(name: &str, mut arguments: Vec<Value>, min: usize, max: usize) -> Result<Receiver<Map>, (ErrorKind, String)> {
+    let map = match arguments.remove(0) {
+        Value::Map(map) => map,
+        other => return Err((ErrorKind::TypeError, format!("{name}() needs a map, not {}", other.type_name())))
+    };
+    expect_arguments(name, &arguments, min, max)?;
+
+    Ok((map, arguments))
+}
+
+fn map_keys(_env: &mut Environment, arguments: Vec<Value>) -> NativeResult {
+    let (map, _) = map_arguments("keys", arguments, 0, 0)?;
+    let keys = map.borrow().entries().iter().map(|(key, _)| key.clone()).collect();
+
+    Ok(Value::list(keys))
+}
+
+fn map_values(_env: &mut Environment, arguments: Vec<Value>) -> NativeResult {
+    let (map, _) = map_arguments("values", arguments, 0, 0)?;
+    let values = map.borrow().entries().iter().map(|(_, value)| value.clone()).collect();
+
+    Ok(Value::list(values))
+}
+
+//[key, value] lists in insertion order
+fn map_items(_env: &mut Environment, arguments: Vec<Value>) -> NativeResult {
+    let (map, _) = map_arguments("items", arguments, 0, 0)?;
+    let items = map.borrow().entries().iter().map(|(key, value)| Value::list(vec![key.clone(), value.clone()])).collect();
+
+    Ok(Value::list(items))
+}
+
+//the value of the key, or the default (null if not given) if it is missing
+fn map_get(_env: &mut Environment, arguments: Vec<Value>) -> NativeResult {
+    let (map, mut arguments) = map_arguments("get", arguments, 1, 2)?;
+    let default = if arguments.len() == 2 { arguments.remove(1) } else { Value::Null };
+
+    hashable(&arguments[0])?;
+    let value = map.borrow().get(&arguments[0]).cloned();
+
+    Ok(value.unwrap_or(default))
+}
@@ -0,0 +1,234 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+use crate::error::ErrorKind;
+use crate::lexer::Span;
+use crate::operators::COMPARISONS;
+use crate::value::Value;
+
+//one step of the vm, the operands are indexes into the tables of the Code or jump targets.
+//the comments show the stack before and after, the top is on the right
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instruction {
+    Constant(usize),
+    Null,
+    Pop,
+    //a -> a a
+    Dup,
+    //a b -> a b a b
+    DupTwo,
+    //a b -> b a
+    Swap,
+    //a b c -> c a b
+    RotateThree,
+    //tries the places of the lookup in order, like a name is looked up in the scopes around it
+    Load(usize),
+    StoreLocal(usize),
+    StoreGlobal(usize),
+    Add,
+    Minus,
+    Mul,
+    Div,
+    FloorDiv,
+    Mod,
+    Pow,
+    LeftShift,
+    RightShift,
+    BitAnd,
+    BitXor,
+    BitOr,
+    //+= which extends a list in place
+    InPlaceAdd,
+    Invert,
+    Not,
+    //the comparison in operators::COMPARISONS
+    Compare(usize),
+    //target index -> item
+    Index,
+    //target start end step -> slice, a bound that was left out is null
+    Slice,
+    //value -> value.name, the name is a constant
+    Attribute(usize),
+    //value container index ->
+    StoreIndex,
+    //value container ->
+    StoreAttribute(usize),
+    BuildList(usize),
+    //key value key value ... -> map
+    BuildMap(usize),
+    //fails if the value on top cant be a key, it stays on the stack
+    Hashable,
+    Jump(usize),
+    JumpIfFalse(usize),
+    //keeps the value if it jumps, pops it otherwise, for and and or
+    JumpIfFalseOrPop(usize),
+    JumpIfTrueOrPop(usize),
+    //iterable -> and starts going through its items
+    GetIter,
+    //pushes the next item or drops the iterator and jumps when there are none left
+    ForIter(usize),
+    //drops the iterator of the loop a break leaves
+    PopIterator,
+    //value -> list of its items, the same items a for loop would get
+    Items,
+    //list -> items, the first one on top
+    Unpack(usize),
+    //function arguments... -> result
+    Call(usize),
+    Return,
+    //a function of the Code closing over the locals of the running one
+    MakeFunction(usize),
+    Print(usize),
+    //with a prompt or not
+    Input(bool),
+    //an error the compiler already knows about, raised when it is reached like the tree
+    //walker does, the message is a constant
+    Raise(ErrorKind, usize)
+}
+
+//where a name can be, the places of a Lookup are tried until one of them is set
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Place {
+    Local(usize),
+    //a local of an enclosing function, 0 is the one the function was defined in
+    Enclosing(usize, usize),
+    Global(usize)
+}
+
+#[derive(Debug, Clone)]
+pub struct Lookup {
+    pub name: String,
+    pub places: Vec<Place>
+}
+
+//a compiled function or the program itself, the params are the first locals
+#[derive(Debug, Default)]
+pub struct Code {
+    pub name: String,
+    pub params: usize,
+    pub instructions: Vec<Instruction>,
+    //the chars of the source every instruction came from, for errors
+    pub spans: Vec<Span>,
+    pub constants: Vec<Value>,
+    pub lookups: Vec<Lookup>,
+    pub locals: Vec<String>,
+    pub functions: Vec<Rc<Code>>
+}
+
+//the locals of a running function, shared with the functions defined in it
+pub type Locals = Rc<RefCell<Vec<Option<Value>>>>;
+
+//a function value of the vm, the locals of the functions around its definition, innermost first
+pub struct Closure {
+    pub code: Rc<Code>,
+    pub captured: Vec<Locals>
+}
+
+//the captured locals can hold the closure itself, so only the name is shown
+impl std::fmt::Debug for Closure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Closure").field("name", &self.code.name).finish()
+    }
+}
+
+pub struct Program {
+    pub code: Rc<Code>,
+    pub globals: Vec<String>,
+    pub source: Arc<str>
+}
+
+//the operand of an instruction as it is written in the disassembly
+fn operand(instruction: &Instruction, code: &Code, program: &Program) -> String {
+    match *instruction {
+        Instruction::Constant(index) => format!("{index} ({})", code.constants[index].repr()),
+        Instruction::Load(index) => format!("{index} ({})", code.lookups[index].name),
+        Instruction::StoreLocal(slot) => format!("{slot} ({})", code.locals[slot]),
+        Instruction::StoreGlobal(slot) => format!("{slot} ({})", program.globals[slot]),
+        Instruction::Compare(index) => format!("{index} ({})", COMPARISONS[index]),
+        Instruction::Attribute(index) | Instruction::StoreAttribute(index) => format!("{index} ({})", code.constants[index]),
+        Instruction::MakeFunction(index) => format!("{index} ({})", code.functions[index].name),
+        Instruction::Raise(kind, index) => format!("{kind:?} ({})", code.constants[index]),
+        Instruction::Jump(target) | Instruction::JumpIfFalse(target) | Instruction::JumpIfFalseOrPop(target)
+        | Instruction::JumpIfTrueOrPop(target) | Instruction::ForIter(target) => format!("to {target}"),
+        Instruction::BuildList(count) | Instruction::BuildMap(count) | Instruction::Unpack(count)
+        | Instruction::Call(count) | Instruction::Print(count) => count.to_string(),
+        Instruction::Input(prompt) => prompt.to_string(),
+        _ => String::new()
+    }
+}
+
+fn disassemble_code(code: &Code, program: &Program, output: &mut String) {
+    output.push_str(&format!("== {} ({} params, locals: {}) ==\n", code.name, code.params, code.locals.join(", ")));
+
+    let chars: Vec<char> = program.source.chars().collect();
+    let mut last_line = 0;
+    for (offset, instruction) in code.instructions.iter().enumerate() {
+        //the source line is only written when it changes
+        let start = code.spans[offset].start.min(chars.len());
+        let line = chars[..start].iter().filter(|char| **char == '\n').count() + 1;
+        let line_text = if line == last_line { String::new() } else { line.to_string() };
+        last_line = line;
+
+        let name = format!("{instruction:?}");
+        let name = name.split('(').next().unwrap();
+        let text = format!("{line_text:>4} {offset:>5}  {name:<18}{}", operand(instruction, code, program));
+        output.push_str(text.trim_end());
+        output.push('\n');
+    }
+
+    for function in &code.functions {
+        output.push('\n');
+        disassemble_code(function, program, output);
+    }
+}
+
+//the instructions of the program and then of every function in it
+pub fn disassemble(program: &Program) -> String {
+    let mut output = format!("globals: {}\n", program.globals.join(", "));
+    disassemble_code(&program.code, program, &mut output);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler;
+    use crate::lexer;
+
+    #[test]
+    fn disassembly() {
+        let parser = crate::grammar().unwrap();
+        let source = "x = 1\nfn f(a) { return a + x }\nprint(f(2))\n";
+        let ast = parser.parse(&lexer::lex(source), source).unwrap();
+
+        let expected = "\
+globals: len, rational, x, f
+== <program> (0 params, locals: <result>) ==
+   1     0  Null
+         1  StoreLocal        0 (<result>)
+         2  Constant          0 (1)
+         3  StoreGlobal       2 (x)
+         4  Null
+         5  StoreLocal        0 (<result>)
+   2     6  MakeFunction      0 (f)
+         7  StoreGlobal       3 (f)
+         8  Null
+         9  StoreLocal        0 (<result>)
+   3    10  Load              0 (f)
+        11  Constant          1 (2)
+        12  Call              1
+        13  Print             1
+        14  StoreLocal        0 (<result>)
+   1    15  Load              1 (<result>)
+        16  Return
+
+== f (1 params, locals: a) ==
+   2     0  Load              0 (a)
+         1  Load              1 (x)
+         2  Add
+         3  Return
+         4  Null
+         5  Return
+";
+        assert_eq!(super::disassemble(&compiler::compile(&ast)), expected);
+    }
+}
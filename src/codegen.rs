@@ -0,0 +1,293 @@
+use crate::parser::{is_predicate, is_sequence, Parser};
+
+const KEYWORDS: [&str; 38] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn", "for", "if", "impl",
+    "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "static", "struct", "super", "trait",
+    "true", "type", "unsafe", "use", "where", "while", "yield"
+];
+
+//tokens that carry a value, all other terminals are fixed and left out of the structs
+const VALUE_TOKENS: [&str; 3] = ["NUM", "STRING", "NAME"];
+
+struct Class {
+    name: String,
+    class_name: String,
+    nonterminal: String,
+    pattern: Vec<String>
+}
+
+fn camel_case(name: &str) -> String {
+    name.split('_').filter(|part| !part.is_empty()).map(|part| {
+        let mut chars = part.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+            None => "".to_string()
+        }
+    }).collect()
+}
+
+fn enum_name(nonterminal: &str) -> String {
+    camel_case(nonterminal) + "Node"
+}
+
+//the rule structs get a suffix so a class like String doesnt shadow the std type
+fn struct_name(class: &str) -> String {
+    class.to_string() + "Rule"
+}
+
+//the symbols every pattern of the class starts and ends with are left out of the name, the
+//symbols in between are appended: `expr LSQB COLON expr RSQB` among the Slice rules is SliceColonExpr
+fn variant_name(class_name: &str, pattern: &[String], patterns: &[&Vec<String>]) -> String {
+    let shortest = patterns.iter().map(|other| other.len()).min().unwrap_or(0);
+    let prefix = (0..shortest).take_while(|&position| patterns.iter().all(|other| other[position] == pattern[position])).count();
+    let suffix = (0..shortest - prefix).take_while(|&position| {
+        patterns.iter().all(|other| other[other.len() - 1 - position] == pattern[pattern.len() - 1 - position])
+    }).count();
+
+    let symbols: String = pattern[prefix..pattern.len() - suffix].iter().map(|symbol| camel_case(&symbol.to_lowercase())).collect();
+    class_name.to_string() + &symbols
+}
+
+fn field_name(symbol: &str, occurrence: usize, count: usize) -> String {
+    let name = symbol.to_lowercase();
+    let name = if count > 1 { format!("{name}_{occurrence}") } else { name };
+
+    if KEYWORDS.contains(&name.as_str()) {
+        format!("r#{name}")
+    } else {
+        name
+    }
+}
+
+//generates rust code with an enum per nonterminal and a struct per rule, so a `match` over
+//them stops compiling when a rule is added. the structs are views of a Node with its children
+//by name, meant to be written to src/typed_ast.rs with write_typed_ast
+pub fn typed_ast(parser: &Parser) -> Result<String, String> {
+    let rules: Vec<_> = parser.levels.iter().flat_map(|level| level.rules.iter()).filter(|rule| !rule.group).collect();
+
+    let mut nonterminals: Vec<String> = vec![];
+    for rule in &rules {
+        if !nonterminals.contains(&rule.name) {
+            nonterminals.push(rule.name.clone());
+        }
+    }
+
+    //a group over another nonterminal like `stmt -> stmts` passes that node through unchanged,
+    //so it becomes a variant holding the other enum
+    let mut wrapped: Vec<(String, String)> = vec![];
+    for rule in parser.levels.iter().flat_map(|level| level.rules.iter()).filter(|rule| rule.group) {
+        for symbol in &rule.pattern {
+            if *symbol != rule.name && nonterminals.contains(symbol) && !wrapped.contains(&(rule.name.clone(), symbol.clone())) {
+                wrapped.push((rule.name.clone(), symbol.clone()));
+            }
+        }
+    }
+
+    let mut classes: Vec<Class> = vec![];
+    for rule in &rules {
+        let pattern: Vec<String> = rule.pattern.iter().filter(|symbol| !is_predicate(symbol)).cloned().collect();
+        if classes.iter().any(|class| class.class_name == rule.class_name && class.nonterminal == rule.name && class.pattern == pattern) {
+            continue;
+        }
+
+        classes.push(Class { name: rule.class_name.clone(), class_name: rule.class_name.clone(), nonterminal: rule.name.clone(), pattern });
+    }
+
+    //a class name used with several patterns gets a struct per pattern, named after the
+    //symbols that set it apart from the others. the names dont depend on the order of the
+    //rules, so a rule added in between doesnt rename the ones after it
+    for index in 0..classes.len() {
+        let patterns: Vec<&Vec<String>> = classes.iter().filter(|class| class.class_name == classes[index].class_name).map(|class| &class.pattern).collect();
+        if patterns.len() > 1 {
+            classes[index].name = variant_name(&classes[index].class_name, &classes[index].pattern, &patterns);
+        }
+    }
+    for class in &classes {
+        if classes.iter().filter(|other| other.name == class.name).count() > 1 {
+            return Err(format!("the rules of {} have no symbols to tell them apart by", class.class_name));
+        }
+    }
+
+    let mut code = vec![
+        "//generated from the grammar by codegen::typed_ast, dont edit by hand".to_string(),
+        "//there is a view for every rule, eval and the compiler dont read all of their fields".to_string(),
+        "#![allow(dead_code)]".to_string(),
+        "".to_string(),
+        "use crate::lexer::Token;".to_string(),
+        "use crate::parser::Node;".to_string(),
+        "".to_string()
+    ];
+
+    code.push("//a node of any nonterminal".to_string());
+    code.push("#[derive(Debug, Clone, Copy)]".to_string());
+    code.push("pub enum AnyNode<'a> {".to_string());
+    for nonterminal in &nonterminals {
+        code.push(format!("    {}({}<'a>),", camel_case(nonterminal), enum_name(nonterminal)));
+    }
+    code.push("}".to_string());
+    code.push("".to_string());
+
+    code.push("impl<'a> AnyNode<'a> {".to_string());
+    code.push("    pub fn from_node(node: &'a Node) -> Result<AnyNode<'a>, String> {".to_string());
+    for nonterminal in &nonterminals {
+        code.push(format!("        if let Ok(typed) = {}::from_node(node) {{", enum_name(nonterminal)));
+        code.push(format!("            return Ok(AnyNode::{}(typed));", camel_case(nonterminal)));
+        code.push("        }".to_string());
+    }
+    code.push("        Err(format!(\"no rule for node: {}\", node.class_name))".to_string());
+    code.push("    }".to_string());
+    code.push("}".to_string());
+    code.push("".to_string());
+
+    for nonterminal in &nonterminals {
+        let name = enum_name(nonterminal);
+
+        code.push("#[derive(Debug, Clone, Copy)]".to_string());
+        code.push(format!("pub enum {name}<'a> {{"));
+        for class in classes.iter().filter(|class| class.nonterminal == *nonterminal) {
+            code.push(format!("    {}({}<'a>),", class.name, struct_name(&class.name)));
+        }
+        for (_, inner) in wrapped.iter().filter(|(outer, _)| outer == nonterminal) {
+            code.push(format!("    {}({}<'a>),", camel_case(inner), enum_name(inner)));
+        }
+        code.push("}".to_string());
+        code.push("".to_string());
+
+        code.push(format!("impl<'a> {name}<'a> {{"));
+        code.push(format!("    pub fn from_node(node: &'a Node) -> Result<{name}<'a>, String> {{"));
+        for class in classes.iter().filter(|class| class.nonterminal == *nonterminal) {
+            let pattern: Vec<String> = class.pattern.iter().map(|symbol| format!("\"{symbol}\"")).collect();
+            if is_sequence(&class.pattern, &class.nonterminal) {
+                code.push(format!("        if node.class_name == \"{}\" && node.token_names.len() > 1 && node.token_names.iter().all(|name| name == \"{}\") {{", class.class_name, class.nonterminal));
+            } else {
+                code.push(format!("        if node.class_name == \"{}\" && node.token_names == [{}] {{", class.class_name, pattern.join(", ")));
+            }
+            code.push(format!("            return Ok({name}::{}({}::from_node(node)?));", class.name, struct_name(&class.name)));
+            code.push("        }".to_string());
+        }
+        for (_, inner) in wrapped.iter().filter(|(outer, _)| outer == nonterminal) {
+            code.push(format!("        if let Ok(inner) = {}::from_node(node) {{", enum_name(inner)));
+            code.push(format!("            return Ok({name}::{}(inner));", camel_case(inner)));
+            code.push("        }".to_string());
+        }
+        code.push(format!("        Err(format!(\"not a {nonterminal} node: {{}}\", node.class_name))"));
+        code.push("    }".to_string());
+        code.push("}".to_string());
+        code.push("".to_string());
+    }
+
+    for class in &classes {
+        let name = struct_name(&class.name);
+        let mut fields = vec!["    pub node: &'a Node,".to_string()];
+        let mut conversions = vec!["            node,".to_string()];
+        let mut comment = format!("//{} -> {}", class.pattern.join(" "), class.nonterminal);
+
+        //the nodes of a sequence rule have all the joined nodes as children
+        if is_sequence(&class.pattern, &class.nonterminal) {
+            let field = field_name(&class.nonterminal, 0, 1);
+            fields.push(format!("    pub {field}: &'a [Node],"));
+            conversions.push(format!("            {field}: &node.token_values,"));
+            comment += &format!(", any number of {}", class.nonterminal);
+        }
+
+        for (position, symbol) in class.pattern.iter().enumerate().filter(|_| !is_sequence(&class.pattern, &class.nonterminal)) {
+            let count = class.pattern.iter().filter(|other| *other == symbol).count();
+            let occurrence = class.pattern[..position].iter().filter(|other| *other == symbol).count();
+            let field = field_name(symbol, occurrence, count);
+
+            if nonterminals.contains(symbol) {
+                fields.push(format!("    pub {field}: &'a Node,"));
+                conversions.push(format!("            {field}: &node.token_values[{position}],"));
+            } else if VALUE_TOKENS.contains(&symbol.as_str()) {
+                fields.push(format!("    pub {field}: &'a Token,"));
+                conversions.push(format!("            {field}: node.token_values[{position}].token.as_ref().ok_or(\"expected a {symbol} token\")?,"));
+            }
+        }
+
+        code.push(comment);
+        code.push("#[derive(Debug, Clone, Copy)]".to_string());
+        code.push(format!("pub struct {name}<'a> {{"));
+        code.append(&mut fields);
+        code.push("}".to_string());
+        code.push("".to_string());
+
+        code.push(format!("impl<'a> {name}<'a> {{"));
+        code.push(format!("    pub fn from_node(node: &'a Node) -> Result<{name}<'a>, String> {{"));
+        code.push(format!("        Ok({name} {{"));
+        code.append(&mut conversions);
+        code.push("        })".to_string());
+        code.push("    }".to_string());
+        code.push("}".to_string());
+        code.push("".to_string());
+    }
+
+    Ok(code.join("\n"))
+}
+
+//writes the typed views of a grammar to a file, from --generate-ast or from a build script:
+//
+//    fn main() {
+//        let parser = programmin_language::grammar().unwrap();
+//        programmin_language::codegen::write_typed_ast(&parser, "src/typed_ast.rs").unwrap();
+//    }
+//
+//the file is only written when it changed so cargo doesnt rebuild over and over, returns
+//whether it was
+pub fn write_typed_ast(parser: &Parser, path: &str) -> Result<bool, String> {
+    let code = typed_ast(parser)?;
+    if std::fs::read_to_string(path).is_ok_and(|old| old == code) {
+        return Ok(false);
+    }
+
+    match std::fs::write(path, code) {
+        Ok(()) => Ok(true),
+        Err(error) => Err(format!("couldnt write {path}: {error}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{LevelPosition, Parser};
+
+    //run --generate-ast after changing the grammar
+    #[test]
+    fn typed_ast_matches_grammar() {
+        let parser = crate::grammar().unwrap();
+        assert!(super::typed_ast(&parser).unwrap() == include_str!("typed_ast.rs"), "src/typed_ast.rs is out of date with the grammar");
+    }
+
+    fn slices(patterns: &[&[&str]]) -> Parser {
+        let mut parser = Parser::new();
+        parser.insert_level("postfix", LevelPosition::Power(250)).unwrap();
+        for pattern in patterns {
+            parser.add_rule_at("postfix", pattern.to_vec(), "expr", "Slice").unwrap();
+        }
+        parser
+    }
+
+    #[test]
+    fn variants_are_named_by_pattern() {
+        let code = super::typed_ast(&slices(&[
+            &["expr", "LSQB", "expr", "COLON", "RSQB"],
+            &["expr", "LSQB", "COLON", "expr", "RSQB"]
+        ])).unwrap();
+        assert!(code.contains("SliceExprColon(SliceExprColonRule<'a>)"));
+        assert!(code.contains("SliceColonExpr(SliceColonExprRule<'a>)"));
+
+        //a new rule in between leaves the names of the others alone
+        let code = super::typed_ast(&slices(&[
+            &["expr", "LSQB", "expr", "COLON", "RSQB"],
+            &["expr", "LSQB", "COLON", "RSQB"],
+            &["expr", "LSQB", "COLON", "expr", "RSQB"]
+        ])).unwrap();
+        assert!(code.contains("SliceExprColon(SliceExprColonRule<'a>)"));
+        assert!(code.contains("SliceColon(SliceColonRule<'a>)"));
+        assert!(code.contains("SliceColonExpr(SliceColonExprRule<'a>)"));
+    }
+
+    #[test]
+    fn single_pattern_keeps_class_name() {
+        let code = super::typed_ast(&slices(&[&["expr", "LSQB", "COLON", "RSQB"]])).unwrap();
+        assert!(code.contains("Slice(SliceRule<'a>)"));
+    }
+}
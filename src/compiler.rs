@@ -0,0 +1,750 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+use crate::ast::{self, argument_nodes};
+use crate::builtins;
+use crate::bytecode::{Code, Instruction, Lookup, Place, Program};
+use crate::error::ErrorKind;
+use crate::lexer::{self, Span};
+use crate::operators::COMPARISONS;
+use crate::parser::Node;
+use crate::typed_ast::*;
+use crate::value::Value;
+
+//the program keeps the value of its last statement here, it is what the program evaluates to
+const RESULT: &str = "<result>";
+
+//a loop that is being compiled, break and continue jump to its end and start
+struct Loop {
+    start: usize,
+    breaks: Vec<usize>,
+    //a for loop has an iterator that a break has to drop
+    iterator: bool
+}
+
+//the Code of a function or of the program while it is compiled
+struct Unit {
+    code: Code,
+    //where each constant is in code.constants, the type is in the key because 1 == 1.0
+    constants: HashMap<(&'static str, Value), usize>,
+    loops: Vec<Loop>,
+    function: bool
+}
+
+struct Compiler {
+    //the chars of every token of the source, the nodes only know their token indexes
+    spans: Vec<Span>,
+    globals: Vec<String>,
+    //the innermost one is the one being compiled
+    units: Vec<Unit>
+}
+
+fn name_of(node: &Node) -> String {
+    node.token_values[0].token.as_ref().unwrap().value()
+}
+
+//the names a function or the program assigns to, without the ones in the functions it defines
+fn assigned(node: &Node, names: &mut Vec<String>) {
+    let mut add = |target: &Node| {
+        if target.class_name == "Name" && !names.contains(&name_of(target)) {
+            names.push(name_of(target));
+        }
+    };
+
+    match node.class_name.as_str() {
+        "FunctionDef" => {
+            let signature = &node.token_values[1];
+            if signature.class_name == "Call" {
+                add(&signature.token_values[0]);
+            }
+            return;
+        }
+        "Assign" | "AugAssign" | "NamedExpr" => add(&node.token_values[0]),
+        "For" => argument_nodes(&node.token_values[1]).into_iter().for_each(add),
+        _ => {}
+    }
+
+    for child in &node.token_values {
+        assigned(child, names);
+    }
+}
+
+impl Compiler {
+    fn unit(&mut self) -> &mut Unit {
+        self.units.last_mut().unwrap()
+    }
+
+    fn emit(&mut self, instruction: Instruction, node: &Node) -> usize {
+        let span = node.span_in(&self.spans);
+        let code = &mut self.unit().code;
+        code.instructions.push(instruction);
+        code.spans.push(span);
+        code.instructions.len() - 1
+    }
+
+    fn here(&mut self) -> usize {
+        self.unit().code.instructions.len()
+    }
+
+    //points the jump at the next instruction, anything else is a bug in the compiler and
+    //becomes an error where the jump should have been
+    fn patch(&mut self, jump: usize) {
+        let target = self.here();
+        let patched = match self.unit().code.instructions[jump] {
+            Instruction::Jump(_) => Instruction::Jump(target),
+            Instruction::JumpIfFalse(_) => Instruction::JumpIfFalse(target),
+            Instruction::JumpIfFalseOrPop(_) => Instruction::JumpIfFalseOrPop(target),
+            Instruction::JumpIfTrueOrPop(_) => Instruction::JumpIfTrueOrPop(target),
+            Instruction::ForIter(_) => Instruction::ForIter(target),
+            other => {
+                let index = self.constant(Value::Str(format!("cant patch {:?}, it doesnt jump", other)));
+                Instruction::Raise(ErrorKind::InternalError, index)
+            }
+        };
+        self.unit().code.instructions[jump] = patched;
+    }
+
+    //equal constants are only stored once, 1 and 1.0 are different constants
+    fn constant(&mut self, value: Value) -> usize {
+        let unit = self.unit();
+        let key = (value.type_name(), value);
+
+        match unit.constants.get(&key) {
+            Some(index) => *index,
+            None => {
+                unit.code.constants.push(key.1.clone());
+                unit.constants.insert(key, unit.code.constants.len() - 1);
+                unit.code.constants.len() - 1
+            }
+        }
+    }
+
+    fn raise(&mut self, kind: ErrorKind, message: &str, node: &Node) {
+        let index = self.constant(Value::Str(message.to_string()));
+        self.emit(Instruction::Raise(kind, index), node);
+    }
+
+    //the local of the running function first, then the ones of the functions around it
+    //and the global last, the first that is set is the value
+    fn load(&mut self, name: &str, node: &Node) {
+        let mut places = vec![];
+        let mut depth = 0;
+        for (position, unit) in self.units.iter().enumerate().rev().filter(|(_, unit)| unit.function) {
+            if let Some(slot) = unit.code.locals.iter().position(|local| local == name) {
+                match position == self.units.len() - 1 {
+                    true => places.push(Place::Local(slot)),
+                    false => places.push(Place::Enclosing(depth, slot))
+                }
+            }
+            if position != self.units.len() - 1 {
+                depth += 1;
+            }
+        }
+        if let Some(slot) = self.globals.iter().position(|global| global == name) {
+            places.push(Place::Global(slot));
+        }
+
+        let lookups = &mut self.unit().code.lookups;
+        let index = match lookups.iter().position(|lookup| lookup.name == name) {
+            Some(index) => index,
+            None => {
+                lookups.push(Lookup { name: name.to_string(), places });
+                lookups.len() - 1
+            }
+        };
+        self.emit(Instruction::Load(index), node);
+    }
+
+    //an assignment sets the local in a function and the global outside of them. assigned()
+    //finds every name beforehand, one that is missing is a bug in the compiler
+    fn store(&mut self, name: &str, node: &Node) {
+        let instruction = match self.unit().function {
+            true => self.unit().code.locals.iter().position(|local| local == name).map(Instruction::StoreLocal),
+            false => self.globals.iter().position(|global| global == name).map(Instruction::StoreGlobal)
+        };
+
+        match instruction {
+            Some(instruction) => {
+                self.emit(instruction, node);
+            }
+            None => self.raise(ErrorKind::InternalError, &format!("{name} has no slot to store it in"), node)
+        }
+    }
+
+    //sets what the program evaluates to, only the statements of the program itself do that
+    fn result(&mut self, node: &Node, keep: bool) {
+        if keep {
+            self.emit(Instruction::Null, node);
+            self.emit(Instruction::StoreLocal(0), node);
+        }
+    }
+
+    fn arguments(&mut self, node: &Node) -> usize {
+        let arguments = argument_nodes(node);
+        for argument in &arguments {
+            self.expression(argument);
+        }
+        arguments.len()
+    }
+
+    //value.name, None after raising the error if the field isnt a name
+    fn attribute_name(&mut self, node: &Node) -> Option<usize> {
+        let field = &node.token_values[2];
+        if field.class_name != "Name" {
+            self.raise(ErrorKind::SyntaxError, "expected a name after .", field);
+            return None;
+        }
+
+        Some(self.constant(Value::Str(name_of(field))))
+    }
+
+    fn binary(class_name: &str) -> Instruction {
+        match class_name {
+            "Add" => Instruction::Add,
+            "Minus" => Instruction::Minus,
+            "Mul" => Instruction::Mul,
+            "Div" => Instruction::Div,
+            "FloorDiv" => Instruction::FloorDiv,
+            "Mod" => Instruction::Mod,
+            "Pow" => Instruction::Pow,
+            "LeftShift" => Instruction::LeftShift,
+            "RightShift" => Instruction::RightShift,
+            "BitAnd" => Instruction::BitAnd,
+            "BitXor" => Instruction::BitXor,
+            _ => Instruction::BitOr
+        }
+    }
+
+    fn expression(&mut self, node: &Node) {
+        //the raised error ends the program, so nothing has to be pushed in its place
+        let expr = match ExprNode::from_node(node) {
+            Ok(expr) => expr,
+            Err(message) => return self.raise(ErrorKind::SyntaxError, &message, node)
+        };
+
+        match expr {
+            ExprNode::Num(NumRule { num, .. }) => {
+                let index = self.constant(ast::number(num));
+                self.emit(Instruction::Constant(index), node);
+            }
+            ExprNode::String(_) => {
+                let index = self.constant(Value::Str(name_of(node)));
+                self.emit(Instruction::Constant(index), node);
+            }
+            ExprNode::BoolTrue(_) | ExprNode::BoolFalse(_) => {
+                let index = self.constant(Value::Bool(matches!(expr, ExprNode::BoolTrue(_))));
+                self.emit(Instruction::Constant(index), node);
+            }
+            ExprNode::Name(_) => self.load(&name_of(node), node),
+            ExprNode::Add(AddRule { expr_0, expr_1, .. })
+            | ExprNode::Minus(MinusRule { expr_0, expr_1, .. })
+            | ExprNode::Mul(MulRule { expr_0, expr_1, .. })
+            | ExprNode::Div(DivRule { expr_0, expr_1, .. })
+            | ExprNode::FloorDiv(FloorDivRule { expr_0, expr_1, .. })
+            | ExprNode::Mod(ModRule { expr_0, expr_1, .. })
+            | ExprNode::Pow(PowRule { expr_0, expr_1, .. })
+            | ExprNode::LeftShift(LeftShiftRule { expr_0, expr_1, .. })
+            | ExprNode::RightShift(RightShiftRule { expr_0, expr_1, .. })
+            | ExprNode::BitAnd(BitAndRule { expr_0, expr_1, .. })
+            | ExprNode::BitXor(BitXorRule { expr_0, expr_1, .. })
+            | ExprNode::BitOr(BitOrRule { expr_0, expr_1, .. }) => {
+                self.expression(expr_0);
+                self.expression(expr_1);
+                self.emit(Compiler::binary(&node.class_name), node);
+            }
+            ExprNode::Invert(InvertRule { expr, .. }) => {
+                self.expression(expr);
+                self.emit(Instruction::Invert, node);
+            }
+            ExprNode::Not(NotRule { expr, .. }) => {
+                self.expression(expr);
+                self.emit(Instruction::Not, node);
+            }
+            ExprNode::Less(_) | ExprNode::Greater(_) | ExprNode::LessEqual(_) | ExprNode::GreaterEqual(_) | ExprNode::Equal(_) | ExprNode::NotEqual(_) => self.compare(node),
+            ExprNode::And(AndRule { expr_0, expr_1, .. }) | ExprNode::Or(OrRule { expr_0, expr_1, .. }) => {
+                self.expression(expr_0);
+                let jump = match expr {
+                    ExprNode::And(_) => self.emit(Instruction::JumpIfFalseOrPop(0), node),
+                    _ => self.emit(Instruction::JumpIfTrueOrPop(0), node)
+                };
+                self.expression(expr_1);
+                self.patch(jump);
+            }
+            ExprNode::Index(IndexRule { expr_0, expr_1, .. }) => {
+                self.expression(expr_0);
+                self.expression(expr_1);
+                self.emit(Instruction::Index, node);
+            }
+            //the missing bounds of value[start:stop:step] are null
+            ExprNode::SliceExprColonExpr(SliceExprColonExprRule { expr_0, expr_1, expr_2, .. }) => self.slice(node, expr_0, [Some(expr_1), Some(expr_2), None]),
+            ExprNode::SliceExprColon(SliceExprColonRule { expr_0, expr_1, .. }) => self.slice(node, expr_0, [Some(expr_1), None, None]),
+            ExprNode::SliceColonExpr(SliceColonExprRule { expr_0, expr_1, .. }) => self.slice(node, expr_0, [None, Some(expr_1), None]),
+            ExprNode::SliceColon(SliceColonRule { expr, .. }) => self.slice(node, expr, [None, None, None]),
+            ExprNode::SliceExprColonExprColonExpr(SliceExprColonExprColonExprRule { expr_0, expr_1, expr_2, expr_3, .. }) => self.slice(node, expr_0, [Some(expr_1), Some(expr_2), Some(expr_3)]),
+            ExprNode::SliceExprColonColonExpr(SliceExprColonColonExprRule { expr_0, expr_1, expr_2, .. }) => self.slice(node, expr_0, [Some(expr_1), None, Some(expr_2)]),
+            ExprNode::SliceColonExprColonExpr(SliceColonExprColonExprRule { expr_0, expr_1, expr_2, .. }) => self.slice(node, expr_0, [None, Some(expr_1), Some(expr_2)]),
+            ExprNode::SliceColonColonExpr(SliceColonColonExprRule { expr_0, expr_1, .. }) => self.slice(node, expr_0, [None, None, Some(expr_1)]),
+            ExprNode::List(_) => {
+                self.emit(Instruction::BuildList(0), node);
+            }
+            ExprNode::ListExpr(ListExprRule { expr: items, .. }) | ExprNode::ListArgs(ListArgsRule { args: items, .. }) => {
+                let count = self.arguments(items);
+                self.emit(Instruction::BuildList(count), node);
+            }
+            ExprNode::MapLbrace(_) => {
+                self.emit(Instruction::BuildMap(0), node);
+            }
+            ExprNode::MapEntries(MapEntriesRule { entries, .. }) => {
+                let count = self.entries(entries);
+                self.emit(Instruction::BuildMap(count), node);
+            }
+            ExprNode::Attribute(AttributeRule { expr_0, .. }) => {
+                if let Some(name) = self.attribute_name(node) {
+                    self.expression(expr_0);
+                    self.emit(Instruction::Attribute(name), node);
+                }
+            }
+            ExprNode::Call(CallRule { expr, .. }) => {
+                self.expression(expr);
+                self.emit(Instruction::Call(0), node);
+            }
+            ExprNode::CallExpr(CallExprRule { expr_0: function, expr_1: arguments, .. }) | ExprNode::CallArgs(CallArgsRule { expr: function, args: arguments, .. }) => {
+                self.expression(function);
+                let count = self.arguments(arguments);
+                self.emit(Instruction::Call(count), node);
+            }
+            ExprNode::Print(_) => {
+                self.emit(Instruction::Print(0), node);
+            }
+            ExprNode::PrintExpr(PrintExprRule { expr: arguments, .. }) | ExprNode::PrintArgs(PrintArgsRule { args: arguments, .. }) => {
+                let count = self.arguments(arguments);
+                self.emit(Instruction::Print(count), node);
+            }
+            ExprNode::Input(_) => {
+                self.emit(Instruction::Input(false), node);
+            }
+            ExprNode::InputExpr(InputExprRule { expr, .. }) => {
+                self.expression(expr);
+                self.emit(Instruction::Input(true), node);
+            }
+            ExprNode::NamedExpr(NamedExprRule { expr_0: target, expr_1: value, .. }) => {
+                if target.class_name != "Name" {
+                    self.raise(ErrorKind::SyntaxError, "only a name can be assigned with :=", target);
+                    return;
+                }
+
+                self.expression(value);
+                self.emit(Instruction::Dup, node);
+                self.store(&name_of(target), target);
+            }
+        }
+    }
+
+    fn slice(&mut self, node: &Node, value: &Node, bounds: [Option<&Node>; 3]) {
+        self.expression(value);
+        for bound in bounds {
+            match bound {
+                Some(bound) => self.expression(bound),
+                None => {
+                    self.emit(Instruction::Null, node);
+                }
+            }
+        }
+        self.emit(Instruction::Slice, node);
+    }
+
+    //a < b < c keeps b on the stack for the next comparison and stops at the first false one
+    fn compare(&mut self, node: &Node) {
+        let mut links = vec![node];
+        loop {
+            let left = &links[0].token_values[0];
+            if !COMPARISONS.contains(&left.class_name.as_str()) || left.parenthesized() {
+                break;
+            }
+            links.insert(0, left);
+        }
+
+        self.expression(&links[0].token_values[0]);
+        let mut cleanups = vec![];
+        for (position, link) in links.iter().enumerate() {
+            self.expression(&link.token_values[2]);
+            let comparison = COMPARISONS.iter().position(|name| *name == link.class_name).unwrap();
+
+            if position == links.len() - 1 {
+                self.emit(Instruction::Compare(comparison), link);
+                break;
+            }
+            self.emit(Instruction::Dup, link);
+            self.emit(Instruction::RotateThree, link);
+            self.emit(Instruction::Compare(comparison), link);
+            cleanups.push(self.emit(Instruction::JumpIfFalseOrPop(0), link));
+        }
+
+        //a false comparison left the operand for the next one under it
+        if !cleanups.is_empty() {
+            let end = self.emit(Instruction::Jump(0), node);
+            for cleanup in cleanups {
+                self.patch(cleanup);
+            }
+            self.emit(Instruction::Swap, node);
+            self.emit(Instruction::Pop, node);
+            self.patch(end);
+        }
+    }
+
+    //a bare name as a key is the string, like in {a: 1}, any other expression is evaluated
+    fn entries(&mut self, node: &Node) -> usize {
+        let mut count = 0;
+        if node.token_names[0] == "entries" {
+            count = self.entries(&node.token_values[0]);
+        }
+
+        let length = node.token_values.len();
+        let key = &node.token_values[length - 3];
+        match key.class_name.as_str() {
+            "Name" if !key.parenthesized() => {
+                let index = self.constant(Value::Str(name_of(key)));
+                self.emit(Instruction::Constant(index), key);
+            }
+            _ => self.expression(key)
+        }
+        self.emit(Instruction::Hashable, key);
+        self.expression(&node.token_values[length - 1]);
+
+        count + 1
+    }
+
+    //keep is set for the statements whose value is the value of the program
+    fn statement(&mut self, node: &Node, keep: bool) {
+        let typed = match AnyNode::from_node(node) {
+            Ok(typed) => typed,
+            Err(message) => return self.raise(ErrorKind::SyntaxError, &message, node)
+        };
+
+        match typed {
+            AnyNode::Program(ProgramNode::Program(ProgramRule { stmts, .. })) => self.statement(stmts, keep),
+            AnyNode::Stmts(StmtsNode::Stmts(StmtsRule { stmts, .. })) => {
+                for stmt in stmts {
+                    self.statement(stmt, keep);
+                }
+            }
+            AnyNode::Stmt(stmt) | AnyNode::Stmts(StmtsNode::Stmt(stmt)) => self.stmt(stmt, keep),
+            AnyNode::Block(BlockNode::BlockStmts(BlockStmtsRule { stmts, .. })) => self.statement(stmts, keep),
+            AnyNode::Block(BlockNode::BlockSemi(_)) => self.result(node, keep),
+            AnyNode::Orelse(OrelseNode::ElifOrelse(ElifOrelseRule { expr, block, orelse, .. })) => self.branch(node, expr, block, Some(orelse), keep),
+            AnyNode::Orelse(OrelseNode::Elif(ElifRule { expr, block, .. })) => self.branch(node, expr, block, None, keep),
+            AnyNode::Orelse(OrelseNode::Else(ElseRule { block, .. })) => self.statement(block, keep),
+            AnyNode::Expr(_) | AnyNode::Entries(_) | AnyNode::Args(_) => self.raise(ErrorKind::SyntaxError, &format!("{} cant be compiled on its own", node.class_name), node)
+        }
+    }
+
+    fn stmt(&mut self, stmt: StmtNode, keep: bool) {
+        match stmt {
+            StmtNode::ExprStmt(ExprStmtRule { node, expr }) => {
+                self.expression(expr);
+                match keep {
+                    true => self.emit(Instruction::StoreLocal(0), node),
+                    false => self.emit(Instruction::Pop, node)
+                };
+            }
+            StmtNode::If(IfRule { node, expr, block }) => self.branch(node, expr, block, None, keep),
+            StmtNode::IfOrelse(IfOrelseRule { node, expr, block, orelse }) => self.branch(node, expr, block, Some(orelse), keep),
+            StmtNode::While(WhileRule { node, expr, block }) => {
+                let start = self.here();
+                self.expression(expr);
+                let exit = self.emit(Instruction::JumpIfFalse(0), node);
+
+                self.unit().loops.push(Loop { start, breaks: vec![], iterator: false });
+                self.statement(block, false);
+                self.emit(Instruction::Jump(start), node);
+                self.patch(exit);
+                self.end_loop();
+                self.result(node, keep);
+            }
+            StmtNode::ForExpr(ForExprRule { node, expr_0: targets, expr_1: iterable, block })
+            | StmtNode::ForArgs(ForArgsRule { node, args: targets, expr: iterable, block }) => self.for_loop(node, targets, iterable, block, keep),
+            StmtNode::Break(BreakRule { node }) | StmtNode::Continue(ContinueRule { node }) => {
+                let (start, iterator) = match self.unit().loops.last() {
+                    Some(innermost) => (innermost.start, innermost.iterator),
+                    None => {
+                        let message = format!("{} outside of a loop", node.class_name.to_lowercase());
+                        self.raise(ErrorKind::SyntaxError, &message, node);
+                        return;
+                    }
+                };
+
+                if let StmtNode::Continue(_) = stmt {
+                    self.emit(Instruction::Jump(start), node);
+                    return;
+                }
+                if iterator {
+                    self.emit(Instruction::PopIterator, node);
+                }
+                let jump = self.emit(Instruction::Jump(0), node);
+                self.unit().loops.last_mut().unwrap().breaks.push(jump);
+            }
+            StmtNode::Assign(AssignRule { node, expr_0: target, expr_1: value }) => {
+                self.expression(value);
+                self.assign(target);
+                self.result(node, keep);
+            }
+            StmtNode::AugAssignPlusequal(AugAssignPlusequalRule { node, .. })
+            | StmtNode::AugAssignMinequal(AugAssignMinequalRule { node, .. })
+            | StmtNode::AugAssignStarequal(AugAssignStarequalRule { node, .. })
+            | StmtNode::AugAssignSlashequal(AugAssignSlashequalRule { node, .. })
+            | StmtNode::AugAssignDoubleslashequal(AugAssignDoubleslashequalRule { node, .. })
+            | StmtNode::AugAssignPercentequal(AugAssignPercentequalRule { node, .. })
+            | StmtNode::AugAssignDoublestarequal(AugAssignDoublestarequalRule { node, .. })
+            | StmtNode::AugAssignLeftshiftequal(AugAssignLeftshiftequalRule { node, .. })
+            | StmtNode::AugAssignRightshiftequal(AugAssignRightshiftequalRule { node, .. })
+            | StmtNode::AugAssignAmperequal(AugAssignAmperequalRule { node, .. })
+            | StmtNode::AugAssignCircumflexequal(AugAssignCircumflexequalRule { node, .. })
+            | StmtNode::AugAssignVbarequal(AugAssignVbarequalRule { node, .. }) => {
+                self.augmented(node);
+                self.result(node, keep);
+            }
+            //the type after -> isnt checked
+            StmtNode::FunctionDef(FunctionDefRule { node, expr: signature, block })
+            | StmtNode::FunctionDefRarrowExpr(FunctionDefRarrowExprRule { node, expr_0: signature, block, .. }) => {
+                self.function(node, signature, block);
+                self.result(node, keep);
+            }
+            StmtNode::Return(ReturnRule { node }) | StmtNode::ReturnExpr(ReturnExprRule { node, .. }) => {
+                if !self.unit().function {
+                    self.raise(ErrorKind::SyntaxError, "return outside of a function", node);
+                    return;
+                }
+
+                match stmt {
+                    StmtNode::ReturnExpr(ReturnExprRule { expr, .. }) => self.expression(expr),
+                    _ => {
+                        self.emit(Instruction::Null, node);
+                    }
+                }
+                self.emit(Instruction::Return, node);
+            }
+        }
+    }
+
+    //if and elif, without an orelse a kept result is null when the condition is false
+    fn branch(&mut self, node: &Node, condition: &Node, block: &Node, orelse: Option<&Node>, keep: bool) {
+        self.expression(condition);
+        let otherwise = self.emit(Instruction::JumpIfFalse(0), node);
+        self.statement(block, keep);
+
+        if orelse.is_none() && !keep {
+            self.patch(otherwise);
+            return;
+        }
+
+        let end = self.emit(Instruction::Jump(0), node);
+        self.patch(otherwise);
+        match orelse {
+            Some(orelse) => self.statement(orelse, keep),
+            None => self.result(node, keep)
+        }
+        self.patch(end);
+    }
+
+    //the breaks jump to after the loop
+    fn end_loop(&mut self) {
+        let innermost = self.unit().loops.pop().unwrap();
+        for jump in innermost.breaks {
+            self.patch(jump);
+        }
+    }
+
+    //for name in iterable or for a, b in iterable, the items are unpacked like in ast::For
+    fn for_loop(&mut self, node: &Node, targets: &Node, iterable: &Node, body: &Node, keep: bool) {
+        let names = argument_nodes(targets);
+        if let Some(target) = names.iter().find(|target| target.class_name != "Name") {
+            self.raise(ErrorKind::TypeError, &format!("cant assign to {:?}", target), target);
+            return;
+        }
+
+        self.expression(iterable);
+        self.emit(Instruction::GetIter, iterable);
+        let start = self.emit(Instruction::ForIter(0), node);
+
+        if names.len() > 1 {
+            self.emit(Instruction::Items, iterable);
+            self.emit(Instruction::Unpack(names.len()), targets);
+        }
+        for target in &names {
+            self.store(&name_of(target), target);
+        }
+
+        self.unit().loops.push(Loop { start, breaks: vec![], iterator: true });
+        self.statement(body, false);
+        self.emit(Instruction::Jump(start), node);
+        self.patch(start);
+        self.end_loop();
+        self.result(node, keep);
+    }
+
+    //the value is on the stack, then the container and the index are evaluated like in ast::Assign
+    fn assign(&mut self, target: &Node) {
+        match target.class_name.as_str() {
+            "Name" => self.store(&name_of(target), target),
+            "Index" => {
+                self.expression(&target.token_values[0]);
+                self.expression(&target.token_values[2]);
+                self.emit(Instruction::StoreIndex, target);
+            }
+            "Attribute" => {
+                if let Some(name) = self.attribute_name(target) {
+                    self.expression(&target.token_values[0]);
+                    self.emit(Instruction::StoreAttribute(name), target);
+                }
+            }
+            _ => self.raise(ErrorKind::TypeError, &format!("cant assign to {:?}", target), target)
+        }
+    }
+
+    //target op= value, the container and the index stay on the stack for the store
+    fn augmented(&mut self, node: &Node) {
+        let target = &node.token_values[0];
+        let operator = match ast::augmented_operator(&node.token_names[1]) {
+            "Add" => Instruction::InPlaceAdd,
+            other => Compiler::binary(other)
+        };
+
+        match target.class_name.as_str() {
+            "Name" => {
+                self.load(&name_of(target), target);
+                self.expression(&node.token_values[2]);
+                self.emit(operator, node);
+                self.store(&name_of(target), target);
+            }
+            "Index" => {
+                self.expression(&target.token_values[0]);
+                self.expression(&target.token_values[2]);
+                self.emit(Instruction::DupTwo, target);
+                self.emit(Instruction::Index, target);
+                self.expression(&node.token_values[2]);
+                self.emit(operator, node);
+                self.emit(Instruction::RotateThree, node);
+                self.emit(Instruction::StoreIndex, target);
+            }
+            "Attribute" => {
+                if let Some(name) = self.attribute_name(target) {
+                    self.expression(&target.token_values[0]);
+                    self.emit(Instruction::Dup, target);
+                    self.emit(Instruction::Attribute(name), target);
+                    self.expression(&node.token_values[2]);
+                    self.emit(operator, node);
+                    self.emit(Instruction::Swap, node);
+                    self.emit(Instruction::StoreAttribute(name), target);
+                }
+            }
+            _ => self.raise(ErrorKind::TypeError, &format!("cant assign to {:?}", target), target)
+        }
+    }
+
+    //fn name(a, b) { ... } compiles the body into its own Code, the checks of ast::FunctionDef
+    //become errors raised when the definition is reached
+    fn function(&mut self, node: &Node, signature: &Node, body: &Node) {
+        if signature.class_name != "Call" || signature.token_values[0].class_name != "Name" {
+            self.raise(ErrorKind::SyntaxError, "expected a name and parameters after fn", signature);
+            return;
+        }
+
+        let parameters = match signature.token_values.len() {
+            3 => vec![],
+            _ => argument_nodes(&signature.token_values[2])
+        };
+
+        let mut params: Vec<String> = vec![];
+        for parameter in parameters {
+            if parameter.class_name != "Name" {
+                self.raise(ErrorKind::SyntaxError, "parameters have to be names", parameter);
+                return;
+            }
+
+            let param = name_of(parameter);
+            if params.contains(&param) {
+                self.raise(ErrorKind::SyntaxError, &format!("duplicate parameter {param}"), parameter);
+                return;
+            }
+            params.push(param);
+        }
+
+        let name = name_of(&signature.token_values[0]);
+        let mut locals = params.clone();
+        assigned(body, &mut locals);
+
+        self.units.push(Unit {
+            code: Code {
+                name: name.clone(),
+                params: params.len(),
+                locals,
+                ..Code::default()
+            },
+            constants: HashMap::new(),
+            loops: vec![],
+            function: true
+        });
+        self.statement(body, false);
+        self.emit(Instruction::Null, node);
+        self.emit(Instruction::Return, node);
+        let code = self.units.pop().unwrap().code;
+
+        let functions = &mut self.unit().code.functions;
+        functions.push(Rc::new(code));
+        let index = functions.len() - 1;
+        self.emit(Instruction::MakeFunction(index), node);
+        self.store(&name, &signature.token_values[0]);
+    }
+}
+
+//the built in functions come first in the globals, then every name the program assigns to
+pub fn compile(program: &Node) -> Program {
+    let mut globals: Vec<String> = builtins::globals().into_iter().map(|function| function.name).collect();
+    assigned(program, &mut globals);
+
+    let mut compiler = Compiler {
+        spans: lexer::lex_spanned(&program.line).into_iter().map(|(_, span)| span).collect(),
+        globals,
+        units: vec![Unit {
+            code: Code {
+                name: "<program>".to_string(),
+                locals: vec![RESULT.to_string()],
+                ..Code::default()
+            },
+            constants: HashMap::new(),
+            loops: vec![],
+            function: false
+        }]
+    };
+
+    compiler.result(program, true);
+    compiler.statement(program, true);
+    compiler.unit().code.lookups.push(Lookup { name: RESULT.to_string(), places: vec![Place::Local(0)] });
+    let index = compiler.unit().code.lookups.len() - 1;
+    compiler.emit(Instruction::Load(index), program);
+    compiler.emit(Instruction::Return, program);
+
+    Program {
+        code: Rc::new(compiler.units.pop().unwrap().code),
+        globals: compiler.globals,
+        source: program.line.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lexer;
+    use crate::value::Value;
+
+    //1 and 1.0 are equal values but different constants, each is stored once
+    #[test]
+    fn constants_are_shared() {
+        let parser = crate::grammar().unwrap();
+        let source = "a = 1; b = 1; c = 1.0; d = \"1\"; e = 1.0; f = \"1\"";
+        let ast = parser.parse(&lexer::lex(source), source).unwrap();
+
+        let program = super::compile(&ast);
+        let constants: Vec<(&str, String)> = program.code.constants.iter().map(|constant| (constant.type_name(), constant.repr())).collect();
+        assert_eq!(constants, [("int", "1".to_string()), ("float", "1.0".to_string()), ("string", "\"1\"".to_string())]);
+        assert_eq!(program.code.constants[0], Value::Int(1));
+    }
+}
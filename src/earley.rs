@@ -0,0 +1,538 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use crate::lexer::Token;
+use crate::parser::{is_sequence, join, needs_parens, Assoc, Node, ParseError, Parser};
+
+//the competing trees of an ambiguous input that are shown, all of them are counted
+const SHOWN_TREES: usize = 8;
+
+//the symbols are numbers, the ones no rule mentions share the last one
+struct FlatRule {
+    pattern: Vec<usize>,
+    names: Vec<String>,
+    name: usize,
+    class_name: String,
+    group: bool,
+    sequence: bool,
+    power: i32,
+    assoc: Assoc
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct Item {
+    rule: usize,
+    dot: usize,
+    origin: usize
+}
+
+//how an item got the symbol before its dot: the item one symbol back ended at `at`, and the
+//symbol is the token there or a node of the rule that was completed from `at` on
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Link {
+    at: usize,
+    child: Option<usize>
+}
+
+//the items of one position of the chart with the links back to where they came from. together
+//the sets are a packed forest, a subtree that is shared by many parses is only there once
+#[derive(Default)]
+struct Set {
+    items: Vec<Item>,
+    positions: HashMap<Item, usize>,
+    links: Vec<Vec<Link>>,
+    //the items with a nonterminal after their dot, by that nonterminal
+    waiting: HashMap<usize, Vec<Item>>,
+    //how many trees every item has, worked out after the chart is full
+    ways: Vec<Option<u64>>
+}
+
+impl Set {
+    fn add(&mut self, item: Item, link: Option<Link>) -> bool {
+        if let Some(position) = self.positions.get(&item) {
+            let links = &mut self.links[*position];
+            if let Some(link) = link {
+                if !links.contains(&link) {
+                    links.push(link);
+                }
+            }
+            return false;
+        }
+
+        self.positions.insert(item, self.items.len());
+        self.items.push(item);
+        self.links.push(link.into_iter().collect());
+        true
+    }
+}
+
+struct Earley<'a> {
+    rules: Vec<FlatRule>,
+    symbols: HashMap<String, usize>,
+    //the rules of every nonterminal, empty for tokens
+    alternatives: Vec<Vec<usize>>,
+    tokens: &'a Vec<Token>,
+    names: Vec<String>,
+    kinds: Vec<usize>,
+    line: Arc<str>
+}
+
+impl<'a> Earley<'a> {
+    fn new(parser: &Parser, tokens: &'a Vec<Token>, line: &str) -> Earley<'a> {
+        let mut symbols: HashMap<String, usize> = HashMap::new();
+        let mut number = |symbol: &String| {
+            let next = symbols.len();
+            *symbols.entry(symbol.clone()).or_insert(next)
+        };
+
+        let mut rules: Vec<FlatRule> = vec![];
+        for level in &parser.levels {
+            for rule in &level.rules {
+                let flat = FlatRule {
+                    pattern: rule.pattern.iter().map(&mut number).collect(),
+                    names: rule.pattern.clone(),
+                    name: number(&rule.name),
+                    class_name: rule.class_name.clone(),
+                    group: rule.group,
+                    sequence: is_sequence(&rule.pattern, &rule.name),
+                    power: level.power,
+                    assoc: level.assoc
+                };
+
+                //the same rule twice would make every parse using it ambiguous with itself
+                let duplicate = rules.iter().any(|other| other.pattern == flat.pattern && other.name == flat.name && other.class_name == flat.class_name
+                    && other.group == flat.group && other.power == flat.power);
+                if !duplicate {
+                    rules.push(flat);
+                }
+            }
+        }
+
+        let mut alternatives = vec![vec![]; symbols.len() + 1];
+        for (index, rule) in rules.iter().enumerate() {
+            alternatives[rule.name].push(index);
+        }
+
+        let names: Vec<String> = tokens.iter().map(|token| token.name()).collect();
+
+        Earley {
+            kinds: names.iter().map(|name| symbols.get(name).copied().unwrap_or(symbols.len())).collect(),
+            rules,
+            symbols,
+            alternatives,
+            tokens,
+            names,
+            line: Arc::from(line)
+        }
+    }
+
+    //whether a node of `child` can be the symbol at `position` of `rule`. with the precedence
+    //filter a node of the same nonterminal has to bind tight enough to go there without parentheses
+    fn accepts(&self, rule: usize, position: usize, child: usize, filter: bool) -> bool {
+        let rule = &self.rules[rule];
+        let length = rule.pattern.len();
+
+        !filter || length == 1 || rule.pattern[position] != rule.name || !needs_parens(rule.power, rule.assoc, position, length, self.rules[child].power)
+    }
+
+    //fills the chart, returns the position where it got stuck if the input isnt a `start`. the
+    //precedence filter is applied while predicting and completing, so the parses it rules out
+    //never get into the chart
+    fn recognize(&self, start: usize, filter: bool) -> Result<Vec<Set>, (usize, Vec<String>)> {
+        let length = self.kinds.len();
+        let mut chart: Vec<Set> = (0..=length).map(|_| Set::default()).collect();
+
+        for rule in &self.alternatives[start] {
+            chart[0].add(Item { rule: *rule, dot: 0, origin: 0 }, None);
+        }
+
+        for position in 0..=length {
+            let (done, rest) = chart.split_at_mut(position);
+            let (current, after) = rest.split_first_mut().unwrap();
+            let mut index = 0;
+
+            while index < current.items.len() {
+                let item = current.items[index];
+                let rule = &self.rules[item.rule];
+                index += 1;
+
+                if item.dot == rule.pattern.len() {
+                    //complete, nothing is empty so the waiting items are in an earlier set
+                    let waiting = done[item.origin].waiting.get(&rule.name).cloned().unwrap_or_default();
+
+                    for waiting in waiting {
+                        if self.accepts(waiting.rule, waiting.dot, item.rule, filter) {
+                            let link = Link { at: item.origin, child: Some(item.rule) };
+                            current.add(Item { dot: waiting.dot + 1, ..waiting }, Some(link));
+                        }
+                    }
+                    continue;
+                }
+
+                let symbol = rule.pattern[item.dot];
+                if !self.alternatives[symbol].is_empty() {
+                    //predict
+                    current.waiting.entry(symbol).or_default().push(item);
+
+                    for other in &self.alternatives[symbol] {
+                        if self.accepts(item.rule, item.dot, *other, filter) {
+                            current.add(Item { rule: *other, dot: 0, origin: position }, None);
+                        }
+                    }
+                } else if position < length && self.kinds[position] == symbol {
+                    //scan
+                    let link = Link { at: position, child: None };
+                    after[0].add(Item { dot: item.dot + 1, ..item }, Some(link));
+                }
+            }
+        }
+
+        if self.complete(&chart, start).next().is_some() {
+            return Ok(chart);
+        }
+
+        let stuck = (0..=length).rev().find(|position| !chart[*position].items.is_empty()).unwrap_or(0);
+        let mut expected = vec![];
+
+        for item in &chart[stuck].items {
+            let rule = &self.rules[item.rule];
+            if item.dot < rule.pattern.len() && self.alternatives[rule.pattern[item.dot]].is_empty() && !expected.contains(&rule.names[item.dot]) {
+                expected.push(rule.names[item.dot].clone());
+            }
+        }
+
+        Err((stuck, expected))
+    }
+
+    //the indexes of the items in the last set that are a whole `start`
+    fn complete<'b>(&'b self, chart: &'b [Set], start: usize) -> impl Iterator<Item = usize> + 'b {
+        let last = &chart[chart.len() - 1];
+
+        (0..last.items.len()).filter(move |index| {
+            let item = last.items[*index];
+            let rule = &self.rules[item.rule];
+            item.origin == 0 && item.dot == rule.pattern.len() && rule.name == start
+        })
+    }
+
+    //the completed item of `rule` from `origin` to `position`
+    fn completed(&self, chart: &[Set], rule: usize, origin: usize, position: usize) -> usize {
+        let item = Item { rule, dot: self.rules[rule].pattern.len(), origin };
+        chart[position].positions[&item]
+    }
+
+    //the item one symbol before the one at `index`, it ended where the link says
+    fn previous(&self, chart: &[Set], position: usize, index: usize, link: Link) -> usize {
+        let item = chart[position].items[index];
+        chart[link.at].positions[&Item { dot: item.dot - 1, ..item }]
+    }
+
+    //the number of trees for the symbols before the dot of every item, saturating. the links of
+    //an item go back to earlier sets, so going through the sets in order only recurses for
+    //children that end at the same position
+    fn count(&self, chart: &mut [Set]) {
+        for position in 0..chart.len() {
+            chart[position].ways = vec![None; chart[position].items.len()];
+
+            for index in 0..chart[position].items.len() {
+                self.ways(chart, position, index, &mut HashSet::new());
+            }
+        }
+    }
+
+    fn ways(&self, chart: &mut [Set], position: usize, index: usize, visiting: &mut HashSet<usize>) -> u64 {
+        if let Some(ways) = chart[position].ways[index] {
+            return ways;
+        }
+        if chart[position].items[index].dot == 0 {
+            chart[position].ways[index] = Some(1);
+            return 1;
+        }
+        //unit rule cycles
+        if !visiting.insert(index) {
+            return 0;
+        }
+
+        let mut ways: u64 = 0;
+        for link in chart[position].links[index].clone() {
+            let previous = self.previous(chart, position, index, link);
+            let before = chart[link.at].ways[previous].unwrap_or(0);
+
+            let child = match link.child {
+                Some(rule) => {
+                    let completed = self.completed(chart, rule, link.at, position);
+                    self.ways(chart, position, completed, visiting)
+                },
+                None => 1
+            };
+
+            ways = ways.saturating_add(before.saturating_mul(child));
+        }
+
+        visiting.remove(&index);
+        chart[position].ways[index] = Some(ways);
+        ways
+    }
+
+    fn node(&self, rule: usize, children: Vec<Node>, start: usize, end: usize) -> Node {
+        let rule = &self.rules[rule];
+
+        if rule.group {
+            //the inner node, but covering the parentheses too
+            let mut inner = children.into_iter().zip(rule.pattern.iter()).find(|(_, symbol)| !self.alternatives[**symbol].is_empty()).unwrap().0;
+            inner.index = start;
+            inner.end = end;
+            inner
+        } else if rule.sequence {
+            join(&rule.class_name, &rule.names[0], children, start, end, &self.line)
+        } else {
+            Node {
+                class_name: rule.class_name.clone(),
+                token_values: children,
+                token_names: rule.names.clone(),
+                index: start,
+                end,
+                line: self.line.clone(),
+                token: None
+            }
+        }
+    }
+
+    fn token(&self, at: usize) -> Node {
+        Node::from_token(&self.tokens[at], at, &self.line)
+    }
+
+    //the tree of a completed item that has exactly one, the links are followed back one symbol
+    //at a time and only recursed into for the children. the children of a sequence rule that
+    //are the same rule are followed back in the same loop, a long program doesnt recurse
+    fn tree(&self, chart: &[Set], position: usize, index: usize) -> Node {
+        let item = chart[position].items[index];
+        let mut children = vec![];
+        let mut pending = vec![(position, index)];
+
+        while let Some((at, current)) = pending.pop() {
+            if chart[at].items[current].dot == 0 {
+                continue;
+            }
+
+            let (link, previous) = chart[at].links[current].iter().map(|link| (*link, self.previous(chart, at, current, *link)))
+                .find(|(link, previous)| chart[link.at].ways[*previous] != Some(0) && self.child_ways(chart, at, *link) != 0)
+                .unwrap();
+
+            //the symbols before this one come after the ones of the child, the children are collected backwards
+            pending.push((link.at, previous));
+
+            match link.child {
+                Some(rule) if rule == item.rule && self.rules[rule].sequence => pending.push((at, self.completed(chart, rule, link.at, at))),
+                Some(rule) => children.push(self.tree(chart, at, self.completed(chart, rule, link.at, at))),
+                None => children.push(self.token(link.at))
+            }
+        }
+
+        children.reverse();
+        self.node(item.rule, children, item.origin, position)
+    }
+
+    fn child_ways(&self, chart: &[Set], position: usize, link: Link) -> u64 {
+        match link.child {
+            Some(rule) => chart[position].ways[self.completed(chart, rule, link.at, position)].unwrap_or(0),
+            None => 1
+        }
+    }
+
+    //up to `limit` of the trees of a completed item, for showing the competing ones
+    fn trees(&self, chart: &[Set], position: usize, index: usize, limit: usize) -> Vec<Node> {
+        let item = chart[position].items[index];
+
+        self.sequences(chart, position, index, limit).into_iter().map(|children| self.node(item.rule, children, item.origin, position)).collect()
+    }
+
+    //up to `limit` ways to get the symbols before the dot of an item
+    fn sequences(&self, chart: &[Set], position: usize, index: usize, limit: usize) -> Vec<Vec<Node>> {
+        if chart[position].items[index].dot == 0 {
+            return vec![vec![]];
+        }
+
+        let mut sequences = vec![];
+        for link in &chart[position].links[index] {
+            let previous = self.previous(chart, position, index, *link);
+            if chart[link.at].ways[previous] == Some(0) || self.child_ways(chart, position, *link) == 0 {
+                continue;
+            }
+
+            let children = match link.child {
+                Some(rule) => self.trees(chart, position, self.completed(chart, rule, link.at, position), limit),
+                None => vec![self.token(link.at)]
+            };
+
+            for head in self.sequences(chart, link.at, previous, limit) {
+                for child in &children {
+                    if sequences.len() >= limit {
+                        return sequences;
+                    }
+
+                    let mut sequence = head.clone();
+                    sequence.push(child.clone());
+                    sequences.push(sequence);
+                }
+            }
+        }
+
+        sequences
+    }
+}
+
+//parses with an earley chart, so any context free grammar without empty rules works (the
+//parser refuses those). ambiguities are resolved with the precedence levels or reported with
+//the competing trees
+pub fn parse(parser: &Parser, tokens: &Vec<Token>, line: &str) -> Result<Node, ParseError> {
+    let earley = Earley::new(parser, tokens, line);
+
+    let start = match parser.start.as_ref().and_then(|start| earley.symbols.get(start)) {
+        Some(start) => *start,
+        None => return Err(ParseError {
+            line: line.to_string(),
+            expected: format!("a start symbol that has rules, got {:?} (see Parser::set_start)", parser.start),
+            names: earley.names.clone(),
+            trees: vec![],
+            competing: 0
+        })
+    };
+
+    //precedence ruled out everything, see if there is a single parse at all
+    let mut chart = match earley.recognize(start, true).or_else(|_| earley.recognize(start, false)) {
+        Ok(chart) => chart,
+        Err((stuck, expected)) => return Err(ParseError {
+            line: line.to_string(),
+            expected: if expected.is_empty() { "end of line".to_string() } else { expected.join(" or ") },
+            names: earley.names[stuck..].to_vec(),
+            trees: vec![],
+            competing: 0
+        })
+    };
+
+    earley.count(&mut chart);
+
+    let last = chart.len() - 1;
+    let complete: Vec<usize> = earley.complete(&chart, start).collect();
+    let competing = complete.iter().fold(0u64, |total, index| total.saturating_add(chart[last].ways[*index].unwrap_or(0)));
+
+    if competing == 1 {
+        let index = complete.into_iter().find(|index| chart[last].ways[*index] == Some(1)).unwrap();
+        return Ok(earley.tree(&chart, last, index));
+    }
+
+    let trees = complete.into_iter().flat_map(|index| earley.trees(&chart, last, index, SHOWN_TREES)).take(SHOWN_TREES);
+
+    Err(ParseError {
+        line: line.to_string(),
+        expected: parser.start.clone().unwrap_or_default(),
+        names: earley.names.clone(),
+        trees: trees.collect(),
+        competing: competing as usize
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar_test::sexpr;
+    use crate::lexer;
+    use crate::parser::{LevelPosition, Mode};
+
+    //sum isnt expr, so the precedence levels dont pick one of the ways to group a sum
+    fn ambiguous() -> Parser {
+        let mut parser = Parser::new();
+        parser.set_mode(Mode::Earley);
+
+        parser.insert_level("atom", LevelPosition::Power(300)).unwrap();
+        parser.insert_level("additive", LevelPosition::Power(100)).unwrap();
+        parser.insert_level("statement", LevelPosition::Power(10)).unwrap();
+
+        parser.add_rule_at("atom", vec!["NUM"], "expr", "Num").unwrap();
+        parser.add_rule_at("atom", vec!["sum"], "expr", "Sum").unwrap();
+        parser.add_rule_at("additive", vec!["expr", "PLUS", "expr"], "sum", "Add").unwrap();
+        parser.add_rule_at("statement", vec!["expr", "SEMI"], "stmt", "ExprStmt").unwrap();
+        parser.set_start("stmt");
+
+        parser
+    }
+
+    fn parse(parser: &Parser, source: &str) -> Result<Node, ParseError> {
+        parser.parse(&lexer::lex(source), source)
+    }
+
+    #[test]
+    fn counts_competing_trees() {
+        let parser = ambiguous();
+
+        let error = parse(&parser, "1 + 2 + 3 + 4").unwrap_err();
+        assert_eq!((error.competing, error.trees.len()), (5, 5));
+
+        //the catalan number of 12, only a few of them are built
+        let error = parse(&parser, &vec!["1"; 13].join(" + ")).unwrap_err();
+        assert_eq!((error.competing, error.trees.len()), (208012, SHOWN_TREES));
+    }
+
+    #[test]
+    fn duplicate_rules() {
+        let mut parser = ambiguous();
+        parser.add_rule_at("atom", vec!["NUM"], "expr", "Num").unwrap();
+
+        let ast = parse(&parser, "1 + 2").unwrap();
+        assert_eq!(sexpr(&ast.token_values[0]), "(Sum (Add 1 2))");
+    }
+
+    #[test]
+    fn precedence() {
+        let mut parser = ambiguous();
+        parser.insert_level("multiplicative", LevelPosition::Power(200)).unwrap();
+        parser.add_rule_at("multiplicative", vec!["expr", "STAR", "expr"], "expr", "Mul").unwrap();
+
+        let ast = parse(&parser, "1 * 2 * 3").unwrap();
+        assert_eq!(sexpr(&ast.token_values[0]), "(Mul (Mul 1 2) 3)");
+    }
+
+    #[test]
+    fn no_start_symbol() {
+        let mut parser = Parser::new();
+        parser.set_mode(Mode::Earley);
+        parser.insert_level("atom", LevelPosition::Power(300)).unwrap();
+        parser.add_rule_at("atom", vec!["NUM"], "expr", "Num").unwrap();
+
+        assert!(parse(&parser, "1").is_err());
+        parser.set_start("missing");
+        assert!(parse(&parser, "1").is_err());
+    }
+
+    fn program(lines: usize) -> String {
+        let source: Vec<String> = (0..lines).map(|i| format!("x{i} = {i} + {i} * 2 - (3 / 4)")).collect();
+        source.join("\n")
+    }
+
+    //the items in the chart of a program, a line adds about the same number of them wherever it is
+    fn chart_size(parser: &Parser, source: &str) -> usize {
+        let tokens = lexer::lex(source);
+        let earley = Earley::new(parser, &tokens, source);
+        let start = earley.symbols[parser.start.as_ref().unwrap()];
+
+        match earley.recognize(start, true) {
+            Ok(chart) => chart.iter().map(|set| set.items.len()).sum(),
+            Err(_) => panic!("{source} didnt parse")
+        }
+    }
+
+    //a program with a single parse is built without going through the ways to split it up,
+    //and its chart grows linearly with the number of lines
+    #[test]
+    fn long_program() {
+        let mut parser = crate::grammar().unwrap();
+        parser.set_mode(Mode::Earley);
+
+        let ast = parse(&parser, &program(300)).unwrap();
+        assert_eq!(ast.class_name, "Program");
+
+        let short = chart_size(&parser, &program(100));
+        let long = chart_size(&parser, &program(200));
+        assert!(long < short * 2 + short / 10, "{short} items for 100 lines, {long} for 200");
+    }
+}
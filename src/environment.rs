@@ -0,0 +1,131 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::rc::Rc;
+use crate::builtins;
+use crate::value::Value;
+
+//the variables of a program, they are kept across all of its lines, together with
+//where print writes to and input reads from
+pub struct Environment {
+    //the scope of the running function, the global one outside of functions
+    pub scope: Rc<RefCell<Scope>>,
+    pub reader: Box<dyn BufRead>,
+    pub writer: Box<dyn Write>,
+    //set by break, continue and return, the statements after them are skipped until a loop
+    //or the call handles it
+    pub control: Control,
+    //how many loops are running in the current function, break and continue are only allowed inside one
+    pub loops: usize,
+    //how many calls are running, return is only allowed inside one
+    pub depth: usize
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Control {
+    Normal,
+    Break,
+    Continue,
+    Return(Value)
+}
+
+//an assignment always sets a name in the innermost scope, reading a name also looks
+//at the scopes around it, so a function sees the variables where it was defined
+#[derive(Default)]
+pub struct Scope {
+    values: HashMap<String, Value>,
+    parent: Option<Rc<RefCell<Scope>>>
+}
+
+impl Scope {
+    pub fn new(parent: Option<Rc<RefCell<Scope>>>) -> Scope {
+        Scope {
+            values: HashMap::new(),
+            parent
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        match self.values.get(name) {
+            Some(value) => Some(value.clone()),
+            None => self.parent.as_ref().and_then(|parent| parent.borrow().get(name))
+        }
+    }
+
+    pub fn set(&mut self, name: &str, value: Value) {
+        self.values.insert(name.to_string(), value);
+    }
+}
+
+//only the names, a function stored in the scope it captured would print forever
+impl std::fmt::Debug for Scope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut names: Vec<&String> = self.values.keys().collect();
+        names.sort();
+        f.debug_struct("Scope").field("names", &names).finish()
+    }
+}
+
+impl Default for Environment {
+    fn default() -> Environment {
+        Environment::new()
+    }
+}
+
+impl Environment {
+    pub fn new() -> Environment {
+        Environment::with_io(Box::new(io::BufReader::new(io::stdin())), Box::new(io::stdout()))
+    }
+
+    pub fn with_io(reader: Box<dyn BufRead>, writer: Box<dyn Write>) -> Environment {
+        let mut globals = Scope::new(None);
+        for function in builtins::globals() {
+            globals.set(&function.name.clone(), Value::NativeFunction(function));
+        }
+
+        Environment {
+            scope: Rc::new(RefCell::new(globals)),
+            reader,
+            writer,
+            control: Control::Normal,
+            loops: 0,
+            depth: 0
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        self.scope.borrow().get(name)
+    }
+
+    pub fn set(&mut self, name: &str, value: Value) {
+        self.scope.borrow_mut().set(name, value);
+    }
+}
+
+//a writer that keeps everything written to it, clones share the same buffer so the
+//output can be read back after handing one to Environment::with_io
+#[derive(Clone, Default)]
+pub struct Capture {
+    buffer: Rc<RefCell<Vec<u8>>>
+}
+
+impl Capture {
+    pub fn new() -> Capture {
+        Capture::default()
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.buffer.borrow()).to_string()
+    }
+}
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
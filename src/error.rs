@@ -0,0 +1,61 @@
+use std::fmt::Display;
+use std::sync::Arc;
+use crate::lexer::Span;
+use crate::parser::Node;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorKind {
+    TypeError,
+    ZeroDivision,
+    OverflowError,
+    MemoryError,
+    UndefinedName,
+    IndexError,
+    KeyError,
+    ValueError,
+    AttributeError,
+    SyntaxError,
+    RecursionError,
+    IoError,
+    InternalError
+}
+
+//an error while evaluating, with the source line and the chars of the node that caused it
+#[derive(Debug, Clone)]
+pub struct RuntimeError {
+    pub kind: ErrorKind,
+    pub message: String,
+    pub line: Arc<str>,
+    pub span: Span
+}
+
+impl RuntimeError {
+    pub fn new(kind: ErrorKind, message: &str, node: &Node) -> RuntimeError {
+        RuntimeError {
+            kind,
+            message: message.to_string(),
+            line: node.line.clone(),
+            span: node.span()
+        }
+    }
+}
+
+//  TypeError: unsupported operand types for -: string and int
+//    line 2: x = "a" - 1
+//                ^^^^^^^
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let chars: Vec<char> = self.line.chars().collect();
+        let start = self.span.start.min(chars.len());
+
+        let line_start = chars[..start].iter().rposition(|char| *char == '\n').map(|index| index + 1).unwrap_or(0);
+        let line_end = chars[start..].iter().position(|char| *char == '\n').map(|index| start + index).unwrap_or(chars.len());
+        let number = chars[..start].iter().filter(|char| **char == '\n').count() + 1;
+
+        let prefix = format!("line {number}: ");
+        let text: String = chars[line_start..line_end].iter().collect();
+        let marker = " ".repeat(prefix.len() + start - line_start) + &"^".repeat((self.span.end.min(line_end) - start).max(1));
+
+        write!(f, "{:?}: {}\n  {}{}\n  {}", self.kind, self.message, prefix, text, marker)
+    }
+}
@@ -0,0 +1,166 @@
+use crate::lexer::Token;
+use crate::parser::{Assoc, Level, Parser, Rule};
+
+const BOX_HEIGHT: i32 = 24;
+const ROW_HEIGHT: i32 = 40;
+const GAP: i32 = 20;
+const RAIL: i32 = 30;
+const CHAR_WIDTH: i32 = 8;
+
+//fixed tokens are written as their text, the ones with a value by name
+fn symbol_text(symbol: &str) -> String {
+    if let Some(predicate) = symbol.strip_prefix('&').or(symbol.strip_prefix('!')) {
+        return format!("{}{}", &symbol[..1], symbol_text(predicate));
+    }
+
+    match Token::from_name(symbol) {
+        Some(token) => format!("\"{}\"", token.value()),
+        None => symbol.to_string()
+    }
+}
+
+fn level_comment(level: &Level) -> String {
+    let mut parts = vec![];
+    if level.name.is_some() {
+        parts.push(level.label());
+    }
+    parts.push(format!("power {}", level.power));
+    if level.assoc == Assoc::Right {
+        parts.push("right".to_string());
+    }
+
+    format!("(* {} *)", parts.join(", "))
+}
+
+//nonterminals in the order their first rule was registered
+fn nonterminals(parser: &Parser) -> Vec<String> {
+    let mut names: Vec<String> = vec![];
+
+    for level in &parser.levels {
+        for rule in &level.rules {
+            if !names.contains(&rule.name) {
+                names.push(rule.name.clone());
+            }
+        }
+    }
+
+    //the start symbol goes first, like the top of a reference
+    if let Some(start) = &parser.start {
+        if let Some(index) = names.iter().position(|name| name == start) {
+            let start = names.remove(index);
+            names.insert(0, start);
+        }
+    }
+
+    names
+}
+
+fn alternatives<'a>(parser: &'a Parser, name: &str) -> Vec<(&'a Level, &'a Rule)> {
+    parser.levels.iter().flat_map(|level| level.rules.iter().map(move |rule| (level, rule))).filter(|(_, rule)| rule.name == name).collect()
+}
+
+//one production per nonterminal, every alternative is annotated with its precedence level
+pub fn ebnf(parser: &Parser) -> String {
+    let mut lines = vec![];
+
+    for name in nonterminals(parser) {
+        let alternatives: Vec<(String, String)> = alternatives(parser, &name).iter().map(|(level, rule)| {
+            (rule.pattern.iter().map(|symbol| symbol_text(symbol)).collect::<Vec<String>>().join(" "), level_comment(level))
+        }).collect();
+        let width = alternatives.iter().map(|(pattern, _)| pattern.len()).max().unwrap_or(0);
+
+        for (index, (pattern, comment)) in alternatives.iter().enumerate() {
+            let prefix = if index == 0 { format!("{name} =") } else { format!("{} |", " ".repeat(name.len())) };
+            lines.push(format!("{prefix} {pattern:<width$}  {comment}"));
+        }
+        lines.push(format!("{} ;", " ".repeat(name.len())));
+        lines.push("".to_string());
+    }
+
+    lines.join("\n")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn box_width(text: &str) -> i32 {
+    text.chars().count() as i32 * CHAR_WIDTH + GAP
+}
+
+//a railroad diagram for one nonterminal: every alternative is a row of boxes between two rails,
+//terminals have round corners and nonterminals link to their own diagram
+pub fn railroad_svg(parser: &Parser, name: &str) -> String {
+    let rows: Vec<Vec<String>> = alternatives(parser, name).iter().map(|(_, rule)| rule.pattern.iter().map(|symbol| symbol_text(symbol)).collect()).collect();
+    let nonterminals = nonterminals(parser);
+
+    let inner = rows.iter().map(|row| row.iter().map(|text| box_width(text) + GAP).sum::<i32>() + GAP).max().unwrap_or(GAP);
+    let width = inner + 2 * RAIL;
+    let height = rows.len().max(1) as i32 * ROW_HEIGHT;
+    let top = ROW_HEIGHT / 2;
+    let bottom = top + (rows.len().max(1) as i32 - 1) * ROW_HEIGHT;
+
+    let mut svg = vec![
+        format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" class=\"railroad\">"),
+        format!("<circle cx=\"5\" cy=\"{top}\" r=\"4\"/>"),
+        format!("<circle cx=\"{}\" cy=\"{top}\" r=\"4\"/>", width - 5),
+        format!("<path d=\"M9 {top} H{RAIL} M{} {top} H{}\"/>", RAIL + inner, width - 9),
+        format!("<path d=\"M{RAIL} {top} V{bottom} M{} {top} V{bottom}\"/>", RAIL + inner)
+    ];
+
+    for (index, row) in rows.iter().enumerate() {
+        let y = top + index as i32 * ROW_HEIGHT;
+        svg.push(format!("<path d=\"M{RAIL} {y} H{}\"/>", RAIL + inner));
+
+        let mut x = RAIL + GAP;
+        for text in row {
+            let symbol = text.trim_start_matches(['&', '!']);
+            let width = box_width(text);
+            let radius = if nonterminals.iter().any(|name| name == symbol) { 0 } else { BOX_HEIGHT / 2 };
+            let rect = format!("<rect x=\"{x}\" y=\"{}\" width=\"{width}\" height=\"{BOX_HEIGHT}\" rx=\"{radius}\"/>", y - BOX_HEIGHT / 2);
+            let label = format!("<text x=\"{}\" y=\"{}\">{}</text>", x + width / 2, y + 4, escape(text));
+
+            if radius == 0 {
+                svg.push(format!("<a href=\"#{symbol}\">{rect}{label}</a>"));
+            } else {
+                svg.push(format!("{rect}{label}"));
+            }
+            x += width + GAP;
+        }
+    }
+
+    svg.push("</svg>".to_string());
+    svg.join("\n")
+}
+
+//a self contained page with the ebnf and a diagram per nonterminal
+pub fn railroad_html(parser: &Parser) -> String {
+    let mut html = vec![
+        "<!DOCTYPE html>".to_string(),
+        "<html>".to_string(),
+        "<head>".to_string(),
+        "<meta charset=\"utf-8\">".to_string(),
+        "<title>fufu grammar</title>".to_string(),
+        "<style>".to_string(),
+        "body { font-family: sans-serif; }".to_string(),
+        "svg.railroad path { stroke: #333; stroke-width: 2; fill: none; }".to_string(),
+        "svg.railroad circle { fill: #333; }".to_string(),
+        "svg.railroad rect { stroke: #333; stroke-width: 2; fill: #ffc; }".to_string(),
+        "svg.railroad text { font-family: monospace; font-size: 13px; text-anchor: middle; }".to_string(),
+        "</style>".to_string(),
+        "</head>".to_string(),
+        "<body>".to_string(),
+        "<h1>Syntax</h1>".to_string(),
+        format!("<pre>{}</pre>", escape(&ebnf(parser))),
+    ];
+
+    for name in nonterminals(parser) {
+        html.push(format!("<h2 id=\"{name}\">{name}</h2>"));
+        html.push(railroad_svg(parser, &name));
+    }
+
+    html.push("</body>".to_string());
+    html.push("</html>".to_string());
+    html.join("\n")
+}
+
@@ -0,0 +1,157 @@
+use std::fs;
+use std::path::Path;
+use crate::lexer::{self, Token};
+use crate::parser::{Node, Parser};
+use crate::unparse::token_text;
+
+//compact notation for parse shapes: (Add 1 (Mul 2 3)), operator tokens are left out
+//and rules over a single token like Num are written as just the token
+pub fn sexpr(node: &Node) -> String {
+    if node.class_name == "TOKEN" {
+        return token_text(node.token.as_ref().unwrap());
+    }
+
+    if node.token_values.len() == 1 && node.token_values[0].class_name == "TOKEN" {
+        return sexpr(&node.token_values[0]);
+    }
+
+    let mut parts = vec![node.class_name.clone()];
+
+    for child in &node.token_values {
+        let is_value = match &child.token {
+            Some(Token::NUM(_, _) | Token::STRING(_) | Token::NAME(_)) => true,
+            Some(_) => false,
+            None => true
+        };
+
+        if is_value {
+            parts.push(sexpr(child));
+        }
+    }
+
+    format!("({})", parts.join(" "))
+}
+
+fn normalize(sexpr: &str) -> String {
+    sexpr.replace("(", " ( ").replace(")", " ) ").split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+//the expected shape can leave out wrappers with a single child like (Program (ExprStmt ...))
+pub fn check_parses(parser: &Parser, source: &str, expected: &str) -> Result<(), String> {
+    let ast = match parser.parse(&lexer::lex(source), source) {
+        Ok(ast) => ast,
+        Err(error) => return Err(format!("{source}: expected {expected}, got {error}"))
+    };
+
+    let normalized = normalize(expected);
+    let mut node = &ast;
+
+    loop {
+        if normalize(&sexpr(node)) == normalized {
+            return Ok(());
+        }
+
+        //the SEMI ending a statement doesn't count as a child
+        let children: Vec<&Node> = node.token_values.iter().filter(|child| child.token != Some(Token::SEMI)).collect();
+
+        match children.as_slice() {
+            [child] if child.class_name != "TOKEN" => node = child,
+            _ => return Err(format!("{source}: expected {expected}, got {}", sexpr(&ast)))
+        }
+    }
+}
+
+pub fn check_rejects(parser: &Parser, source: &str) -> Result<(), String> {
+    match parser.parse(&lexer::lex(source), source) {
+        Ok(ast) => Err(format!("{source}: expected a syntax error, got {}", sexpr(&ast))),
+        Err(_) => Ok(())
+    }
+}
+
+//the same for unit tests, they panic with the mismatch
+#[cfg(test)]
+pub fn assert_parses(parser: &Parser, source: &str, expected: &str) {
+    if let Err(error) = check_parses(parser, source, expected) {
+        panic!("{error}");
+    }
+}
+
+#[cfg(test)]
+pub fn assert_rejects(parser: &Parser, source: &str) {
+    if let Err(error) = check_rejects(parser, source) {
+        panic!("{error}");
+    }
+}
+
+//runs a snippet file with one case per line:
+//  1 + 2 * 3 => (Add 1 (Mul 2 3))
+//  1 + => error
+//empty lines and lines starting with # are skipped
+pub fn run_file(parser: &Parser, path: &Path) -> (usize, Vec<String>) {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(error) => return (0, vec![format!("{}: couldnt read file: {error}", path.display())])
+    };
+
+    let mut passed = 0;
+    let mut failures = vec![];
+
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let result = match line.rsplit_once("=>") {
+            Some((source, expected)) if expected.trim() == "error" => check_rejects(parser, source.trim()),
+            Some((source, expected)) => check_parses(parser, source.trim(), expected.trim()),
+            None => Err("missing => in case".to_string())
+        };
+
+        match result {
+            Ok(()) => passed += 1,
+            Err(error) => failures.push(format!("{}:{}: {}", path.display(), number + 1, error))
+        }
+    }
+
+    (passed, failures)
+}
+
+//every .fufu file in the directory, sorted by name
+pub fn run_dir(parser: &Parser, dir: &Path) -> (usize, Vec<String>) {
+    let mut paths = match fs::read_dir(dir) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).filter(|path| path.extension().is_some_and(|extension| extension == "fufu")).collect::<Vec<_>>(),
+        Err(error) => return (0, vec![format!("{}: couldnt read directory: {error}", dir.display())])
+    };
+    paths.sort();
+
+    let mut passed = 0;
+    let mut failures = vec![];
+
+    for path in paths {
+        let (file_passed, mut file_failures) = run_file(parser, &path);
+        passed += file_passed;
+        failures.append(&mut file_failures);
+    }
+
+    (passed, failures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Mode;
+
+    //the snippets in tests/grammar, with every parser
+    #[test]
+    fn grammar_files() {
+        for mode in [Mode::Rewrite, Mode::Earley, Mode::Peg] {
+            let mut parser = crate::grammar().unwrap();
+            parser.set_mode(mode);
+
+            let (passed, failures) = run_dir(&parser, Path::new("tests/grammar"));
+            assert!(failures.is_empty(), "{:?}: {}", mode, failures.join("\n"));
+            assert!(passed > 0);
+        }
+    }
+}
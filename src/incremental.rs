@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::lexer::{self, Span, Token};
+use crate::parser::{fits, is_sequence, Node, ParseError, Parser, Rule};
+use crate::unparse::Rng;
+
+//replaces the chars start..end of the source with `text`
+#[derive(Debug, Clone)]
+pub struct Edit {
+    pub start: usize,
+    pub end: usize,
+    pub text: String
+}
+
+//a parsed source that can be edited without lexing and parsing all of it again
+pub struct Document {
+    pub source: String,
+    pub tokens: Vec<Token>,
+    pub spans: Vec<Span>,
+    pub tree: Result<Node, ParseError>,
+    //how many subtrees the last edit could keep and how many tokens it lexed again
+    pub reused: usize,
+    pub relexed: usize,
+    //how many brackets are open before every token
+    depths: Vec<usize>
+}
+
+impl Document {
+    pub fn new(parser: &Parser, source: &str) -> Document {
+        let lexed = lexer::lex_spanned(source);
+        let depths = depths(&lexed);
+        let (tokens, spans): (Vec<Token>, Vec<Span>) = lexed.into_iter().unzip();
+        let tree = parser.parse(&tokens, source);
+
+        Document {
+            source: source.to_string(),
+            tokens,
+            spans,
+            tree,
+            reused: 0,
+            relexed: 0,
+            depths
+        }
+    }
+
+    pub fn edit(&mut self, parser: &Parser, edit: &Edit) -> &Result<Node, ParseError> {
+        let chars: Vec<char> = self.source.chars().collect();
+        let start = edit.start.min(chars.len());
+        let end = edit.end.clamp(start, chars.len());
+        let delta = edit.text.chars().count() as isize - (end - start) as isize;
+
+        let source: String = chars[..start].iter().collect::<String>() + &edit.text + &chars[end..].iter().collect::<String>();
+
+        //tokens touching the edit plus one on each side, a string can swallow the rest of the source
+        let mut first = self.spans.iter().position(|span| span.end >= start).unwrap_or(self.tokens.len());
+        let mut last = self.spans.iter().rposition(|span| span.start <= end).map(|index| index + 1).unwrap_or(0).max(first);
+        first = first.saturating_sub(1);
+        last = (last + 1).min(self.tokens.len());
+
+        //whether a minus is the sign of a number depends on the token before it
+        if first > 0 && first < self.tokens.len() && self.tokens[first] == Token::MINUS {
+            first -= 1;
+        }
+
+        if edit.text.contains('"') || self.tokens[first..last].iter().any(|token| matches!(token, Token::STRING(_) | Token::ERRORTOKEN(_))) {
+            last = self.tokens.len();
+        }
+
+        //only the top level statements around the edit are lexed again. if the brackets in them
+        //dont add up anymore or they dont end a statement the ones after them change too
+        let mut window = (self.boundary_before(first), self.boundary_after(last));
+        let mut lexed = self.lex(&source, window, delta);
+
+        if window.1 < self.tokens.len() && (lexed.last().map(|(token, _)| token) != Some(&Token::SEMI) || depths(&lexed)[lexed.len()] != 0) {
+            window = (0, self.tokens.len());
+            lexed = self.lex(&source, window, delta);
+        }
+
+        //subtrees with at least one unchanged token between them and the edit keep their shape,
+        //as long as the tokens around the edit stayed the same
+        let old = &self.tokens[window.0..window.1];
+        let same_start = old.iter().zip(lexed.iter()).take_while(|(token, (lexed, _))| *token == lexed).count();
+        let same_end = old.iter().rev().zip(lexed.iter().rev()).take_while(|(token, (lexed, _))| *token == lexed).count();
+        let token_delta = lexed.len() as isize - old.len() as isize;
+
+        let mut reuse = Reuse::new(parser, first, last);
+        if let Ok(tree) = &mut self.tree {
+            if same_start >= first - window.0 && same_end >= window.1 - last {
+                for child in std::mem::take(&mut tree.token_values) {
+                    if let Some(child) = reuse.collect(child) {
+                        reuse.keep(child);
+                    }
+                }
+            }
+        }
+
+        let mut reused = reuse.nodes;
+        for node in reused.iter_mut() {
+            if node.index > last {
+                node.shift(token_delta);
+            }
+        }
+
+        //the depths after the window stay the same, the brackets in it add up
+        let mut window_depths = depths(&lexed);
+        let replaced = if window.1 < self.tokens.len() {
+            window_depths.pop();
+            window.1
+        } else {
+            window.1 + 1
+        };
+        self.depths.splice(window.0..replaced, window_depths);
+
+        for span in self.spans[window.1..].iter_mut() {
+            span.start = (span.start as isize + delta) as usize;
+            span.end = (span.end as isize + delta) as usize;
+        }
+
+        self.relexed = lexed.len();
+        let (tokens, spans): (Vec<Token>, Vec<Span>) = lexed.into_iter().unzip();
+        self.tokens.splice(window.0..window.1, tokens);
+        self.spans.splice(window.0..window.1, spans);
+
+        self.reused = reused.len();
+        self.tree = parser.parse_reusing(&self.tokens, &source, reused);
+        self.source = source;
+
+        &self.tree
+    }
+
+    //the start of the top level statement the token at `index` is in
+    fn boundary_before(&self, index: usize) -> usize {
+        (0..=index.min(self.tokens.len())).rev().find(|index| self.boundary(*index)).unwrap_or(0)
+    }
+
+    //the end of the top level statement the token before `index` is in
+    fn boundary_after(&self, index: usize) -> usize {
+        (index..self.tokens.len()).find(|index| self.boundary(*index)).unwrap_or(self.tokens.len())
+    }
+
+    //whether a top level statement starts at the token, an else or elif still belongs to the if
+    fn boundary(&self, index: usize) -> bool {
+        index == 0 || index == self.tokens.len() || (self.depths[index] == 0 && self.tokens[index - 1] == Token::SEMI && !matches!(self.tokens[index], Token::ELSE | Token::ELIF))
+    }
+
+    //the tokens of the statements in the window, lexed from the edited source
+    fn lex(&self, source: &str, window: (usize, usize), delta: isize) -> Vec<(Token, Span)> {
+        let start = if window.0 > 0 { self.spans[window.0 - 1].end } else { 0 };
+        let end = if window.1 < self.tokens.len() { (self.spans[window.1].start as isize + delta) as usize } else { source.chars().count() };
+
+        let text: String = source.chars().skip(start).take(end - start).collect();
+        let mut lexed = lexer::lex_spanned(&text);
+
+        for (_, span) in lexed.iter_mut() {
+            span.start += start;
+            span.end += start;
+        }
+
+        lexed
+    }
+}
+
+//how many brackets are open before every token, and after the last one
+fn depths(tokens: &[(Token, Span)]) -> Vec<usize> {
+    let mut depths: Vec<usize> = vec![0];
+
+    for (token, _) in tokens {
+        let depth = depths[depths.len() - 1];
+        depths.push(match token {
+            Token::LPAR | Token::LSQB | Token::LBRACE => depth + 1,
+            Token::RPAR | Token::RSQB | Token::RBRACE => depth.saturating_sub(1),
+            _ => depth
+        });
+    }
+
+    depths
+}
+
+//the rewrite loop joins statements (stmts -> stmts stmts) into one flat node as soon as they
+//are done, so a kept subtree is only the same as in a full parse if it is done at the same
+//point: before any reduction looser than the joins. the joins themselves depend on the
+//statements around them, so they are only kept inside a subtree that has all the statements
+//they join
+struct Reuse<'a> {
+    powers: HashMap<&'a str, Vec<(&'a Rule, i32)>>,
+    sequence: i32,
+    first: usize,
+    last: usize,
+    nodes: Vec<Node>
+}
+
+impl<'a> Reuse<'a> {
+    fn new(parser: &'a Parser, first: usize, last: usize) -> Reuse<'a> {
+        let mut powers: HashMap<&str, Vec<(&Rule, i32)>> = HashMap::new();
+        let mut sequence = i32::MIN;
+
+        for level in &parser.levels {
+            for rule in level.rules.iter().filter(|rule| !rule.group) {
+                powers.entry(rule.class_name.as_str()).or_default().push((rule, level.power));
+
+                if is_sequence(&rule.pattern, &rule.name) {
+                    sequence = sequence.max(level.power);
+                }
+            }
+        }
+
+        Reuse { powers, sequence, first, last, nodes: vec![] }
+    }
+
+    fn power(&self, node: &Node) -> Option<i32> {
+        self.powers.get(node.class_name.as_str())?.iter().find(|(rule, _)| fits(&rule.pattern, &rule.name, &node.token_names)).map(|(_, power)| *power)
+    }
+
+    //returns the node if all of it is outside first..last and done before the looser reductions
+    fn collect(&mut self, mut node: Node) -> Option<Node> {
+        if node.class_name == "TOKEN" {
+            return Some(node);
+        }
+
+        let outside = node.end < self.first || node.index > self.last;
+        let mut whole = outside && self.power(&node).is_some_and(|power| power >= self.sequence);
+        let mut kept = vec![];
+
+        for child in std::mem::take(&mut node.token_values) {
+            match self.collect(child) {
+                Some(child) => kept.push(child),
+                None => whole = false
+            }
+        }
+
+        if whole {
+            node.token_values = kept;
+            return Some(node);
+        }
+
+        for child in kept {
+            self.keep(child);
+        }
+        None
+    }
+
+    //keeps a node that has all of it, or the statements of a join
+    fn keep(&mut self, node: Node) {
+        if node.class_name == "TOKEN" {
+            return;
+        }
+        if self.power(&node).is_some_and(|power| power > self.sequence) {
+            self.nodes.push(node);
+            return;
+        }
+
+        for child in node.token_values {
+            self.keep(child);
+        }
+    }
+}
+
+//the kept subtrees have to point at the edited source too, or their errors show the old one
+fn same_line(node: &Node, line: &Arc<str>) -> bool {
+    Arc::ptr_eq(&node.line, line) && node.token_values.iter().all(|child| same_line(child, line))
+}
+
+//applies `count` random edits and compares every incremental result with a full parse,
+//returns how many subtrees could be reused in total
+pub fn check_incremental(parser: &Parser, source: &str, count: usize, seed: u64) -> Result<usize, String> {
+    let mut rng = Rng::new(seed);
+    let mut document = Document::new(parser, source);
+    let mut reused = 0;
+
+    let operators = ["+", "-", "*", "/", "==", "<"];
+    let operands = ["1", "23", "a", "foo", "(4 - b)", "\"hi\"", "\"a b\"", "[1, a]", "f(2)", "größe", "\"grüße\""];
+    let statements = ["x = 1", "ä = \"ö\"", "if a { b = 2 }", "while c < 3 { c += 1 }", "s = \"hi there\"", "print(a, \"b\")", "fn g(a) { return a }", "for i in xs { if i { break } }"];
+    let pieces = [" {", "}", " else { y = 3 }", " elif a { y = 4 }", "\"", "\n", "(", ")"];
+
+    for _ in 0..count {
+        //mostly edits that keep the source valid, sometimes ones that break it
+        let index = rng.next(document.tokens.len().max(1)).min(document.tokens.len().saturating_sub(1));
+        //spans and edits count chars, not bytes
+        let chars: Vec<char> = document.source.chars().collect();
+        let span = document.spans.get(index).copied().unwrap_or(Span { start: 0, end: 0 });
+        let operator = operators[rng.next(operators.len())];
+        let operand = operands[rng.next(operands.len())];
+        let statement = statements[rng.next(statements.len())];
+        let piece = pieces[rng.next(pieces.len())];
+
+        let edit = match (rng.next(10), document.tokens.get(index)) {
+            (0, Some(Token::PLUS | Token::MINUS | Token::STAR | Token::SLASH)) => Edit { start: span.start, end: span.end, text: operator.to_string() },
+            (1, _) => Edit { start: span.end, end: span.end, text: format!(" {operator} {operand}") },
+            (2, Some(Token::NUM(_, _) | Token::NAME(_) | Token::STRING(_))) => Edit { start: span.start, end: span.end, text: operand.to_string() },
+            (3, _) => Edit { start: span.start, end: span.end, text: "".to_string() },
+            (4 | 5, _) => {
+                //a new line before or after the one of the token
+                let line = chars[..span.start].iter().rposition(|char| *char == '\n').map(|index| index + 1).unwrap_or(0);
+                Edit { start: line, end: line, text: format!("{statement}\n") }
+            },
+            (6, _) => {
+                //the whole line of the token
+                let line = chars[..span.start].iter().rposition(|char| *char == '\n').map(|index| index + 1).unwrap_or(0);
+                let end = chars[span.start..].iter().position(|char| *char == '\n').map(|index| span.start + index + 1).unwrap_or(chars.len());
+                Edit { start: line, end, text: "".to_string() }
+            },
+            (7, _) => Edit { start: span.end, end: span.end, text: piece.to_string() },
+            _ => Edit { start: span.start, end: span.start, text: format!("{operand} {operator} ") }
+        };
+
+        let incremental = document.edit(parser, &edit).clone();
+        let tokens = lexer::lex(&document.source);
+        let full = parser.parse(&tokens, &document.source);
+
+        let same = match (&incremental, &full) {
+            (Ok(incremental), Ok(full)) => incremental == full && *incremental.line == *document.source && same_line(incremental, &incremental.line),
+            (Err(_), Err(_)) => true,
+            _ => false
+        };
+
+        if !same || document.tokens != tokens {
+            return Err(format!("incremental parse differs after {:?}: {}\n  incremental: {:?}\n  full: {:?}", edit, document.source, incremental, full));
+        }
+
+        reused += document.reused;
+
+        if incremental.is_err() || document.source.chars().count() > source.chars().count() * 2 {
+            document = Document::new(parser, source);
+        }
+    }
+
+    Ok(reused)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //functions with blocks, strings and calls, 9 lines each
+    fn program(functions: usize) -> String {
+        let mut source = String::new();
+
+        for i in 0..functions {
+            source += &format!("fn f{i}(a) {{\n    if a > {i} {{\n        return a * {i}\n    }} else {{\n        s = \"value {i}\"\n    }}\n    return a\n}}\nx{i} = f{i}({i}) + [1, 2, {i}][2]\n");
+        }
+
+        source
+    }
+
+    #[test]
+    fn edits_match_full_parse() {
+        let parser = crate::grammar().unwrap();
+        check_incremental(&parser, &program(3), 100, 3).unwrap();
+    }
+
+    #[test]
+    fn non_ascii_source() {
+        let parser = crate::grammar().unwrap();
+        let source = program(3).replace("value", "wärme größe").replace('x', "ä");
+        check_incremental(&parser, &source, 200, 5).unwrap();
+    }
+
+    //an edit in a program of a few hundred lines only lexes the function it is in again, and
+    //keeps the other functions
+    #[test]
+    fn edit_keeps_the_rest() {
+        let parser = crate::grammar().unwrap();
+        let source = program(50);
+        let mut document = Document::new(&parser, &source);
+        let function = Document::new(&parser, &program(1)).tokens.len();
+
+        for i in 0..10 {
+            let start = document.source.find(&format!("return a * {}\n", 20 + i)).unwrap() + "return a * ".len();
+            let edit = Edit { start, end: start + 2, text: format!("({} + b)", i) };
+
+            assert!(document.edit(&parser, &edit).is_ok());
+            let tree = parser.parse(&lexer::lex(&document.source), &document.source);
+            assert_eq!(document.tree.as_ref().ok(), tree.as_ref().ok());
+
+            assert!(document.relexed < function, "lexed {} tokens again", document.relexed);
+            assert!(document.reused >= 49 * 2, "reused {} subtrees", document.reused);
+        }
+    }
+}
@@ -2,22 +2,46 @@ use std::fmt::Debug;
 
 
 
-#[derive(Debug, Clone)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum NumType {
     FLOAT,
-    INT
+    INT,
+    //an exact fraction written with an r at the end, like 0.1r
+    RATIONAL
 }
 
 
 
 
-#[derive(Clone)]
+//named like pythons token module, not all of its tokens are produced yet
+#[allow(dead_code, non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Clone, PartialEq)]
 pub enum Token {
-    NUM(f32, NumType),
+    //the literal as it was written, so an int isnt rounded before it is evaluated
+    NUM(String, NumType),
     STRING(String),
     NAME(String),
+    //text that isnt a token, with what is wrong with it. no rule matches it so the parser
+    //reports it as a syntax error
+    ERRORTOKEN(String),
     PRINT,
     INPUT,
+    TRUE,
+    FALSE,
+    AND,
+    OR,
+    NOT,
+    IF,
+    ELIF,
+    ELSE,
+    WHILE,
+    FOR,
+    IN,
+    BREAK,
+    CONTINUE,
+    FN,
+    RETURN,
 
     LPAR,
     RPAR,
@@ -82,20 +106,20 @@ impl Debug for Token {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         unsafe {
             if AST_PRINT {
-                return match self {
+                match self {
                     Self::NUM(inner, _) => write!(f, "{}", inner),
                     Self::STRING(inner) => write!(f, "{}", inner),
                     Self::NAME(inner) => write!(f, "{}", inner),
-                    other => write!(f, ""),
-                };
+                    _ => write!(f, ""),
+                }
             }
             else {
-                return match self {
+                match self {
                     Self::NUM(inner, inner2) => write!(f, "NUM({}, {:?})", inner, inner2),
                     Self::STRING(inner) => write!(f, "STRING({})", inner),
                     Self::NAME(inner) => write!(f, "NAME({})", inner),
                     other => write!(f, "{}", other.name()),
-                };
+                }
             }
         }
     }
@@ -107,9 +131,8 @@ macro_rules! impl_variant_name {
             fn variant_name(&self) -> &str {
                 match self {
                     $(
-                        $enum::$variant$(($($field),+))? => stringify!($variant),
+                        $enum::$variant { .. } => stringify!($variant),
                     )*
-                    _ => panic!("Variant not included in macro call"),
                 }
             }
         }
@@ -118,28 +141,28 @@ macro_rules! impl_variant_name {
 
 
 impl Token {
-    pub fn get_num(&self) -> Option<f32> {
+    pub fn get_num(&self) -> Option<String> {
         match self {
-            Token::NUM(number, type_of_number) => Some(*number),
-            other => None
+            Token::NUM(number, _) => Some(number.to_string()),
+            _ => None
         }
     }
     pub fn get_num_type(&self) -> Option<NumType> {
         match self {
-            Token::NUM(number, type_of_number) => Some(type_of_number.clone()),
-            other => None
+            Token::NUM(_, type_of_number) => Some(type_of_number.clone()),
+            _ => None
         }
     }
     pub fn get_string(&self) -> Option<String> {
         match self {
             Token::STRING(string) => Some(string.to_string()),
-            other => None
+            _ => None
         }
     }
     pub fn get_name(&self) -> Option<String> {
         match self {
             Token::NAME(name) => Some(name.to_string()),
-            other => None
+            _ => None
         }
     }
     pub fn value(&self) -> String {
@@ -152,9 +175,137 @@ impl Token {
             Token::MINUS => "-".to_string(),
             Token::PLUS => "+".to_string(),
             Token::EQUAL => "=".to_string(),
+            Token::LPAR => "(".to_string(),
+            Token::RPAR => ")".to_string(),
+            Token::COMMA => ",".to_string(),
+            Token::LSQB => "[".to_string(),
+            Token::RSQB => "]".to_string(),
+            Token::COLON => ":".to_string(),
+            Token::LESS => "<".to_string(),
+            Token::GREATER => ">".to_string(),
+            Token::EQEQUAL => "==".to_string(),
+            Token::NOTEQUAL => "!=".to_string(),
+            Token::LESSEQUAL => "<=".to_string(),
+            Token::GREATEREQUAL => ">=".to_string(),
             Token::PRINT => "print".to_string(),
             Token::INPUT => "input".to_string(),
-            other => todo!()
+            Token::TRUE => "true".to_string(),
+            Token::FALSE => "false".to_string(),
+            Token::AND => "and".to_string(),
+            Token::OR => "or".to_string(),
+            Token::NOT => "not".to_string(),
+            Token::IF => "if".to_string(),
+            Token::ELIF => "elif".to_string(),
+            Token::ELSE => "else".to_string(),
+            Token::WHILE => "while".to_string(),
+            Token::FOR => "for".to_string(),
+            Token::IN => "in".to_string(),
+            Token::BREAK => "break".to_string(),
+            Token::CONTINUE => "continue".to_string(),
+            Token::FN => "fn".to_string(),
+            Token::RETURN => "return".to_string(),
+            Token::RARROW => "->".to_string(),
+            Token::DOT => ".".to_string(),
+            Token::PERCENT => "%".to_string(),
+            Token::DOUBLESLASH => "//".to_string(),
+            Token::DOUBLESTAR => "**".to_string(),
+            Token::AMPER => "&".to_string(),
+            Token::VBAR => "|".to_string(),
+            Token::CIRCUMFLEX => "^".to_string(),
+            Token::TILDE => "~".to_string(),
+            Token::LEFTSHIFT => "<<".to_string(),
+            Token::RIGHTSHIFT => ">>".to_string(),
+            Token::PLUSEQUAL => "+=".to_string(),
+            Token::MINEQUAL => "-=".to_string(),
+            Token::STAREQUAL => "*=".to_string(),
+            Token::SLASHEQUAL => "/=".to_string(),
+            Token::PERCENTEQUAL => "%=".to_string(),
+            Token::DOUBLESTAREQUAL => "**=".to_string(),
+            Token::DOUBLESLASHEQUAL => "//=".to_string(),
+            Token::AMPEREQUAL => "&=".to_string(),
+            Token::VBAREQUAL => "|=".to_string(),
+            Token::CIRCUMFLEXEQUAL => "^=".to_string(),
+            Token::LEFTSHIFTEQUAL => "<<=".to_string(),
+            Token::RIGHTSHIFTEQUAL => ">>=".to_string(),
+            Token::COLONEQUAL => ":=".to_string(),
+            Token::SEMI => ";".to_string(),
+            Token::LBRACE => "{".to_string(),
+            Token::RBRACE => "}".to_string(),
+            Token::AT => "@".to_string(),
+            Token::ATEQUAL => "@=".to_string(),
+            Token::ELLIPSIS => "...".to_string(),
+            Token::AWAIT => "await".to_string(),
+            Token::ASYNC => "async".to_string(),
+            Token::ERRORTOKEN(error) => error.to_string(),
+            //never lexed, they dont stand for any text
+            Token::OP | Token::TYPE_IGNORE | Token::TYPE_COMMENT | Token::SOFT_KEYWORD => "".to_string()
+        }
+    }
+    //inverse of name() for tokens without a value
+    pub fn from_name(name: &str) -> Option<Token> {
+        match name {
+            "SLASH" => Some(Token::SLASH),
+            "STAR" => Some(Token::STAR),
+            "MINUS" => Some(Token::MINUS),
+            "PLUS" => Some(Token::PLUS),
+            "EQUAL" => Some(Token::EQUAL),
+            "LPAR" => Some(Token::LPAR),
+            "RPAR" => Some(Token::RPAR),
+            "COMMA" => Some(Token::COMMA),
+            "LSQB" => Some(Token::LSQB),
+            "RSQB" => Some(Token::RSQB),
+            "COLON" => Some(Token::COLON),
+            "LESS" => Some(Token::LESS),
+            "GREATER" => Some(Token::GREATER),
+            "EQEQUAL" => Some(Token::EQEQUAL),
+            "NOTEQUAL" => Some(Token::NOTEQUAL),
+            "LESSEQUAL" => Some(Token::LESSEQUAL),
+            "GREATEREQUAL" => Some(Token::GREATEREQUAL),
+            "PRINT" => Some(Token::PRINT),
+            "INPUT" => Some(Token::INPUT),
+            "TRUE" => Some(Token::TRUE),
+            "FALSE" => Some(Token::FALSE),
+            "AND" => Some(Token::AND),
+            "OR" => Some(Token::OR),
+            "NOT" => Some(Token::NOT),
+            "IF" => Some(Token::IF),
+            "ELIF" => Some(Token::ELIF),
+            "ELSE" => Some(Token::ELSE),
+            "WHILE" => Some(Token::WHILE),
+            "FOR" => Some(Token::FOR),
+            "IN" => Some(Token::IN),
+            "BREAK" => Some(Token::BREAK),
+            "CONTINUE" => Some(Token::CONTINUE),
+            "FN" => Some(Token::FN),
+            "RETURN" => Some(Token::RETURN),
+            "RARROW" => Some(Token::RARROW),
+            "DOT" => Some(Token::DOT),
+            "PERCENT" => Some(Token::PERCENT),
+            "DOUBLESLASH" => Some(Token::DOUBLESLASH),
+            "DOUBLESTAR" => Some(Token::DOUBLESTAR),
+            "AMPER" => Some(Token::AMPER),
+            "VBAR" => Some(Token::VBAR),
+            "CIRCUMFLEX" => Some(Token::CIRCUMFLEX),
+            "TILDE" => Some(Token::TILDE),
+            "LEFTSHIFT" => Some(Token::LEFTSHIFT),
+            "RIGHTSHIFT" => Some(Token::RIGHTSHIFT),
+            "PLUSEQUAL" => Some(Token::PLUSEQUAL),
+            "MINEQUAL" => Some(Token::MINEQUAL),
+            "STAREQUAL" => Some(Token::STAREQUAL),
+            "SLASHEQUAL" => Some(Token::SLASHEQUAL),
+            "PERCENTEQUAL" => Some(Token::PERCENTEQUAL),
+            "DOUBLESTAREQUAL" => Some(Token::DOUBLESTAREQUAL),
+            "DOUBLESLASHEQUAL" => Some(Token::DOUBLESLASHEQUAL),
+            "AMPEREQUAL" => Some(Token::AMPEREQUAL),
+            "VBAREQUAL" => Some(Token::VBAREQUAL),
+            "CIRCUMFLEXEQUAL" => Some(Token::CIRCUMFLEXEQUAL),
+            "LEFTSHIFTEQUAL" => Some(Token::LEFTSHIFTEQUAL),
+            "RIGHTSHIFTEQUAL" => Some(Token::RIGHTSHIFTEQUAL),
+            "COLONEQUAL" => Some(Token::COLONEQUAL),
+            "SEMI" => Some(Token::SEMI),
+            "LBRACE" => Some(Token::LBRACE),
+            "RBRACE" => Some(Token::RBRACE),
+            _ => None
         }
     }
     pub fn name(&self) -> String {
@@ -170,6 +321,21 @@ impl Token {
 impl_variant_name!(Token, 
     PRINT,
     INPUT,
+    TRUE,
+    FALSE,
+    AND,
+    OR,
+    NOT,
+    IF,
+    ELIF,
+    ELSE,
+    WHILE,
+    FOR,
+    IN,
+    BREAK,
+    CONTINUE,
+    FN,
+    RETURN,
     LPAR,
     RPAR,
     LSQB,
@@ -223,101 +389,271 @@ impl_variant_name!(Token,
     TYPE_IGNORE,
     TYPE_COMMENT,
     SOFT_KEYWORD,
-    NUM(i32, NumType),
+    NUM(String, NumType),
     STRING(String),
-    NAME(String));
+    NAME(String),
+    ERRORTOKEN(String));
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize
+}
 
 macro_rules! add_num_to_tokens {
     ($num: expr, $tokens: expr, $index: expr) => {
+        if $num != "" && $num.trim_end_matches('r').parse::<f64>().is_err() {
+            $tokens.push((Token::ERRORTOKEN(format!("a number instead of {}", $num)), Span { start: $index - $num.len(), end: $index }));
+            $num.clear();
+        }
         if $num != "" {
-                    $tokens.push(Token::NUM(
-            $num.parse().expect("couldnt parse num"), 
+                    $tokens.push((Token::NUM(
+            $num.clone(), 
             
-            if $num.contains(".") 
+            if $num.ends_with("r") 
+            {
+                NumType::RATIONAL
+            } 
+            else if $num.contains(".") 
             {
                 NumType::FLOAT
             } 
             else {
                 NumType::INT
             }
-        ));
+        ), Span { start: $index - $num.len(), end: $index }));
 
-        $num = "".to_string();
+        $num.clear();
         }
     };
 }
 
 pub fn lex(line: &str) -> Vec<Token> {
+    lex_spanned(line).into_iter().map(|(token, _)| token).collect()
+}
+
+//same as lex, but every token comes with the char range it was lexed from
+pub fn lex_spanned(line: &str) -> Vec<(Token, Span)> {
+    normalize(lex_raw(line))
+}
+
+//statements end with a SEMI, a newline is one too unless it is inside parentheses, brackets
+//or a map literal. empty statements are left out, every block gets one before its } and
+//an else or elif after the } continues the statement
+pub fn normalize(tokens: Vec<(Token, Span)>) -> Vec<(Token, Span)> {
+    let mut normalized: Vec<(Token, Span)> = vec![];
+    //the open brackets, a { after a value or else opens a block ('b'), otherwise a map
+    let mut open: Vec<char> = vec![];
+
+    for (token, span) in tokens {
+        let last = normalized.last().map(|(token, _)| token.clone());
+
+        match token {
+            Token::SEMI => {
+                let inside = matches!(open.last(), Some('(' | '[' | '{'));
+                let empty = matches!(last, None | Some(Token::SEMI)) || (last == Some(Token::LBRACE) && open.last() == Some(&'b'));
+
+                if inside || empty {
+                    continue;
+                }
+            }
+            Token::LPAR => open.push('('),
+            Token::LSQB => open.push('['),
+            Token::LBRACE => {
+                let block = matches!(last, Some(Token::NUM(_, _) | Token::STRING(_) | Token::NAME(_) | Token::RPAR | Token::RSQB | Token::RBRACE | Token::TRUE | Token::FALSE | Token::ELSE));
+                open.push(if block { 'b' } else { '{' });
+            }
+            Token::RPAR | Token::RSQB => {
+                open.pop();
+            }
+            Token::RBRACE => {
+                let closes_block = open.pop() == Some('b');
+                if closes_block && last != Some(Token::SEMI) {
+                    normalized.push((Token::SEMI, Span { start: span.start, end: span.start }));
+                }
+            }
+            Token::ELSE | Token::ELIF => {
+                let after_block = normalized.len() >= 2 && normalized[normalized.len() - 2].0 == Token::RBRACE;
+                if last == Some(Token::SEMI) && after_block {
+                    normalized.pop();
+                }
+            }
+            _ => {}
+        }
+
+        normalized.push((token, span));
+    }
+
+    if let Some((last, span)) = normalized.last() {
+        if *last != Token::SEMI {
+            let end = span.end;
+            normalized.push((Token::SEMI, Span { start: end, end }));
+        }
+    }
+
+    normalized
+}
+
+//the tokens as they are in the source, without the statement ends sorted out
+pub fn lex_raw(line: &str) -> Vec<(Token, Span)> {
     let mut index = 0;
 
     let mut tokens = vec![];
     
     let mut num = "".to_string();
 
-    while index < line.len() {
-        let char = line.chars().collect::<Vec<char>>()[index];
+    let chars = line.chars().collect::<Vec<char>>();
+
+    while index < chars.len() {
+        let char = chars[index];
+
+        let triple = match (char, chars.get(index + 1).copied().unwrap_or(' '), chars.get(index + 2).copied().unwrap_or(' ')) {
+            ('*', '*', '=') => Some(Token::DOUBLESTAREQUAL),
+            ('/', '/', '=') => Some(Token::DOUBLESLASHEQUAL),
+            ('<', '<', '=') => Some(Token::LEFTSHIFTEQUAL),
+            ('>', '>', '=') => Some(Token::RIGHTSHIFTEQUAL),
+            _ => None
+        };
+
+        let double = match (char, chars.get(index + 1).copied().unwrap_or(' ')) {
+            ('=', '=') => Some(Token::EQEQUAL),
+            ('!', '=') => Some(Token::NOTEQUAL),
+            ('<', '=') => Some(Token::LESSEQUAL),
+            ('>', '=') => Some(Token::GREATEREQUAL),
+            ('-', '>') => Some(Token::RARROW),
+            ('/', '/') => Some(Token::DOUBLESLASH),
+            ('*', '*') => Some(Token::DOUBLESTAR),
+            ('<', '<') => Some(Token::LEFTSHIFT),
+            ('>', '>') => Some(Token::RIGHTSHIFT),
+            ('+', '=') => Some(Token::PLUSEQUAL),
+            ('-', '=') => Some(Token::MINEQUAL),
+            ('*', '=') => Some(Token::STAREQUAL),
+            ('/', '=') => Some(Token::SLASHEQUAL),
+            ('%', '=') => Some(Token::PERCENTEQUAL),
+            ('&', '=') => Some(Token::AMPEREQUAL),
+            ('|', '=') => Some(Token::VBAREQUAL),
+            ('^', '=') => Some(Token::CIRCUMFLEXEQUAL),
+            (':', '=') => Some(Token::COLONEQUAL),
+            _ => None
+        };
+
+        //a minus right before a digit is the sign of a number, unless it follows a value like in 3-1
+        let sign = char == '-' && num.is_empty() && chars.get(index + 1).is_some_and(|next| next.is_ascii_digit())
+            && !matches!(tokens.last(), Some((Token::NUM(_, _) | Token::STRING(_) | Token::NAME(_) | Token::RPAR | Token::RSQB, _)));
 
-        //println!("char: {} num: {}, line: {}", char, num, line);
+        let single = match char {
+            '=' => Some(Token::EQUAL),
+            '+' => Some(Token::PLUS),
+            '-' if !sign => Some(Token::MINUS),
+            '*' => Some(Token::STAR),
+            '/' => Some(Token::SLASH),
+            '(' => Some(Token::LPAR),
+            ')' => Some(Token::RPAR),
+            ',' => Some(Token::COMMA),
+            '[' => Some(Token::LSQB),
+            ']' => Some(Token::RSQB),
+            ':' => Some(Token::COLON),
+            '.' if num.is_empty() => Some(Token::DOT),
+            '%' => Some(Token::PERCENT),
+            '&' => Some(Token::AMPER),
+            '|' => Some(Token::VBAR),
+            '^' => Some(Token::CIRCUMFLEX),
+            '~' => Some(Token::TILDE),
+            '{' => Some(Token::LBRACE),
+            '}' => Some(Token::RBRACE),
+            ';' | '\n' => Some(Token::SEMI),
+            '<' => Some(Token::LESS),
+            '>' => Some(Token::GREATER),
+            _ => None
+        };
 
         if char == ' ' {
             add_num_to_tokens!(num, tokens, index);
         }
-        else if char == '=' {
+        else if let Some(token) = triple {
             add_num_to_tokens!(num, tokens, index);
-            tokens.push(Token::EQUAL);
+            tokens.push((token, Span { start: index, end: index + 3 }));
+            index += 2;
         }
-        else if char == '+' {
+        else if let Some(token) = double {
             add_num_to_tokens!(num, tokens, index);
-            tokens.push(Token::PLUS);
-        }
-        else if char == '-' {
-            add_num_to_tokens!(num, tokens, index);
-            tokens.push(Token::MINUS);
-        }
-        else if char == '*' {
-            add_num_to_tokens!(num, tokens, index);
-            tokens.push(Token::STAR);
+            tokens.push((token, Span { start: index, end: index + 2 }));
+            index += 1;
         }
-        else if char == '/' {
+        else if let Some(token) = single {
             add_num_to_tokens!(num, tokens, index);
-            tokens.push(Token::SLASH);
+            tokens.push((token, Span { start: index, end: index + 1 }));
         }
-        else if (num.len() == 0 && "-0123456789".contains(char)) || (num.len() > 0 && ".0123456789".contains(char)) {
+        else if (num.is_empty() && "-0123456789".contains(char)) || (!num.is_empty() && ".0123456789".contains(char)) {
             num += &char.to_string();
         }
+        else if !num.is_empty() && char == 'r' {
+            num += "r";
+            add_num_to_tokens!(num, tokens, index + 1);
+        }
         else if char == '"' {
             add_num_to_tokens!(num, tokens, index);
+            let start = index;
             let mut string = "".to_string();
             index += 1;
             loop {
-                let char = line.chars().collect::<Vec<char>>()[index];
+                let char = chars.get(index).copied().unwrap_or('\n');
 
-                if r"^1234567890ß´qwertzuiopü+asdfghjklöä#asdfghjklöä#<yxcvbnm,.- °!§$%&/()=?`*_:;>@€{}[\~'|".contains(char) {
-                    string += &char.to_string();
+                //\" and \\ stand for a quote and a backslash, other backslashes are kept as they are
+                if char == '\\' && matches!(chars.get(index + 1), Some('"' | '\\')) {
+                    string += &chars[index + 1].to_string();
+                    index += 1;
                 }
-                else {
-                    tokens.push(Token::STRING(string));
+                else if char == '"' {
+                    tokens.push((Token::STRING(string), Span { start, end: index + 1 }));
                     break;
                 }
+                //the newline is lexed again, it still ends the statement
+                else if char == '\n' {
+                    let text: String = chars[start..index].iter().collect();
+                    tokens.push((Token::ERRORTOKEN(format!("a closing \" after {text}")), Span { start, end: index }));
+                    index -= 1;
+                    break;
+                }
+                else {
+                    string += &char.to_string();
+                }
 
                 index += 1;
             }
         }
-        else if "qwertzuiopüasdfghjklöäyxcvbnmQWERTZUIOPÜASDFGHJKLÖÄYXCVBNMß".contains(char) {
+        else if "qwertzuiopüasdfghjklöäyxcvbnmQWERTZUIOPÜASDFGHJKLÖÄYXCVBNMß_".contains(char) {
             add_num_to_tokens!(num, tokens, index);
+            let start = index;
             let mut name = char.to_string();
             index += 1;
             loop {
-                let char = line.chars().collect::<Vec<char>>()[index];
+                let char = chars.get(index).copied().unwrap_or(' ');
 
-                if r"^1234567890qwertzuiopüasdfghjklöäyxcvbnmQWERTZUIOPÜASDFGHJKLÖÄYXCVBNMß".contains(char) {
+                if "1234567890qwertzuiopüasdfghjklöäyxcvbnmQWERTZUIOPÜASDFGHJKLÖÄYXCVBNMß_".contains(char) {
                     name += &char.to_string();
                 }
                 else {
+                    let span = Span { start, end: index };
                     match name.as_str() {
-                        "print" => tokens.push(Token::PRINT),
-                        "input" => tokens.push(Token::INPUT),
-                        name => tokens.push(Token::NAME(name.to_string()))
+                        "print" => tokens.push((Token::PRINT, span)),
+                        "input" => tokens.push((Token::INPUT, span)),
+                        "true" => tokens.push((Token::TRUE, span)),
+                        "false" => tokens.push((Token::FALSE, span)),
+                        "and" => tokens.push((Token::AND, span)),
+                        "or" => tokens.push((Token::OR, span)),
+                        "not" => tokens.push((Token::NOT, span)),
+                        "if" => tokens.push((Token::IF, span)),
+                        "elif" => tokens.push((Token::ELIF, span)),
+                        "else" => tokens.push((Token::ELSE, span)),
+                        "while" => tokens.push((Token::WHILE, span)),
+                        "for" => tokens.push((Token::FOR, span)),
+                        "in" => tokens.push((Token::IN, span)),
+                        "break" => tokens.push((Token::BREAK, span)),
+                        "continue" => tokens.push((Token::CONTINUE, span)),
+                        "fn" => tokens.push((Token::FN, span)),
+                        "return" => tokens.push((Token::RETURN, span)),
+                        name => tokens.push((Token::NAME(name.to_string()), span))
                     }
                     
                     index -= 1;
@@ -336,4 +672,38 @@ pub fn lex(line: &str) -> Vec<Token> {
     add_num_to_tokens!(num, tokens, index);
 
     tokens
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bad_number() {
+        assert_eq!(lex("print(1.2.3)"), [Token::PRINT, Token::LPAR, Token::ERRORTOKEN("a number instead of 1.2.3".to_string()), Token::RPAR, Token::SEMI]);
+        assert_eq!(lex_spanned("x = 1..2")[2], (Token::ERRORTOKEN("a number instead of 1..2".to_string()), Span { start: 4, end: 8 }));
+    }
+
+    //the string ends at the newline, the lines after it are lexed as usual
+    #[test]
+    fn unterminated_string() {
+        let tokens = lex("x = \"abc\ny = 1");
+        assert_eq!(tokens[2], Token::ERRORTOKEN("a closing \" after \"abc".to_string()));
+        assert_eq!(tokens[3..], [Token::SEMI, Token::NAME("y".to_string()), Token::EQUAL, Token::NUM("1".to_string(), NumType::INT), Token::SEMI]);
+
+        assert!(matches!(lex("\"abc")[0], Token::ERRORTOKEN(_)));
+        assert!(matches!(lex("\"abc\\\"")[0], Token::ERRORTOKEN(_)));
+    }
+
+    #[test]
+    fn strings_take_any_char() {
+        assert_eq!(lex("\"Hallo, Welt! \\o/ ]\"")[0], Token::STRING("Hallo, Welt! \\o/ ]".to_string()));
+    }
+
+    #[test]
+    fn unlexed_tokens_have_a_value() {
+        assert_eq!(Token::AT.value(), "@");
+        assert_eq!(Token::ELLIPSIS.value(), "...");
+        assert_eq!(Token::SOFT_KEYWORD.value(), "");
+    }
 } 
\ No newline at end of file
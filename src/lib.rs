@@ -0,0 +1,182 @@
+pub mod lexer;
+pub mod parser;
+pub mod readfile;
+pub mod ast;
+pub mod trace;
+pub mod unparse;
+pub mod earley;
+pub mod peg;
+pub mod incremental;
+pub mod grammar_test;
+pub mod codegen;
+pub mod typed_ast;
+pub mod grammar_doc;
+pub mod environment;
+pub mod error;
+pub mod value;
+pub mod builtins;
+pub mod bigint;
+pub mod rational;
+pub mod operators;
+pub mod bytecode;
+pub mod compiler;
+pub mod vm;
+pub mod program_test;
+
+pub const EVAL_STACK_SIZE: usize = 512 * 1024 * 1024;
+
+//the grammar of fufu
+pub fn grammar() -> Result<parser::Parser, String> {
+    #[allow(non_snake_case)]
+    let mut PG = parser::Parser::new();
+
+    PG.insert_level("atom", parser::LevelPosition::Power(300))?;
+    PG.insert_level("postfix", parser::LevelPosition::Power(250))?;
+    PG.insert_level("power", parser::LevelPosition::Power(240))?;
+    PG.insert_level("unary", parser::LevelPosition::Power(220))?;
+    PG.insert_level("multiplicative", parser::LevelPosition::Power(200))?;
+    PG.insert_level("additive", parser::LevelPosition::Power(100))?;
+    PG.insert_level("shift", parser::LevelPosition::Power(95))?;
+    PG.insert_level("bitand", parser::LevelPosition::Power(90))?;
+    PG.insert_level("bitxor", parser::LevelPosition::Power(88))?;
+    PG.insert_level("bitor", parser::LevelPosition::Power(85))?;
+    PG.insert_level("comparison", parser::LevelPosition::Power(80))?;
+    PG.insert_level("not", parser::LevelPosition::Power(70))?;
+    PG.insert_level("and", parser::LevelPosition::Power(60))?;
+    PG.insert_level("or", parser::LevelPosition::Power(55))?;
+    PG.insert_level("assignment", parser::LevelPosition::Power(50))?;
+    //:= binds looser than or and tighter than =, it gets a power between the two
+    PG.insert_level("walrus", parser::LevelPosition::After("or"))?;
+    PG.insert_level("entries", parser::LevelPosition::Power(40))?;
+    PG.insert_level("arguments", parser::LevelPosition::Power(20))?;
+    PG.insert_level("statement", parser::LevelPosition::Power(10))?;
+    PG.insert_level("block", parser::LevelPosition::Power(8))?;
+    PG.insert_level("statements", parser::LevelPosition::Power(7))?;
+    PG.insert_level("sequence", parser::LevelPosition::Power(6))?;
+    PG.insert_level("orelse", parser::LevelPosition::Power(5))?;
+    PG.insert_level("program", parser::LevelPosition::Power(0))?;
+
+    PG.add_rule_at("atom", vec!["NAME"], "expr", "Name")?;
+    PG.add_rule_at("atom", vec!["NUM"], "expr", "Num")?;
+    PG.add_rule_at("atom", vec!["STRING"], "expr", "String")?;
+    PG.add_rule_at("atom", vec!["TRUE"], "expr", "Bool")?;
+    PG.add_rule_at("atom", vec!["FALSE"], "expr", "Bool")?;
+    PG.add_group_rule("atom", vec!["LPAR", "expr", "RPAR"], "expr")?;
+    PG.add_rule_at("atom", vec!["LSQB", "RSQB"], "expr", "List")?;
+    PG.add_rule_at("atom", vec!["LSQB", "expr", "RSQB"], "expr", "List")?;
+    PG.add_rule_at("atom", vec!["LSQB", "args", "RSQB"], "expr", "List")?;
+    PG.add_rule_at("atom", vec!["LBRACE", "RBRACE"], "expr", "Map")?;
+    PG.add_rule_at("atom", vec!["entries", "RBRACE"], "expr", "Map")?;
+    PG.add_rule_at("atom", vec!["PRINT", "LPAR", "RPAR"], "expr", "Print")?;
+    PG.add_rule_at("atom", vec!["PRINT", "LPAR", "expr", "RPAR"], "expr", "Print")?;
+    PG.add_rule_at("atom", vec!["PRINT", "LPAR", "args", "RPAR"], "expr", "Print")?;
+    PG.add_rule_at("atom", vec!["INPUT", "LPAR", "RPAR"], "expr", "Input")?;
+    PG.add_rule_at("atom", vec!["INPUT", "LPAR", "expr", "RPAR"], "expr", "Input")?;
+
+    PG.add_rule_at("postfix", vec!["expr", "LSQB", "expr", "RSQB"], "expr", "Index")?;
+    PG.add_rule_at("postfix", vec!["expr", "LSQB", "expr", "COLON", "expr", "RSQB"], "expr", "Slice")?;
+    PG.add_rule_at("postfix", vec!["expr", "LSQB", "expr", "COLON", "RSQB"], "expr", "Slice")?;
+    PG.add_rule_at("postfix", vec!["expr", "LSQB", "COLON", "expr", "RSQB"], "expr", "Slice")?;
+    PG.add_rule_at("postfix", vec!["expr", "LSQB", "COLON", "RSQB"], "expr", "Slice")?;
+    PG.add_rule_at("postfix", vec!["expr", "LSQB", "expr", "COLON", "expr", "COLON", "expr", "RSQB"], "expr", "Slice")?;
+    PG.add_rule_at("postfix", vec!["expr", "LSQB", "expr", "COLON", "COLON", "expr", "RSQB"], "expr", "Slice")?;
+    PG.add_rule_at("postfix", vec!["expr", "LSQB", "COLON", "expr", "COLON", "expr", "RSQB"], "expr", "Slice")?;
+    PG.add_rule_at("postfix", vec!["expr", "LSQB", "COLON", "COLON", "expr", "RSQB"], "expr", "Slice")?;
+    PG.add_rule_at("postfix", vec!["expr", "LPAR", "RPAR"], "expr", "Call")?;
+    PG.add_rule_at("postfix", vec!["expr", "LPAR", "expr", "RPAR"], "expr", "Call")?;
+    PG.add_rule_at("postfix", vec!["expr", "LPAR", "args", "RPAR"], "expr", "Call")?;
+    PG.add_rule_at("postfix", vec!["expr", "DOT", "expr"], "expr", "Attribute")?;
+
+    //like in python 2 ** 3 ** 2 is 2 ** (3 ** 2) and ~2 ** 2 is ~(2 ** 2)
+    PG.set_assoc("power", parser::Assoc::Right)?;
+    PG.add_rule_at("power", vec!["expr", "DOUBLESTAR", "expr"], "expr", "Pow")?;
+    PG.set_assoc("unary", parser::Assoc::Right)?;
+    PG.add_rule_at("unary", vec!["TILDE", "expr"], "expr", "Invert")?;
+
+    PG.add_rule_at("multiplicative", vec!["expr", "STAR", "expr"], "expr", "Mul")?;
+    PG.add_rule_at("multiplicative", vec!["expr", "SLASH", "expr"], "expr", "Div")?;
+    PG.add_rule_at("multiplicative", vec!["expr", "DOUBLESLASH", "expr"], "expr", "FloorDiv")?;
+    PG.add_rule_at("multiplicative", vec!["expr", "PERCENT", "expr"], "expr", "Mod")?;
+
+    PG.add_rule_at("additive", vec!["expr", "PLUS", "expr"], "expr", "Add")?;
+    PG.add_rule_at("additive", vec!["expr", "MINUS", "expr"], "expr", "Minus")?;
+
+    PG.add_rule_at("shift", vec!["expr", "LEFTSHIFT", "expr"], "expr", "LeftShift")?;
+    PG.add_rule_at("shift", vec!["expr", "RIGHTSHIFT", "expr"], "expr", "RightShift")?;
+    PG.add_rule_at("bitand", vec!["expr", "AMPER", "expr"], "expr", "BitAnd")?;
+    PG.add_rule_at("bitxor", vec!["expr", "CIRCUMFLEX", "expr"], "expr", "BitXor")?;
+    PG.add_rule_at("bitor", vec!["expr", "VBAR", "expr"], "expr", "BitOr")?;
+
+    PG.add_rule_at("comparison", vec!["expr", "LESS", "expr"], "expr", "Less")?;
+    PG.add_rule_at("comparison", vec!["expr", "GREATER", "expr"], "expr", "Greater")?;
+    PG.add_rule_at("comparison", vec!["expr", "LESSEQUAL", "expr"], "expr", "LessEqual")?;
+    PG.add_rule_at("comparison", vec!["expr", "GREATEREQUAL", "expr"], "expr", "GreaterEqual")?;
+    PG.add_rule_at("comparison", vec!["expr", "EQEQUAL", "expr"], "expr", "Equal")?;
+    PG.add_rule_at("comparison", vec!["expr", "NOTEQUAL", "expr"], "expr", "NotEqual")?;
+
+    //prefix operators are right associative, so not not a needs no parentheses
+    PG.set_assoc("not", parser::Assoc::Right)?;
+    PG.add_rule_at("not", vec!["NOT", "expr"], "expr", "Not")?;
+    PG.add_rule_at("and", vec!["expr", "AND", "expr"], "expr", "And")?;
+    PG.add_rule_at("or", vec!["expr", "OR", "expr"], "expr", "Or")?;
+
+    //name := value is an expression, so it can be used in conditions like while (line := input()) != ""
+    PG.set_assoc("walrus", parser::Assoc::Right)?;
+    PG.add_rule_at("walrus", vec!["expr", "COLONEQUAL", "expr"], "expr", "NamedExpr")?;
+
+    PG.add_rule_at("assignment", vec!["expr", "EQUAL", "expr", "SEMI"], "stmt", "Assign")?;
+    //x += 1 and the other operators, the target is only evaluated once
+    PG.add_rule_at("assignment", vec!["expr", "PLUSEQUAL", "expr", "SEMI"], "stmt", "AugAssign")?;
+    PG.add_rule_at("assignment", vec!["expr", "MINEQUAL", "expr", "SEMI"], "stmt", "AugAssign")?;
+    PG.add_rule_at("assignment", vec!["expr", "STAREQUAL", "expr", "SEMI"], "stmt", "AugAssign")?;
+    PG.add_rule_at("assignment", vec!["expr", "SLASHEQUAL", "expr", "SEMI"], "stmt", "AugAssign")?;
+    PG.add_rule_at("assignment", vec!["expr", "DOUBLESLASHEQUAL", "expr", "SEMI"], "stmt", "AugAssign")?;
+    PG.add_rule_at("assignment", vec!["expr", "PERCENTEQUAL", "expr", "SEMI"], "stmt", "AugAssign")?;
+    PG.add_rule_at("assignment", vec!["expr", "DOUBLESTAREQUAL", "expr", "SEMI"], "stmt", "AugAssign")?;
+    PG.add_rule_at("assignment", vec!["expr", "LEFTSHIFTEQUAL", "expr", "SEMI"], "stmt", "AugAssign")?;
+    PG.add_rule_at("assignment", vec!["expr", "RIGHTSHIFTEQUAL", "expr", "SEMI"], "stmt", "AugAssign")?;
+    PG.add_rule_at("assignment", vec!["expr", "AMPEREQUAL", "expr", "SEMI"], "stmt", "AugAssign")?;
+    PG.add_rule_at("assignment", vec!["expr", "CIRCUMFLEXEQUAL", "expr", "SEMI"], "stmt", "AugAssign")?;
+    PG.add_rule_at("assignment", vec!["expr", "VBAREQUAL", "expr", "SEMI"], "stmt", "AugAssign")?;
+
+    //the entries of a map start at its brace, so the colons cant be confused with slices
+    PG.add_rule_at("entries", vec!["LBRACE", "expr", "COLON", "expr"], "entries", "Entries")?;
+    PG.add_rule_at("entries", vec!["entries", "COMMA", "expr", "COLON", "expr"], "entries", "Entries")?;
+
+    PG.add_rule_at("arguments", vec!["expr", "COMMA", "expr"], "args", "Args")?;
+    PG.add_rule_at("arguments", vec!["args", "COMMA", "expr"], "args", "Args")?;
+
+    //every statement ends with a SEMI, the lexer puts one at the end of lines and blocks
+    PG.add_rule_at("statement", vec!["expr", "SEMI"], "stmt", "ExprStmt")?;
+    PG.add_rule_at("statement", vec!["BREAK", "SEMI"], "stmt", "Break")?;
+    PG.add_rule_at("statement", vec!["CONTINUE", "SEMI"], "stmt", "Continue")?;
+    PG.add_rule_at("statement", vec!["IF", "expr", "block", "SEMI"], "stmt", "If")?;
+    PG.add_rule_at("statement", vec!["IF", "expr", "block", "orelse", "SEMI"], "stmt", "If")?;
+    PG.add_rule_at("statement", vec!["WHILE", "expr", "block", "SEMI"], "stmt", "While")?;
+    PG.add_rule_at("statement", vec!["FOR", "expr", "IN", "expr", "block", "SEMI"], "stmt", "For")?;
+    PG.add_rule_at("statement", vec!["FOR", "args", "IN", "expr", "block", "SEMI"], "stmt", "For")?;
+    //the signature is a call like f(a, b), the expr after -> is a return annotation that is only parsed
+    PG.add_rule_at("statement", vec!["FN", "expr", "block", "SEMI"], "stmt", "FunctionDef")?;
+    PG.add_rule_at("statement", vec!["FN", "expr", "RARROW", "expr", "block", "SEMI"], "stmt", "FunctionDef")?;
+    PG.add_rule_at("statement", vec!["RETURN", "SEMI"], "stmt", "Return")?;
+    PG.add_rule_at("statement", vec!["RETURN", "expr", "SEMI"], "stmt", "Return")?;
+
+    PG.add_rule_at("block", vec!["LBRACE", "stmts", "RBRACE"], "block", "Block")?;
+    PG.add_rule_at("block", vec!["LBRACE", "SEMI", "RBRACE"], "block", "Block")?;
+
+    PG.add_group_rule("statements", vec!["stmt"], "stmts")?;
+    //every parser joins a stmts made of stmts into one flat Stmts node with all the statements
+    PG.add_rule_at("sequence", vec!["stmts", "stmts"], "stmts", "Stmts")?;
+
+    //looser than the blocks so they are done first, an elif chain is reduced from its end
+    PG.set_assoc("orelse", parser::Assoc::Right)?;
+    PG.add_rule_at("orelse", vec!["ELIF", "expr", "block", "orelse"], "orelse", "Elif")?;
+    PG.add_rule_at("orelse", vec!["ELIF", "expr", "block"], "orelse", "Elif")?;
+    PG.add_rule_at("orelse", vec!["ELSE", "block"], "orelse", "Else")?;
+
+    PG.add_rule_at("program", vec!["stmts"], "program", "Program")?;
+
+    PG.set_start("program");
+
+    Ok(PG)
+}
@@ -1,41 +1,188 @@
-mod lexer;
-mod parser;
-mod readfile;
-mod ast;
+use std::env;
+use std::path::Path;
+
+use programmin_language::{
+    bytecode, codegen, compiler, environment, grammar, grammar_doc, grammar_test, incremental, lexer, parser, program_test, readfile,
+    unparse, vm, EVAL_STACK_SIZE
+};
 
 fn main() {
-    let lines = readfile::read("fufu");
+    let args: Vec<String> = env::args().collect();
+    let trace = args.contains(&"--trace".to_string());
+    let trace_json = args.contains(&"--trace-json".to_string());
+
+    #[allow(non_snake_case)]
+    let mut PG = match grammar() {
+        Ok(PG) => PG,
+        Err(error) => panic!("{error}")
+    };
 
-    let mut PG = parser::Parser::new();
+    if args.contains(&"--earley".to_string()) {
+        PG.set_mode(parser::Mode::Earley);
+    }
+    if args.contains(&"--peg".to_string()) {
+        PG.set_mode(parser::Mode::Peg);
+    }
 
-    PG.add_rule(vec!["NAME"], "expr", "Name");
-    PG.add_rule(vec!["NUM"], "expr", "Num");
-    PG.add_rule(vec!["STRING"], "expr", "String");
+    if args.contains(&"--precedence".to_string()) {
+        println!("{}", PG.precedence_table());
+    }
 
-    PG.new_precedence();
+    if args.contains(&"--ebnf".to_string()) {
+        println!("{}", grammar_doc::ebnf(&PG));
+        return;
+    }
 
-    PG.add_rule(vec!["expr", "STAR", "expr"], "expr", "Mul");
-    PG.add_rule(vec!["expr", "SLASH", "expr"], "expr", "Div");
+    if let Some(index) = args.iter().position(|arg| arg == "--railroad") {
+        let path = args.get(index + 1).map(|path| path.as_str()).unwrap_or("grammar.html");
+        match std::fs::write(path, grammar_doc::railroad_html(&PG)) {
+            Ok(()) => println!("wrote railroad diagrams to {path}"),
+            Err(error) => panic!("couldnt write {path}: {error}")
+        }
+        return;
+    }
 
-    PG.new_precedence();
+    //regenerates src/typed_ast.rs after the grammar changed: --generate-ast src/typed_ast.rs
+    if let Some(index) = args.iter().position(|arg| arg == "--generate-ast") {
+        let path = args.get(index + 1).map(|path| path.as_str()).unwrap_or("src/typed_ast.rs");
+        match codegen::write_typed_ast(&PG, path) {
+            Ok(true) => println!("wrote typed ast to {path}"),
+            Ok(false) => println!("{path} is up to date"),
+            Err(error) => panic!("{error}")
+        }
+        return;
+    }
 
-    PG.add_rule(vec!["expr", "PLUS", "expr"], "expr", "Add");
-    PG.add_rule(vec!["expr", "MINUS", "expr"], "expr", "Minus");
+    if args.contains(&"--check-roundtrip".to_string()) {
+        match unparse::check_roundtrip(&PG, "expr", 1000, 42) {
+            Ok(count) => println!("roundtrip ok for {count} generated expressions"),
+            Err(error) => panic!("{error}")
+        }
+        return;
+    }
 
-    for line in lines {
-        println!("{line}");
-        let tokens = lexer::lex(&line);
-        println!("toks: {:?}", tokens);
+    if let Some(index) = args.iter().position(|arg| arg == "--test") {
+        let dir = args.get(index + 1).map(|dir| dir.as_str()).unwrap_or("tests/grammar");
+        let (passed, failures) = grammar_test::run_dir(&PG, Path::new(dir));
+
+        for failure in &failures {
+            println!("FAILED {failure}");
+        }
+        println!("{passed} passed, {} failed", failures.len());
+
+        if !failures.is_empty() {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    //runs the programs in tests/programs with both the tree walker and the vm
+    if let Some(index) = args.iter().position(|arg| arg == "--test-programs") {
+        let dir = args.get(index + 1).map(|dir| dir.as_str()).unwrap_or("tests/programs");
+        //error messages show nodes like a normal run does
         unsafe {
             lexer::AST_PRINT = true;
         }
-        let ast = PG.parse(&tokens, &line);
-        
-        println!("ast: {:?}", ast);
+        let (passed, failures) = program_test::run_dir(&PG, Path::new(dir));
+
+        for failure in &failures {
+            println!("FAILED {failure}");
+        }
+        println!("{passed} passed, {} failed", failures.len());
+
+        if !failures.is_empty() {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    //edits a program with statements, blocks and strings in it
+    if let Some(index) = args.iter().position(|arg| arg == "--check-incremental") {
+        let file = args.get(index + 1).map(|file| file.as_str()).unwrap_or("tests/programs/control.fufu");
+        let source = match std::fs::read_to_string(file) {
+            Ok(source) => source,
+            Err(error) => panic!("couldnt read {file}: {error}")
+        };
+
+        match incremental::check_incremental(&PG, &source, 2000, 7) {
+            Ok(reused) => println!("incremental parse ok for 2000 random edits, {reused} subtrees reused"),
+            Err(error) => panic!("{error}")
+        }
+        return;
+    }
+
+    let source = readfile::read("fufu").join("\n");
 
-        println!("eval: {:?}", ast.eval());
-        println!("-------------------------------------------------------------------------------------------");
+    println!("{source}");
+    let tokens = lexer::lex(&source);
+    if tokens.is_empty() {
+        return;
+    }
+    println!("toks: {:?}", tokens);
+    unsafe {
+        lexer::AST_PRINT = true;
     }
+    let ast = if trace || trace_json {
+        let (ast, trace) = match PG.parse_traced(&tokens, &source) {
+            Ok(traced) => traced,
+            Err(error) => panic!("{error}")
+        };
+        if trace_json {
+            println!("{}", trace.to_json());
+        } else {
+            println!("{trace}");
+        }
+        ast
+    } else {
+        PG.parse(&tokens, &source)
+    };
+
+    let ast = match ast {
+        Ok(ast) => ast,
+        Err(error) => {
+            println!("{error}");
+            return;
+        }
+    };
 
-    
+    println!("ast: {:?}", ast);
+
+    if args.contains(&"--unparse".to_string()) {
+        match unparse::unparse(&PG, &ast) {
+            Ok(source) => println!("unparsed: {source}"),
+            Err(error) => println!("couldnt unparse: {error}")
+        }
+    }
+
+    if args.contains(&"--disassemble".to_string()) {
+        println!("{}", bytecode::disassemble(&compiler::compile(&ast)));
+    }
+
+    println!("-------------------------------------------------------------------------------------------");
+    //the tree walker runs the program unless the vm is asked for
+    if args.contains(&"--vm".to_string()) {
+        let program = compiler::compile(&ast);
+        let mut env = environment::Environment::new();
+
+        match vm::run(&program, &mut env) {
+            Ok(value) => println!("eval: {:?}", value),
+            Err(error) => println!("{error}")
+        }
+        return;
+    }
+
+    //every fufu call recurses on the rust stack, ast::MAX_DEPTH of them need more than the default
+    let evaluator = std::thread::Builder::new().stack_size(EVAL_STACK_SIZE).spawn(move || {
+        let mut env = environment::Environment::new();
+
+        match ast.eval(&mut env) {
+            Ok(value) => println!("eval: {:?}", value),
+            Err(error) => println!("{error}")
+        }
+    });
+
+    match evaluator {
+        Ok(evaluator) => evaluator.join().unwrap(),
+        Err(error) => panic!("couldnt start the evaluator: {error}")
+    }
 }
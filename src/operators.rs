@@ -0,0 +1,488 @@
+use std::cmp::Ordering;
+use crate::builtins;
+use crate::bigint::BigInt;
+use crate::error::ErrorKind;
+use crate::rational::Rational;
+use crate::value::Value;
+
+//what the operators do to values, shared by the tree walker and the vm. the errors have
+//no location yet, the caller knows which node or instruction they belong to
+pub type OperatorResult<T> = Result<T, (ErrorKind, String)>;
+
+fn unsupported(operator: &str, value1: &Value, value2: &Value) -> (ErrorKind, String) {
+    (ErrorKind::TypeError, format!("unsupported operand types for {operator}: {} and {}", value1.type_name(), value2.type_name()))
+}
+
+fn overflow() -> (ErrorKind, String) {
+    (ErrorKind::OverflowError, "integer result too large".to_string())
+}
+
+//the most bytes a repeat like "ab" * n or [0] * n can make, more would abort the allocation
+const MAX_REPEAT: usize = 1 << 30;
+
+//how often to repeat something of size bytes, checked before anything is allocated
+fn repeat_count(size: usize, count: i64) -> OperatorResult<usize> {
+    let count = usize::try_from(count.max(0)).map_err(|_| (ErrorKind::OverflowError, "cant repeat that many times".to_string()))?;
+
+    match size.checked_mul(count) {
+        None => Err((ErrorKind::OverflowError, "cant repeat that many times".to_string())),
+        Some(total) if total > MAX_REPEAT => Err((ErrorKind::MemoryError, format!("repeating would need {total} bytes"))),
+        Some(_) => Ok(count)
+    }
+}
+
+//an operator that works the same on every kind of number
+struct Arithmetic {
+    symbol: &'static str,
+    checked: fn(i64, i64) -> Option<i64>,
+    int: fn(&BigInt, &BigInt) -> BigInt,
+    rational: fn(&Rational, &Rational) -> Rational,
+    float: fn(f64, f64) -> f64
+}
+
+const ADD: Arithmetic = Arithmetic { symbol: "+", checked: i64::checked_add, int: BigInt::add, rational: Rational::add, float: |num1, num2| num1 + num2 };
+const MINUS: Arithmetic = Arithmetic { symbol: "-", checked: i64::checked_sub, int: BigInt::sub, rational: Rational::sub, float: |num1, num2| num1 - num2 };
+const MUL: Arithmetic = Arithmetic { symbol: "*", checked: i64::checked_mul, int: BigInt::mul, rational: Rational::mul, float: |num1, num2| num1 * num2 };
+
+//the operands are brought to the wider of their types: int, rational, float. ints are
+//done in 64 bits as long as the result fits and become a BigInt when it doesnt
+fn arithmetic(operator: &Arithmetic, value1: &Value, value2: &Value) -> OperatorResult<Value> {
+    if let (Value::Int(int1), Value::Int(int2)) = (value1, value2) {
+        if let Some(result) = (operator.checked)(*int1, *int2) {
+            return Ok(Value::Int(result));
+        }
+    }
+    if let (Some(int1), Some(int2)) = (value1.integer(), value2.integer()) {
+        return Ok(Value::int((operator.int)(&int1, &int2)));
+    }
+    if let (Some(rational1), Some(rational2)) = (value1.rational(), value2.rational()) {
+        return Ok(Value::Rational((operator.rational)(&rational1, &rational2)));
+    }
+
+    match (value1.float(), value2.float()) {
+        (Some(num1), Some(num2)) => Ok(Value::Float((operator.float)(num1, num2))),
+        _ => Err(unsupported(operator.symbol, value1, value2))
+    }
+}
+
+pub fn add(value1: Value, value2: Value) -> OperatorResult<Value> {
+    match (&value1, &value2) {
+        (Value::Str(string1), Value::Str(string2)) => Ok(Value::Str(format!("{string1}{string2}"))),
+        (Value::List(list1), Value::List(list2)) => Ok(Value::list(list1.borrow().iter().chain(list2.borrow().iter()).cloned().collect())),
+        (Value::Str(_), other) | (other, Value::Str(_)) => Err((
+            ErrorKind::TypeError,
+            format!("can only concatenate string to string, not {}", other.type_name())
+        )),
+        _ => arithmetic(&ADD, &value1, &value2)
+    }
+}
+
+pub fn minus(value1: Value, value2: Value) -> OperatorResult<Value> {
+    arithmetic(&MINUS, &value1, &value2)
+}
+
+pub fn mul(value1: Value, value2: Value) -> OperatorResult<Value> {
+    match (&value1, &value2) {
+        (Value::Str(string), Value::Int(count)) | (Value::Int(count), Value::Str(string)) => Ok(Value::Str(string.repeat(repeat_count(string.len(), *count)?))),
+        //the items are shared, not copied, like in python
+        (Value::List(list), Value::Int(count)) | (Value::Int(count), Value::List(list)) => {
+            let list = list.borrow();
+            let count = repeat_count(list.len() * std::mem::size_of::<Value>(), *count)?;
+            Ok(Value::list(list.iter().cloned().cycle().take(list.len() * count).collect()))
+        }
+        (Value::Str(_) | Value::List(_), Value::BigInt(_)) | (Value::BigInt(_), Value::Str(_) | Value::List(_)) => {
+            Err((ErrorKind::OverflowError, "cant repeat that many times".to_string()))
+        }
+        (Value::Str(_), other) | (other, Value::Str(_)) => Err((
+            ErrorKind::TypeError,
+            format!("a string can only be repeated by an int, not {}", other.type_name())
+        )),
+        _ => arithmetic(&MUL, &value1, &value2)
+    }
+}
+
+//true division, ints give a float like in python, // keeps them ints. a rational stays
+//exact and huge ints are divided exactly before they become a float
+pub fn div(value1: Value, value2: Value) -> OperatorResult<Value> {
+    let exact = matches!(value1, Value::Rational(_)) || matches!(value2, Value::Rational(_));
+    let big = matches!(value1, Value::BigInt(_)) || matches!(value2, Value::BigInt(_));
+
+    if let (true, Some(rational1), Some(rational2)) = (exact || big, value1.rational(), value2.rational()) {
+        let quotient = rational1.div(&rational2).ok_or_else(|| (ErrorKind::ZeroDivision, "division by zero".to_string()))?;
+        return Ok(if exact { Value::Rational(quotient) } else { Value::Float(quotient.to_f64()) });
+    }
+
+    match (value1.float(), value2.float()) {
+        (Some(_), Some(0.0)) => Err((ErrorKind::ZeroDivision, "division by zero".to_string())),
+        (Some(num1), Some(num2)) => Ok(Value::Float(num1 / num2)),
+        _ => Err(unsupported("/", &value1, &value2))
+    }
+}
+
+//floor division and modulo round towards negative infinity like in python, so the
+//result of % has the sign of the divisor. // of rationals is an int
+pub fn floor_div(value1: Value, value2: Value) -> OperatorResult<Value> {
+    if let (Some(int1), Some(int2)) = (value1.integer(), value2.integer()) {
+        if int2.is_zero() {
+            return Err((ErrorKind::ZeroDivision, "integer division by zero".to_string()));
+        }
+        if let (Value::Int(int1), Value::Int(int2)) = (&value1, &value2) {
+            if let Some(quotient) = int1.checked_div(*int2) {
+                let inexact = int1 % int2 != 0 && (*int1 < 0) != (*int2 < 0);
+                return Ok(Value::Int(if inexact { quotient - 1 } else { quotient }));
+            }
+        }
+        return Ok(Value::int(int1.div_floor(&int2).0));
+    }
+    if let (Some(rational1), Some(rational2)) = (value1.rational(), value2.rational()) {
+        return match rational1.div(&rational2) {
+            Some(quotient) => Ok(Value::int(quotient.floor())),
+            None => Err((ErrorKind::ZeroDivision, "division by zero".to_string()))
+        };
+    }
+
+    match (value1.float(), value2.float()) {
+        (Some(_), Some(0.0)) => Err((ErrorKind::ZeroDivision, "division by zero".to_string())),
+        (Some(num1), Some(num2)) => Ok(Value::Float((num1 / num2).floor())),
+        _ => Err(unsupported("//", &value1, &value2))
+    }
+}
+
+pub fn modulo(value1: Value, value2: Value) -> OperatorResult<Value> {
+    if let (Some(int1), Some(int2)) = (value1.integer(), value2.integer()) {
+        if int2.is_zero() {
+            return Err((ErrorKind::ZeroDivision, "integer modulo by zero".to_string()));
+        }
+        if let (Value::Int(int1), Value::Int(int2)) = (&value1, &value2) {
+            let remainder = int1.wrapping_rem(*int2);
+            return Ok(Value::Int(if remainder != 0 && (remainder < 0) != (*int2 < 0) { remainder + int2 } else { remainder }));
+        }
+        return Ok(Value::int(int1.div_floor(&int2).1));
+    }
+    if let (Some(rational1), Some(rational2)) = (value1.rational(), value2.rational()) {
+        return match rational1.div(&rational2) {
+            Some(quotient) => Ok(Value::Rational(rational1.sub(&Rational::from_int(quotient.floor()).mul(&rational2)))),
+            None => Err((ErrorKind::ZeroDivision, "modulo by zero".to_string()))
+        };
+    }
+
+    match (value1.float(), value2.float()) {
+        (Some(_), Some(0.0)) => Err((ErrorKind::ZeroDivision, "modulo by zero".to_string())),
+        (Some(num1), Some(num2)) => {
+            let remainder = num1 % num2;
+            Ok(Value::Float(if remainder != 0.0 && (remainder < 0.0) != (num2 < 0.0) { remainder + num2 } else { remainder }))
+        }
+        _ => Err(unsupported("%", &value1, &value2))
+    }
+}
+
+//an int to a negative power is a float, like 2 ** -1 == 0.5, a rational to an int power stays exact
+pub fn pow(value1: Value, value2: Value) -> OperatorResult<Value> {
+    if let (Some(base), Some(exponent)) = (value1.integer(), value2.integer()) {
+        if !exponent.is_negative() {
+            //these stay small however large the exponent is
+            match base.to_i64() {
+                Some(0) => return Ok(Value::Int(if exponent.is_zero() { 1 } else { 0 })),
+                Some(1) => return Ok(Value::Int(1)),
+                Some(-1) if exponent.div_rem(&BigInt::from_i64(2)).1.is_zero() => return Ok(Value::Int(1)),
+                Some(-1) => return Ok(Value::Int(-1)),
+                _ => {}
+            }
+            let exponent = exponent.to_i64().and_then(|exponent| u32::try_from(exponent).ok()).ok_or_else(overflow)?;
+            if let Some(power) = base.to_i64().and_then(|base| base.checked_pow(exponent)) {
+                return Ok(Value::Int(power));
+            }
+            return Ok(Value::int(base.pow(exponent)));
+        }
+    }
+    if let (Value::Rational(rational), Some(exponent)) = (&value1, value2.integer()) {
+        let exponent = exponent.to_i64().filter(|exponent| exponent.unsigned_abs() <= u32::MAX as u64).ok_or_else(overflow)?;
+        return match rational.pow(exponent) {
+            Some(power) => Ok(Value::Rational(power)),
+            None => Err((ErrorKind::ZeroDivision, "0 cant be raised to a negative power".to_string()))
+        };
+    }
+
+    match (value1.float(), value2.float()) {
+        (Some(num1), Some(num2)) if num1 == 0.0 && num2 < 0.0 => {
+            Err((ErrorKind::ZeroDivision, "0 cant be raised to a negative power".to_string()))
+        }
+        (Some(num1), Some(num2)) if num1 < 0.0 && num2.fract() != 0.0 => {
+            Err((ErrorKind::ValueError, "a negative number cant be raised to a fractional power".to_string()))
+        }
+        (Some(num1), Some(num2)) => Ok(Value::Float(num1.powf(num2))),
+        _ => Err(unsupported("**", &value1, &value2))
+    }
+}
+
+//the bitwise operators only work on ints
+pub fn bitwise(class_name: &str, value1: Value, value2: Value) -> OperatorResult<Value> {
+    let operator = match class_name {
+        "BitAnd" => "&",
+        "BitOr" => "|",
+        "BitXor" => "^",
+        "LeftShift" => "<<",
+        _ => ">>"
+    };
+
+    //small ints without going through BigInt
+    if let (Value::Int(int1), Value::Int(int2)) = (&value1, &value2) {
+        let result = match class_name {
+            "BitAnd" => Some(int1 & int2),
+            "BitOr" => Some(int1 | int2),
+            "BitXor" => Some(int1 ^ int2),
+            "RightShift" if *int2 >= 0 => Some(int1 >> (*int2).min(63)),
+            "LeftShift" if (0..64).contains(int2) => Some(int1 << int2).filter(|shifted| shifted >> int2 == *int1),
+            _ => None
+        };
+        if let Some(result) = result {
+            return Ok(Value::Int(result));
+        }
+    }
+
+    let (int1, int2) = match (value1.integer(), value2.integer()) {
+        (Some(int1), Some(int2)) => (int1, int2),
+        _ => return Err(unsupported(operator, &value1, &value2))
+    };
+
+    let result = match class_name {
+        "BitAnd" => int1.bitwise(&int2, |digit1, digit2| digit1 & digit2),
+        "BitOr" => int1.bitwise(&int2, |digit1, digit2| digit1 | digit2),
+        "BitXor" => int1.bitwise(&int2, |digit1, digit2| digit1 ^ digit2),
+        _ if int2.is_negative() => return Err((ErrorKind::ValueError, "negative shift count".to_string())),
+        //a count too large for usize shifts everything out
+        "RightShift" => int1.shr(int2.to_i64().map(|count| count as usize).unwrap_or(usize::MAX)),
+        _ if int1.is_zero() => int1,
+        _ => int1.shl(int2.to_i64().filter(|count| *count < 1 << 32).ok_or_else(overflow)? as usize)
+    };
+
+    Ok(Value::int(result))
+}
+
+//the binary operators by class name, for the ones that dont have their own node like x += 1
+pub fn binary(class_name: &str, value1: Value, value2: Value) -> OperatorResult<Value> {
+    match class_name {
+        "Add" => add(value1, value2),
+        "Minus" => minus(value1, value2),
+        "Mul" => mul(value1, value2),
+        "Div" => div(value1, value2),
+        "FloorDiv" => floor_div(value1, value2),
+        "Mod" => modulo(value1, value2),
+        "Pow" => pow(value1, value2),
+        other => bitwise(other, value1, value2)
+    }
+}
+
+//x += y, a list is extended in place so every name for it sees the new items
+pub fn augmented(class_name: &str, current: Value, value: Value) -> OperatorResult<Value> {
+    match (&current, &value) {
+        (Value::List(list), Value::List(other)) if class_name == "Add" => {
+            let items = other.borrow().clone();
+            list.borrow_mut().extend(items);
+            Ok(current)
+        }
+        _ => binary(class_name, current, value)
+    }
+}
+
+pub fn invert(value: Value) -> OperatorResult<Value> {
+    match value {
+        Value::Int(int) => Ok(Value::Int(!int)),
+        Value::BigInt(int) => Ok(Value::int(int.invert())),
+        other => Err((ErrorKind::TypeError, format!("bad operand type for ~: {}", other.type_name())))
+    }
+}
+
+pub const COMPARISONS: [&str; 6] = ["Less", "Greater", "LessEqual", "GreaterEqual", "Equal", "NotEqual"];
+
+//numbers compare by value, strings and lists lexicographically, everything can be checked for equality
+pub fn compare(class_name: &str, value1: &Value, value2: &Value) -> OperatorResult<bool> {
+    let operator = match class_name {
+        "Less" => "<",
+        "Greater" => ">",
+        "LessEqual" => "<=",
+        "GreaterEqual" => ">=",
+        "Equal" => return Ok(value1 == value2),
+        _ => return Ok(value1 != value2)
+    };
+
+    let ordering = match value1.order(value2) {
+        Some(ordering) => ordering,
+        None => return Err((
+            ErrorKind::TypeError,
+            format!("{operator} is not supported between {} and {}", value1.type_name(), value2.type_name())
+        ))
+    };
+
+    Ok(match class_name {
+        "Less" => ordering == Ordering::Less,
+        "Greater" => ordering == Ordering::Greater,
+        "LessEqual" => ordering != Ordering::Greater,
+        _ => ordering != Ordering::Less
+    })
+}
+
+//python style bounds: a missing one is the default, a negative one counts from the end and
+//out of range ones are clamped
+fn slice_bound(bound: &Value, length: i64, default: i64, lowest: i64, highest: i64) -> OperatorResult<i64> {
+    match bound {
+        Value::Null => Ok(default),
+        Value::Int(int) if *int < 0 => Ok((int + length).clamp(lowest, highest)),
+        Value::Int(int) => Ok((*int).clamp(lowest, highest)),
+        Value::BigInt(int) => Ok(if int.is_negative() { lowest } else { highest }),
+        other => Err((ErrorKind::TypeError, format!("slice indices must be int, not {}", other.type_name())))
+    }
+}
+
+//the positions a slice takes, a negative step goes backwards from the end
+fn slice_positions(start: &Value, end: &Value, step: &Value, length: usize) -> OperatorResult<Vec<usize>> {
+    let length = length as i64;
+    let step = match step {
+        Value::Null => 1,
+        Value::Int(0) => return Err((ErrorKind::ValueError, "slice step cant be zero".to_string())),
+        Value::Int(int) => *int,
+        other => return Err((ErrorKind::TypeError, format!("slice step must be int, not {}", other.type_name())))
+    };
+
+    let (start, end) = if step > 0 {
+        (slice_bound(start, length, 0, 0, length)?, slice_bound(end, length, length, 0, length)?)
+    } else {
+        (slice_bound(start, length, length - 1, -1, length - 1)?, slice_bound(end, length, -1, -1, length - 1)?)
+    };
+
+    let mut positions = vec![];
+    let mut position = start;
+    while (step > 0 && position < end) || (step < 0 && position > end) {
+        positions.push(position as usize);
+        position += step;
+    }
+
+    Ok(positions)
+}
+
+pub fn item(target: &Value, index: &Value) -> OperatorResult<Value> {
+    match target {
+        Value::Str(string) => {
+            let chars: Vec<char> = string.chars().collect();
+            let position = builtins::position(index, chars.len())?;
+            Ok(Value::Str(chars[position].to_string()))
+        }
+        Value::List(list) => {
+            let list = list.borrow();
+            let position = builtins::position(index, list.len())?;
+            Ok(list[position].clone())
+        }
+        Value::Map(map) => {
+            builtins::hashable(index)?;
+            match map.borrow().get(index) {
+                Some(value) => Ok(value.clone()),
+                None => Err((ErrorKind::KeyError, format!("key {} not found", index.repr())))
+            }
+        }
+        other => Err((ErrorKind::TypeError, format!("{} is not indexable", other.type_name())))
+    }
+}
+
+//target[start:end:step], a bound that was left out is null
+pub fn slice(target: &Value, start: &Value, end: &Value, step: &Value) -> OperatorResult<Value> {
+    match target {
+        Value::Str(string) => {
+            let chars: Vec<char> = string.chars().collect();
+            let positions = slice_positions(start, end, step, chars.len())?;
+            Ok(Value::Str(positions.iter().map(|position| chars[*position]).collect()))
+        }
+        Value::List(list) => {
+            let list = list.borrow();
+            let positions = slice_positions(start, end, step, list.len())?;
+            Ok(Value::list(positions.iter().map(|position| list[*position].clone()).collect()))
+        }
+        other => Err((ErrorKind::TypeError, format!("{} cant be sliced", other.type_name())))
+    }
+}
+
+//value.name, the methods of the built in types come first, then the string keys of maps
+pub fn attribute(value: &Value, name: &str) -> OperatorResult<Value> {
+    if let Some(method) = builtins::method(value, name) {
+        return Ok(Value::NativeFunction(method));
+    }
+
+    let field = match value {
+        Value::Map(map) => map.borrow().get(&Value::Str(name.to_string())).cloned(),
+        _ => None
+    };
+
+    match field {
+        Some(field) => Ok(field),
+        None => Err((ErrorKind::AttributeError, format!("{} has no attribute {name}", value.type_name())))
+    }
+}
+
+//container[index] = value
+pub fn store_item(container: &Value, index: Value, value: Value) -> OperatorResult<()> {
+    match container {
+        Value::List(list) => {
+            let mut list = list.borrow_mut();
+            let position = builtins::position(&index, list.len())?;
+            list[position] = value;
+        }
+        Value::Map(map) => {
+            builtins::hashable(&index)?;
+            map.borrow_mut().insert(index, value);
+        }
+        other => return Err((ErrorKind::TypeError, format!("{} doesnt support item assignment", other.type_name())))
+    }
+
+    Ok(())
+}
+
+//container.name = value, a map gets the name as a string key
+pub fn store_attribute(container: &Value, name: &str, value: Value) -> OperatorResult<()> {
+    match container {
+        Value::Map(map) => map.borrow_mut().insert(Value::Str(name.to_string()), value),
+        other => return Err((ErrorKind::AttributeError, format!("cant set attributes of {}", other.type_name())))
+    }
+
+    Ok(())
+}
+
+//what a for loop goes through, the items are copied so changing a list in the loop doesnt change the loop
+pub fn items(value: &Value) -> OperatorResult<Vec<Value>> {
+    match value {
+        Value::Str(string) => Ok(string.chars().map(|char| Value::Str(char.to_string())).collect()),
+        Value::List(list) => Ok(list.borrow().clone()),
+        Value::Map(map) => Ok(map.borrow().entries().iter().map(|(key, _)| key.clone()).collect()),
+        other => Err((ErrorKind::TypeError, format!("cant iterate over {}", other.type_name())))
+    }
+}
+
+//for a, b in ... needs exactly as many values as names
+pub fn unpack(values: Vec<Value>, count: usize) -> OperatorResult<Vec<Value>> {
+    if values.len() != count {
+        return Err((ErrorKind::ValueError, format!("cant unpack {} values into {} names", values.len(), count)));
+    }
+
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //an int stays an Int while it fits in 64 bits and comes back to one when it fits again
+    #[test]
+    fn overflow_into_bigint() {
+        let big = add(Value::Int(i64::MAX), Value::Int(1)).unwrap();
+        assert!(matches!(big, Value::BigInt(_)));
+        assert_eq!(big.repr(), "9223372036854775808");
+
+        let back = minus(big, Value::Int(1)).unwrap();
+        assert!(matches!(back, Value::Int(i64::MAX)));
+
+        let small = minus(Value::Int(i64::MIN), Value::Int(1)).unwrap();
+        assert_eq!(small.repr(), "-9223372036854775809");
+        assert!(matches!(mul(Value::Int(i64::MIN), Value::Int(-1)).unwrap(), Value::BigInt(_)));
+        assert!(matches!(mul(Value::Int(i64::MIN), Value::Int(1)).unwrap(), Value::Int(i64::MIN)));
+        assert_eq!(pow(Value::Int(3), Value::Int(50)).unwrap().repr(), "717897987691852588770249");
+        assert!(matches!(floor_div(pow(Value::Int(2), Value::Int(70)).unwrap(), Value::Int(1 << 20)).unwrap(), Value::Int(1125899906842624)));
+    }
+}
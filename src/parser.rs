@@ -1,6 +1,15 @@
-use std::fmt::Debug;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::{Debug, Display};
+use std::sync::Arc;
 use crate::ast;
-use crate::lexer::Token;
+use crate::earley;
+use crate::peg;
+use crate::lexer::{self, Span, Token};
+use crate::error::{ErrorKind, RuntimeError};
+use crate::typed_ast::{AnyNode, ExprNode, OrelseNode, StmtNode, StmtsNode};
+use crate::value::Value;
+use crate::environment::Environment;
+use crate::trace::Trace;
 //use crate::ast::*
 //rule.class_name, next_token_values, next_token_names, index, line
 
@@ -10,45 +19,139 @@ pub struct Node {
     pub class_name: String,
     pub token_values: Vec<Node>,
     pub token_names: Vec<String>,
+    //tokens index..end of the parsed line are covered by this node
     pub index: usize,
-    pub line: String,
+    pub end: usize,
+    //the whole source, shared by every node of a parse
+    pub line: Arc<str>,
     pub token: Option<Token>
 }
 
 
-#[derive(Debug, Clone)]
-pub enum ValType {
-    NULL,
-    NUM,
-    STRING,
-}
-#[derive(Debug, Clone)]
-pub struct EvalVal {
-    pub num: Option<f32>,
-    pub string: Option<String>,
-    pub valtype: ValType
-}
+impl Node {
+    pub fn from_token(token: &Token, index: usize, line: &Arc<str>) -> Node {
+        Node {
+            class_name: "TOKEN".to_string(),
+            token_values: vec![],
+            token_names: vec![],
+            index,
+            end: index + 1,
+            line: line.clone(),
+            token: Some(token.clone())
+        }
+    }
+
+    //moves the token positions of the node and all its children
+    pub fn shift(&mut self, delta: isize) {
+        self.index = (self.index as isize + delta) as usize;
+        self.end = (self.end as isize + delta) as usize;
+
+        for child in self.token_values.iter_mut() {
+            child.shift(delta);
+        }
+    }
+
+    //points the node and its children at the source of another parse, for subtrees that
+    //are kept after an edit
+    pub fn set_line(&mut self, line: &Arc<str>) {
+        self.line = line.clone();
 
-impl Default for EvalVal {
-    fn default() -> Self {
-        EvalVal { num: None, string: None, valtype: ValType::NULL}
+        for child in self.token_values.iter_mut() {
+            child.set_line(line);
+        }
     }
-}
 
+    //the chars of the source line covered by the node, for error messages
+    pub fn span(&self) -> Span {
+        let spans: Vec<Span> = lexer::lex_spanned(&self.line).into_iter().map(|(_, span)| span).collect();
+        self.span_in(&spans)
+    }
 
+    //same as span with the token spans of the line already known
+    pub fn span_in(&self, spans: &[Span]) -> Span {
+        match (spans.get(self.index), spans.get(self.end.max(self.index + 1) - 1)) {
+            (Some(first), Some(last)) => Span { start: first.start, end: last.end },
+            _ => Span { start: 0, end: self.line.chars().count() }
+        }
+    }
 
-impl Node {
-    pub fn eval(&self) -> Option<EvalVal> {
-        match self.class_name.as_str() {
-            "Num" => ast::Num(self.clone()),
-            "String" => ast::String(self.clone()),
-            "Div" => ast::Div(self.clone()),
-            "Mul" => ast::Mul(self.clone()),
-            "Add" => ast::Add(self.clone()),
-            "Minus" => ast::Minus(self.clone()),
-            other => panic!("node eval not implemented: {}", other)
+    //the node was written in parentheses, group rules widen the inner node over them
+    pub fn parenthesized(&self) -> bool {
+        match (self.token_values.first(), self.token_values.last()) {
+            (Some(first), Some(last)) => self.index < first.index || self.end > last.end,
+            _ => false
         }
-    } 
+    }
+
+    pub fn eval(&self, env: &mut Environment) -> Result<Value, RuntimeError> {
+        let typed = match AnyNode::from_node(self) {
+            Ok(typed) => typed,
+            Err(message) => return Err(RuntimeError::new(ErrorKind::SyntaxError, &message, self))
+        };
+
+        match typed {
+            AnyNode::Expr(expr) => match expr {
+                ExprNode::Num(_) => ast::Num(self, env),
+                ExprNode::String(_) => ast::String(self, env),
+                ExprNode::Name(_) => ast::Name(self, env),
+                ExprNode::BoolTrue(_) | ExprNode::BoolFalse(_) => ast::Bool(self, env),
+                ExprNode::List(_) | ExprNode::ListExpr(_) | ExprNode::ListArgs(_) => ast::List(self, env),
+                ExprNode::MapLbrace(_) | ExprNode::MapEntries(_) => ast::Map(self, env),
+                ExprNode::Print(_) | ExprNode::PrintExpr(_) | ExprNode::PrintArgs(_) => ast::Print(self, env),
+                ExprNode::Input(_) | ExprNode::InputExpr(_) => ast::Input(self, env),
+                ExprNode::Index(_) => ast::Index(self, env),
+                ExprNode::SliceExprColonExpr(_) | ExprNode::SliceExprColon(_) | ExprNode::SliceColonExpr(_) | ExprNode::SliceColon(_)
+                | ExprNode::SliceExprColonExprColonExpr(_) | ExprNode::SliceExprColonColonExpr(_)
+                | ExprNode::SliceColonExprColonExpr(_) | ExprNode::SliceColonColonExpr(_) => ast::Slice(self, env),
+                ExprNode::Call(_) | ExprNode::CallExpr(_) | ExprNode::CallArgs(_) => ast::Call(self, env),
+                ExprNode::Attribute(_) => ast::Attribute(self, env),
+                ExprNode::Pow(_) => ast::Pow(self, env),
+                ExprNode::Invert(_) => ast::Invert(self, env),
+                ExprNode::Mul(_) => ast::Mul(self, env),
+                ExprNode::Div(_) => ast::Div(self, env),
+                ExprNode::FloorDiv(_) => ast::FloorDiv(self, env),
+                ExprNode::Mod(_) => ast::Mod(self, env),
+                ExprNode::Add(_) => ast::Add(self, env),
+                ExprNode::Minus(_) => ast::Minus(self, env),
+                ExprNode::LeftShift(_) | ExprNode::RightShift(_) | ExprNode::BitAnd(_) | ExprNode::BitXor(_) | ExprNode::BitOr(_) => ast::Bitwise(self, env),
+                ExprNode::Less(_) | ExprNode::Greater(_) | ExprNode::LessEqual(_) | ExprNode::GreaterEqual(_)
+                | ExprNode::Equal(_) | ExprNode::NotEqual(_) => ast::Compare(self, env),
+                ExprNode::Not(_) => ast::Not(self, env),
+                ExprNode::And(_) => ast::And(self, env),
+                ExprNode::Or(_) => ast::Or(self, env),
+                ExprNode::NamedExpr(_) => ast::NamedExpr(self, env)
+            },
+            AnyNode::Stmt(stmt) | AnyNode::Stmts(StmtsNode::Stmt(stmt)) => match stmt {
+                StmtNode::Assign(_) => ast::Assign(self, env),
+                StmtNode::AugAssignPlusequal(_) | StmtNode::AugAssignMinequal(_) | StmtNode::AugAssignStarequal(_)
+                | StmtNode::AugAssignSlashequal(_) | StmtNode::AugAssignDoubleslashequal(_) | StmtNode::AugAssignPercentequal(_)
+                | StmtNode::AugAssignDoublestarequal(_) | StmtNode::AugAssignLeftshiftequal(_) | StmtNode::AugAssignRightshiftequal(_)
+                | StmtNode::AugAssignAmperequal(_) | StmtNode::AugAssignCircumflexequal(_) | StmtNode::AugAssignVbarequal(_) => ast::AugAssign(self, env),
+                StmtNode::ExprStmt(_) => ast::ExprStmt(self, env),
+                StmtNode::Break(_) => ast::Break(self, env),
+                StmtNode::Continue(_) => ast::Continue(self, env),
+                StmtNode::If(_) | StmtNode::IfOrelse(_) => ast::If(self, env),
+                StmtNode::While(_) => ast::While(self, env),
+                StmtNode::ForExpr(_) | StmtNode::ForArgs(_) => ast::For(self, env),
+                StmtNode::FunctionDef(_) | StmtNode::FunctionDefRarrowExpr(_) => ast::FunctionDef(self, env),
+                StmtNode::Return(_) | StmtNode::ReturnExpr(_) => ast::Return(self, env)
+            },
+            AnyNode::Stmts(StmtsNode::Stmts(_)) => ast::Stmts(self, env),
+            AnyNode::Block(_) => ast::Block(self, env),
+            AnyNode::Orelse(OrelseNode::ElifOrelse(_) | OrelseNode::Elif(_)) => ast::If(self, env),
+            AnyNode::Orelse(OrelseNode::Else(_)) => ast::Else(self, env),
+            AnyNode::Program(_) => ast::Program(self, env),
+            //the parts of a map or a call are evaluated by the node they are in
+            AnyNode::Entries(_) | AnyNode::Args(_) => Err(RuntimeError::new(ErrorKind::SyntaxError, &format!("{} cant be evaluated on its own", self.class_name), self))
+        }
+    }
+}
+
+//structural, the position and source line a node came from dont matter
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.class_name == other.class_name && self.token == other.token && self.token_values == other.token_values
+    }
 }
 
 impl Debug for Node {
@@ -58,11 +161,17 @@ impl Debug for Node {
             "Mul" => write!(f, "Mul({:?} * {:?})", self.token_values[0], self.token_values[2]),
             "Div" => write!(f, "Div({:?} / {:?})", self.token_values[0], self.token_values[2]),
             "Minus" => write!(f, "Minus({:?} - {:?})", self.token_values[0], self.token_values[2]),
+            "Program" => write!(f, "Program({:?})", self.token_values[0]),
+            "ExprStmt" => write!(f, "ExprStmt({:?})", self.token_values[0]),
+            "Assign" => write!(f, "Assign({:?} = {:?})", self.token_values[0], self.token_values[2]),
             "TOKEN" => write!(f, "{}", self.token.as_ref().unwrap().value()),
             "Num" => write!(f, "{:?}", self.token_values[0].token.as_ref().unwrap()),
             "String" => write!(f, "{:?}", self.token_values[0].token.as_ref().unwrap()),
             "Name" => write!(f, "{:?}", self.token_values[0].token.as_ref().unwrap()),
-            other => todo!("{other}")
+            other => {
+                let children: Vec<String> = self.token_values.iter().map(|child| format!("{:?}", child)).collect();
+                write!(f, "{}({})", other, children.join(" "))
+            }
         }
         
     }
@@ -73,26 +182,340 @@ pub struct Rule
 {
     pub pattern: Vec<String>,
     pub class_name: String,
-    pub name: String
+    pub name: String,
+    //group rules like ( expr ) reduce to their inner node instead of a new one
+    pub group: bool
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} -> {} ({})", self.pattern.join(" "), self.name, self.class_name)
+    }
+}
+
+//levels with a higher power bind tighter and are applied first
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Assoc {
+    Left,
+    Right
+}
+
+pub struct Level
+{
+    pub power: i32,
+    pub name: Option<String>,
+    pub assoc: Assoc,
+    pub rules: Vec<Rule>
+}
+
+impl Level {
+    pub fn label(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => "".to_string()
+        }
+    }
+}
+
+pub enum LevelPosition<'a> {
+    Power(i32),
+    Before(&'a str),
+    After(&'a str)
+}
+
+fn is_token(symbol: &str) -> bool {
+    matches!(symbol, "NUM" | "STRING" | "NAME") || Token::from_name(symbol).is_some()
+}
+
+//lookahead predicates like &NAME or !LPAR, only the peg mode understands them
+pub fn is_predicate(symbol: &str) -> bool {
+    symbol.len() > 1 && (symbol.starts_with('&') || symbol.starts_with('!'))
+}
+
+//a rule like stmts -> stmts stmts that joins nodes of its own nonterminal. its nodes are
+//flat, all the joined nodes are children of one node instead of a chain as deep as a program
+//is long
+pub fn is_sequence(pattern: &[String], name: &str) -> bool {
+    pattern.len() > 1 && pattern.iter().all(|symbol| symbol == name)
+}
+
+//whether a node with children `names` can come from a rule with the pattern
+pub fn fits(pattern: &[String], name: &str, names: &[String]) -> bool {
+    if is_sequence(pattern, name) {
+        return names.len() > 1 && names.iter().all(|symbol| symbol == name);
+    }
+
+    pattern.iter().filter(|symbol| !is_predicate(symbol)).eq(names.iter())
+}
+
+//the node of a sequence rule, the children that are sequences of the same rule are spliced
+//in. the first one keeps its vectors, so joining one node after another stays linear
+pub fn join(class_name: &str, name: &str, children: Vec<Node>, index: usize, end: usize, line: &Arc<str>) -> Node {
+    let mut token_values: Vec<Node> = vec![];
+    let mut token_names: Vec<String> = vec![];
+
+    for mut child in children {
+        let sequence = child.token_names.len() > 1 && child.token_names.iter().all(|symbol| symbol == name);
+        if child.class_name == class_name && sequence {
+            if token_values.is_empty() {
+                token_values = std::mem::take(&mut child.token_values);
+                token_names = std::mem::take(&mut child.token_names);
+            } else {
+                token_values.append(&mut child.token_values);
+                token_names.append(&mut child.token_names);
+            }
+        } else {
+            token_values.push(child);
+            token_names.push(name.to_string());
+        }
+    }
+
+    Node {
+        class_name: class_name.to_string(),
+        index,
+        end,
+        token_values,
+        token_names,
+        line: line.clone(),
+        token: None
+    }
+}
+
+//whether a child at `position` of a rule pattern needs parentheses to keep its place,
+//children between two tokens of the pattern are delimited and never do
+pub fn needs_parens(power: i32, assoc: Assoc, position: usize, length: usize, child_power: i32) -> bool {
+    let left_edge = position == 0;
+    let right_edge = position == length - 1;
+
+    if !left_edge && !right_edge || child_power > power {
+        return false;
+    }
+    if child_power < power {
+        return true;
+    }
+    if left_edge && right_edge {
+        return false;
+    }
+
+    (left_edge && assoc == Assoc::Right) || (right_edge && assoc == Assoc::Left)
+}
+
+pub const POWER_STEP: i32 = 10;
+
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    pub line: String,
+    pub expected: String,
+    pub names: Vec<String>,
+    //some of the competing trees if the input was ambiguous, and how many there are
+    pub trees: Vec<Node>,
+    pub competing: usize
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.trees.is_empty() {
+            write!(f, "ambiguous parse in line: {} ({} competing trees", self.line, self.competing)?;
+            if self.competing > self.trees.len() {
+                write!(f, ", showing {}", self.trees.len())?;
+            }
+            write!(f, ")")?;
+            for tree in &self.trees {
+                write!(f, "\n  {:?}", tree)?;
+            }
+            return Ok(());
+        }
+
+        write!(f, "syntax error in line: {} (expected {}, got: {})", self.line, self.expected, self.names.join(" "))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Rewrite,
+    Earley,
+    Peg
 }
 
 pub struct Parser
 {
-    pub rules: Vec<Vec<Rule>>,
-    pub precedence: usize
+    pub levels: Vec<Level>,
+    //the whole input has to reduce to this, any single nonterminal if not set
+    pub start: Option<String>,
+    pub mode: Mode
+}
+
+//the rules as numbers, worked out once per parse so matching doesnt compare strings
+struct RuleTable {
+    //every symbol of the rules gets a number, the ones no rule mentions share the last one
+    symbols: HashMap<String, usize>,
+    //per level and rule, the pattern as symbol numbers
+    patterns: Vec<Vec<Vec<usize>>>,
+    //per level, whether a rule starts with the symbol, only such nodes can start a match
+    starts: Vec<Vec<bool>>,
+    //per level, the first two symbols of the tighter rules that could take the last node of a match
+    right: Vec<HashSet<(usize, usize)>>,
+    //per level, the last two symbols of the tighter rules that could take the first node of a match
+    left: Vec<HashSet<(usize, usize)>>,
+    //whether a rule reduces to the symbol, every other one is a token
+    nonterminals: Vec<bool>,
+    //(second symbol of a rule, symbol the node before it can end with) -> length of the longest such rule
+    continued: HashMap<(usize, usize), usize>,
+    //the longest pattern, a reduction can only change the matches that close to it
+    longest: usize
+}
+
+impl RuleTable {
+    fn symbol(&self, name: &str) -> usize {
+        self.symbols.get(name).copied().unwrap_or(self.symbols.len())
+    }
+}
+
+const NONE: usize = usize::MAX;
+
+//the nodes of the line while it is rewritten, linked by the token index they start at so a
+//reduction doesnt move the nodes after it
+struct Sequence {
+    names: Vec<String>,
+    symbols: Vec<usize>,
+    values: Vec<Option<Node>>,
+    next: Vec<usize>,
+    previous: Vec<usize>,
+    first: usize,
+    last: usize
+}
+
+impl Sequence {
+    fn new(length: usize) -> Sequence {
+        Sequence {
+            names: vec![String::new(); length],
+            symbols: vec![NONE; length],
+            values: vec![None; length],
+            next: vec![NONE; length],
+            previous: vec![NONE; length],
+            first: NONE,
+            last: NONE
+        }
+    }
+
+    fn push(&mut self, key: usize, name: String, symbol: usize, node: Node) {
+        self.names[key] = name;
+        self.symbols[key] = symbol;
+        self.values[key] = Some(node);
+        self.previous[key] = self.last;
+
+        match self.last {
+            NONE => self.first = key,
+            last => self.next[last] = key
+        }
+        self.last = key;
+    }
+
+    fn before(&self, key: usize) -> Option<usize> {
+        Some(self.previous[key]).filter(|key| *key != NONE)
+    }
+
+    fn after(&self, key: usize) -> Option<usize> {
+        Some(self.next[key]).filter(|key| *key != NONE)
+    }
+
+    fn keys(&self) -> Vec<usize> {
+        self.following(self.first, usize::MAX)
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.keys().into_iter().map(|key| self.names[key].clone()).collect()
+    }
+
+    //`key` and the ones after it, at most `count` of them
+    fn following(&self, key: usize, count: usize) -> Vec<usize> {
+        let mut keys = vec![];
+        let mut key = key;
+
+        while key != NONE && keys.len() < count {
+            keys.push(key);
+            key = self.next[key];
+        }
+
+        keys
+    }
+
+    //the keys whose matches can see the node at `key`, they start up to `distance` nodes before it
+    //and look one node past their end
+    fn around(&self, key: usize, distance: usize) -> Vec<usize> {
+        let mut start = key;
+        for _ in 0..distance {
+            match self.before(start) {
+                Some(before) => start = before,
+                None => break
+            }
+        }
+
+        let mut keys = vec![];
+        let mut current = start;
+        while current != NONE {
+            keys.push(current);
+            if current == key {
+                keys.extend(self.after(key));
+                break;
+            }
+            current = self.next[current];
+        }
+
+        keys
+    }
+
+    //the nodes of `keys` are replaced with one stored under the first key
+    fn replace(&mut self, keys: &[usize], name: String, symbol: usize, node: Node) {
+        let first = keys[0];
+        let after = self.next[keys[keys.len() - 1]];
+
+        for key in &keys[1..] {
+            self.values[*key] = None;
+        }
+
+        self.names[first] = name;
+        self.symbols[first] = symbol;
+        self.values[first] = Some(node);
+        self.next[first] = after;
+
+        match after {
+            NONE => self.last = first,
+            after => self.previous[after] = first
+        }
+    }
+}
+
+impl Default for Parser {
+    fn default() -> Parser {
+        Parser::new()
+    }
 }
 
 impl Parser
 {
     pub fn new() -> Parser {
         Parser {
-            rules: vec![vec![]],
-            precedence: 0
+            levels: vec![],
+            start: None,
+            mode: Mode::Rewrite
         }
     }
 
-    pub fn add_rule(&mut self, pattern: Vec<&str>, name: &str, class_name: &str)
-    {
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+    }
+
+    pub fn set_start(&mut self, name: &str) {
+        self.start = Some(name.to_string());
+    }
+
+    //every mode expects a rule to take up at least one token, rules that match nothing are refused
+    fn make_rule(pattern: Vec<&str>, name: &str, class_name: &str) -> Result<Rule, String> {
+        if pattern.iter().all(|symbol| is_predicate(symbol)) {
+            return Err(format!("empty rules arent supported: {name} -> {}", pattern.join(" ")));
+        }
+
         let mut pattern2 = vec![];
 
         for value in pattern {
@@ -103,121 +526,603 @@ impl Parser
 
         let class_name2 = class_name.to_string();
 
-        let rule = Rule {
+        Ok(Rule {
             pattern: pattern2,
             class_name: class_name2,
-            name: name2
+            name: name2,
+            group: false
+        })
+    }
+
+    pub fn add_rule_at(&mut self, level: &str, pattern: Vec<&str>, name: &str, class_name: &str) -> Result<(), String>
+    {
+        let index = self.level_index(level).ok_or(format!("precedence level not found: {level}"))?;
+
+        let rule = Parser::make_rule(pattern, name, class_name)?;
+        self.levels[index].rules.push(rule);
+
+        Ok(())
+    }
+
+    //creates an unnamed level if there is none with that power yet. the fufu grammar only
+    //uses named levels, this and remove_level are for grammars changed after they are built
+    pub fn add_rule_with_power(&mut self, power: i32, pattern: Vec<&str>, name: &str, class_name: &str) -> Result<(), String>
+    {
+        let rule = Parser::make_rule(pattern, name, class_name)?;
+
+        let index = match self.levels.iter().position(|level| level.power == power) {
+            Some(index) => index,
+            None => self.insert_level_at_power(power, None)
         };
-        self.rules[self.precedence].push(rule);
+        self.levels[index].rules.push(rule);
+
+        Ok(())
     }
 
-    pub fn new_precedence(&mut self) {
-        self.rules.push(vec![]);
-        self.precedence += 1;
+    //pattern has to contain exactly one nonterminal, e.g. LPAR expr RPAR
+    pub fn add_group_rule(&mut self, level: &str, pattern: Vec<&str>, name: &str) -> Result<(), String>
+    {
+        let inner = pattern.iter().filter(|symbol| !is_predicate(symbol) && !is_token(symbol)).count();
+        if inner != 1 {
+            return Err(format!("a group needs exactly one nonterminal, {name} has {inner}: {}", pattern.join(" ")));
+        }
+
+        self.add_rule_at(level, pattern, name, "Group")?;
+
+        let index = self.level_index(level).unwrap();
+        self.levels[index].rules.last_mut().unwrap().group = true;
+
+        Ok(())
     }
 
-    fn parse_rules(&self, mut names: Vec<String>, mut values: Vec<Node>, line: &str, rules: &Vec<Rule<>>) -> (Vec<Node>, Vec<String>){
+    pub fn set_assoc(&mut self, level: &str, assoc: Assoc) -> Result<(), String> {
+        let index = self.level_index(level).ok_or(format!("precedence level not found: {level}"))?;
+        self.levels[index].assoc = assoc;
 
-        
-        
-        for rule in rules {
-            let mut index = 0;
-            
-            if names.len() < rule.pattern.len() {
-                continue;
-            }
-            //println!("names: {:?}, rule: {:?}",  names, rule.pattern);
-            
-            //go throug all Tokens/Nodes
-            while index <= names.len() - rule.pattern.len() {
-                //println!("index: {}", index);
-                //println!("names: {:?}, {:?}", future_names, names);
-                //get:? the next tokens
-                //println!("{:?}, {}, {}", names, index, index + rule.pattern.len());
-                let next_token_names: Vec<String> = names[index..index + rule.pattern.len()].to_vec();
-                //println!("{:?}, {:?}, {}, {:?}", next_token_names, names, index, rule.pattern);
-                //matches
-                if next_token_names == rule.pattern {
-                    let next_token_values: Vec<Node> = values[index..index + rule.pattern.len()].to_vec();
-
-                    let node = Node {
-                        class_name: rule.class_name.clone(),
-                        token_values: next_token_values,
-                        token_names: next_token_names,
-                        index: index,
-                        line: line.to_string(),
-                        token: None
-                    };
-                    
-                    for _ in 0..rule.pattern.len() {
-                        values.remove(index);
-                        names.remove(index);
-                    }
-                    
-                    values.insert(index, node);
-                    names.insert(index, rule.name.clone());
-                    
-                    index += 1;
-                    //println!("matches: {:?}, {}", names, index);
+        Ok(())
+    }
+
+    pub fn level_index(&self, name: &str) -> Option<usize> {
+        self.levels.iter().position(|level| level.name.as_deref() == Some(name))
+    }
+
+    //returns the power the new level got
+    pub fn insert_level(&mut self, name: &str, position: LevelPosition) -> Result<i32, String> {
+        if self.level_index(name).is_some() {
+            return Err(format!("precedence level already exists: {name}"));
+        }
+
+        let power = match position {
+            LevelPosition::Power(power) => {
+                if self.levels.iter().any(|level| level.power == power) {
+                    return Err(format!("precedence level with power {power} already exists"));
+                }
+                power
+            },
+            LevelPosition::Before(other) => {
+                let index = self.level_index(other).ok_or(format!("precedence level not found: {other}"))?;
+
+                if index == 0 {
+                    self.levels[index].power + POWER_STEP
+                } else {
+                    self.power_between(index - 1, index)
+                }
+            },
+            LevelPosition::After(other) => {
+                let index = self.level_index(other).ok_or(format!("precedence level not found: {other}"))?;
+
+                if index == self.levels.len() - 1 {
+                    self.levels[index].power - POWER_STEP
                 } else {
-                    index += 1;
+                    self.power_between(index, index + 1)
+                }
+            }
+        };
+
+        self.insert_level_at_power(power, Some(name.to_string()));
+
+        Ok(power)
+    }
+
+    pub fn remove_level(&mut self, name: &str) -> Option<Level> {
+        let index = self.level_index(name)?;
+        Some(self.levels.remove(index))
+    }
+
+    //power strictly between two neighbouring levels, makes room if they are adjacent
+    fn power_between(&mut self, upper: usize, lower: usize) -> i32 {
+        if self.levels[upper].power - self.levels[lower].power < 2 {
+            for level in self.levels[..=upper].iter_mut() {
+                level.power += POWER_STEP;
+            }
+        }
+
+        self.levels[lower].power + (self.levels[upper].power - self.levels[lower].power) / 2
+    }
+
+    fn insert_level_at_power(&mut self, power: i32, name: Option<String>) -> usize {
+        let index = self.levels.iter().position(|level| level.power < power).unwrap_or(self.levels.len());
+
+        self.levels.insert(index, Level { power, name, assoc: Assoc::Left, rules: vec![] });
+
+        index
+    }
+
+    pub fn precedence_table(&self) -> String {
+        let mut table = vec![];
+
+        for level in &self.levels {
+            if level.assoc == Assoc::Right {
+                table.push(format!("{:>6}  {} (right)", level.power, level.label()));
+            } else {
+                table.push(format!("{:>6}  {}", level.power, level.label()));
+            }
+
+            for rule in &level.rules {
+                table.push(format!("          {}", rule));
+            }
+        }
+
+        table.join("\n")
+    }
+
+    //the levels can change until the parse starts, so the table is made for every parse
+    fn rule_table(&self) -> RuleTable {
+        let rules: Vec<(&Level, &Rule)> = self.levels.iter().flat_map(|level| level.rules.iter().map(move |rule| (level, rule))).collect();
+
+        let mut symbols: HashMap<String, usize> = HashMap::new();
+        for (_, rule) in &rules {
+            for symbol in rule.pattern.iter().chain([&rule.name]) {
+                let next = symbols.len();
+                symbols.entry(symbol.clone()).or_insert(next);
+            }
+        }
+
+        let count = symbols.len() + 1;
+        let pattern = |rule: &Rule| -> Vec<usize> { rule.pattern.iter().map(|symbol| symbols[symbol]).collect() };
+
+        let mut patterns = vec![];
+        let mut starts = vec![];
+        for level in &self.levels {
+            let level_patterns: Vec<Vec<usize>> = level.rules.iter().map(pattern).collect();
+            let mut level_starts = vec![false; count];
+            for first in level_patterns.iter().filter_map(|pattern| pattern.first()) {
+                level_starts[*first] = true;
+            }
+
+            patterns.push(level_patterns);
+            starts.push(level_starts);
+        }
+
+        let mut right = vec![];
+        let mut left = vec![];
+        for level in &self.levels {
+            let mut level_right = HashSet::new();
+            let mut level_left = HashSet::new();
+
+            for (other, rule) in &rules {
+                let tighter_right = other.power > level.power || (other.power == level.power && level.assoc == Assoc::Right);
+                let tighter_left = other.power > level.power || (other.power == level.power && level.assoc == Assoc::Left);
+                let pattern = pattern(rule);
+                let length = pattern.len();
+                if length < 2 {
+                    continue;
+                }
+
+                if tighter_right {
+                    level_right.insert((pattern[0], pattern[1]));
                 }
+                if tighter_left {
+                    level_left.insert((pattern[length - 1], pattern[length - 2]));
+                }
+            }
+
+            right.push(level_right);
+            left.push(level_left);
+        }
+
+        let mut continued: HashMap<(usize, usize), usize> = HashMap::new();
+        for (_, rule) in &rules {
+            let pattern = pattern(rule);
+            if pattern.len() < 2 {
+                continue;
+            }
+
+            //the node before can be the first symbol itself or a node that ends like one of its rules
+            let mut before = vec![pattern[0]];
+            before.extend(rules.iter().filter(|(_, other)| other.name == rule.pattern[0]).filter_map(|(_, other)| other.pattern.last().map(|last| symbols[last])));
+
+            for symbol in before {
+                let longest = continued.entry((pattern[1], symbol)).or_insert(0);
+                *longest = (*longest).max(pattern.len());
+            }
+        }
+
+        let mut nonterminals = vec![false; count];
+        for (_, rule) in &rules {
+            nonterminals[symbols[&rule.name]] = true;
+        }
+
+        RuleTable {
+            symbols,
+            patterns,
+            starts,
+            right,
+            left,
+            nonterminals,
+            continued,
+            longest: rules.iter().map(|(_, rule)| rule.pattern.len()).max().unwrap_or(0)
+        }
+    }
+
+    //a match has to wait if a tighter rule could still take one of its edge nodes,
+    //e.g. 1 + 2 * (3) must not reduce 1 + 2 before the group is done. a match starting
+    //with a token waits too if a rule of any level continues the node before it (or one it
+    //is the end of) with that token, so the (x) in f(x) and g(a)(x) is an argument list
+    fn blocked(table: &RuleTable, level: usize, sequence: &Sequence, keys: &[usize]) -> bool {
+        let first = sequence.symbols[keys[0]];
+        let last = sequence.symbols[keys[keys.len() - 1]];
+
+        if let Some(after) = sequence.after(keys[keys.len() - 1]) {
+            if table.right[level].contains(&(last, sequence.symbols[after])) {
+                return true;
+            }
+        }
+
+        if let Some(before) = sequence.before(keys[0]) {
+            let before = sequence.symbols[before];
+            if table.left[level].contains(&(first, before)) {
+                return true;
+            }
+
+            let starts_with_token = !table.nonterminals[first];
+            if starts_with_token && table.continued.get(&(first, before)).is_some_and(|longest| *longest > keys.len()) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    //the first rule of the level that can be reduced at `key`, with the keys of the nodes it takes
+    fn applicable<'a>(table: &RuleTable, number: usize, level: &'a Level, sequence: &Sequence, key: usize) -> Option<(&'a Rule, Vec<usize>)> {
+        let following = sequence.following(key, table.longest);
+
+        for (rule, pattern) in level.rules.iter().zip(&table.patterns[number]) {
+            if pattern.len() > following.len() {
+                continue;
+            }
+
+            let keys = &following[..pattern.len()];
+            if keys.iter().zip(pattern).all(|(key, symbol)| sequence.symbols[*key] == *symbol) && !Parser::blocked(table, number, sequence, keys) {
+                return Some((rule, keys.to_vec()));
+            }
+        }
+
+        None
+    }
+
+    //reduces the nodes of `keys` with the rule, group rules give back their inner node
+    fn reduce(sequence: &mut Sequence, table: &RuleTable, rule: &Rule, keys: &[usize], line: &Arc<str>) {
+        let next_token_names: Vec<String> = keys.iter().map(|key| sequence.names[*key].clone()).collect();
+        let next_token_values: Vec<Node> = keys.iter().map(|key| sequence.values[*key].take().unwrap()).collect();
+        let index = next_token_values[0].index;
+        let end = next_token_values[next_token_values.len() - 1].end;
+
+        let node = if rule.group {
+            //the inner node, but covering the parentheses too
+            let mut inner = next_token_values.into_iter().find(|value| value.class_name != "TOKEN").unwrap();
+            inner.index = index;
+            inner.end = end;
+            inner
+        } else if is_sequence(&rule.pattern, &rule.name) {
+            join(&rule.class_name, &rule.name, next_token_values, index, end, line)
+        } else {
+            Node {
+                class_name: rule.class_name.clone(),
+                index,
+                end,
+                token_values: next_token_values,
+                token_names: next_token_names,
+                line: line.clone(),
+                token: None
+            }
+        };
+
+        sequence.replace(keys, rule.name.clone(), table.symbol(&rule.name), node);
+    }
+
+    pub fn parse(&self, tokens: &Vec<Token>, line: &str) -> Result<Node, ParseError> {
+        self.parse_inner(tokens, line, None)
+    }
+
+    //same as parse but records every step of the rewrite loop, the earley and peg modes
+    //dont have one to record
+    pub fn parse_traced(&self, tokens: &Vec<Token>, line: &str) -> Result<(Result<Node, ParseError>, Trace), String> {
+        if self.mode != Mode::Rewrite {
+            return Err(format!("only the rewrite mode can be traced, not {:?}", self.mode));
+        }
+
+        let mut trace = Trace::new(line);
+        let node = self.parse_inner(tokens, line, Some(&mut trace));
+
+        Ok((node, trace))
+    }
+
+    //like parse, but already parsed subtrees (with their token positions in `tokens`) are
+    //used as they are, only the rewrite mode can make use of them
+    pub fn parse_reusing(&self, tokens: &Vec<Token>, line: &str, reused: Vec<Node>) -> Result<Node, ParseError> {
+        if self.mode != Mode::Rewrite {
+            return self.parse(tokens, line);
+        }
 
+        Parser::check_tokens(tokens, line)?;
+        self.check_predicates(tokens, line)?;
+        self.parse_inner_reusing(tokens, line, None, reused)
+    }
 
-                if names.len() < rule.pattern.len() {
-                    break;
+    //the name a node was reduced to
+    pub fn rule_name(&self, node: &Node) -> Option<String> {
+        for level in &self.levels {
+            for rule in &level.rules {
+                if !rule.group && rule.class_name == node.class_name && fits(&rule.pattern, &rule.name, &node.token_names) {
+                    return Some(rule.name.clone());
                 }
             }
+        }
+
+        None
+    }
+
+    //only the peg mode can check a lookahead predicate, the others would never match the rule
+    fn check_predicates(&self, tokens: &[Token], line: &str) -> Result<(), ParseError> {
+        let rule = self.levels.iter().flat_map(|level| level.rules.iter()).find(|rule| rule.pattern.iter().any(|symbol| is_predicate(symbol)));
+
+        match rule {
+            Some(rule) if self.mode != Mode::Peg => Err(ParseError {
+                line: line.to_string(),
+                expected: format!("a grammar without lookahead predicates in {:?} mode, got {} -> {}", self.mode, rule.name, rule.pattern.join(" ")),
+                names: tokens.iter().map(|token| token.name()).collect(),
+                trees: vec![],
+                competing: 0
+            }),
+            _ => Ok(())
+        }
+    }
+
+    //text the lexer couldnt make a token of is reported with what was wrong with it
+    fn check_tokens(tokens: &[Token], line: &str) -> Result<(), ParseError> {
+        match tokens.iter().position(|token| matches!(token, Token::ERRORTOKEN(_))) {
+            Some(index) => Err(ParseError {
+                line: line.to_string(),
+                expected: tokens[index].value(),
+                names: tokens[index..].iter().map(|token| token.name()).collect(),
+                trees: vec![],
+                competing: 0
+            }),
+            None => Ok(())
+        }
+    }
+
+    fn parse_inner(&self, tokens: &Vec<Token>, line: &str, trace: Option<&mut Trace>) -> Result<Node, ParseError> {
+        Parser::check_tokens(tokens, line)?;
+        self.check_predicates(tokens, line)?;
 
-            //println!("{:?}", names);
+        if self.mode == Mode::Earley {
+            return earley::parse(self, tokens, line);
+        }
+        if self.mode == Mode::Peg {
+            return peg::parse(self, tokens, line);
         }
 
-        //println!("names at the end: {:?}", names);
+        self.parse_inner_reusing(tokens, line, trace, vec![])
+    }
 
-        (values, names)
-    }   
+    fn parse_inner_reusing(&self, tokens: &[Token], line: &str, mut trace: Option<&mut Trace>, mut reused: Vec<Node>) -> Result<Node, ParseError> {
+        let table = self.rule_table();
+        let mut sequence = Sequence::new(tokens.len());
+        let source: Arc<str> = Arc::from(line);
 
-    pub fn parse(&self, tokens: &Vec<Token>, line: &str) -> Node {
-        let mut names: Vec<String> = vec![];
-        let mut values: Vec<Node> = vec![];
+        reused.sort_by_key(|node| node.index);
+        let mut reused = reused.into_iter().peekable();
 
         //put the tokens in the ast tree
-        for token in tokens {
-            values.push(
-                Node {
-                    class_name: "TOKEN".to_string(),
-                    token_values: vec![],
-                    token_names: vec![],
-                    index: 0,
-                    line: "TOKEN".to_string(),
-                    token: Some(token.clone())
+        let mut index = 0;
+        while index < tokens.len() {
+            if let Some(mut node) = reused.next_if(|node| node.index == index) {
+                if let Some(name) = self.rule_name(&node) {
+                    node.set_line(&source);
+                    let end = node.end;
+                    sequence.push(index, name.clone(), table.symbol(&name), node);
+                    index = end;
+                    continue;
                 }
-            );
-            names.push(token.name());
+            }
+
+            let name = tokens[index].name();
+            sequence.push(index, name.clone(), table.symbol(&name), Node::from_token(&tokens[index], index, &source));
+            index += 1;
         }
 
-        //aply rules until it hasnt changed
+        //aply one rule at a time, always the leftmost match (rightmost for right associative
+        //levels) of the tightest level that has one, until nothing matches. every level keeps
+        //the nodes a match could start at, the ones without one are dropped and only come back
+        //when a reduction changes the nodes around them
+        let keys = sequence.keys();
+        let mut candidates: Vec<BTreeSet<usize>> = table.starts.iter().map(|starts| keys.iter().copied().filter(|key| starts[sequence.symbols[*key]]).collect()).collect();
+        let mut iteration = 0;
         loop {
-            let prev_ast = names.clone();
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.iteration(iteration, &sequence.names());
+            }
+
+            let mut reduced = None;
+            'levels: for (number, level) in self.levels.iter().enumerate() {
+                loop {
+                    let key = match level.assoc {
+                        Assoc::Left => candidates[number].pop_first(),
+                        Assoc::Right => candidates[number].pop_last()
+                    };
+                    let key = match key {
+                        Some(key) => key,
+                        None => break
+                    };
 
-            for rules_in_precedence in self.rules.iter() {
-                (values, names) = self.parse_rules(names, values, line, rules_in_precedence);
+                    if sequence.values[key].is_none() {
+                        continue;
+                    }
+                    if let Some((rule, keys)) = Parser::applicable(&table, number, level, &sequence, key) {
+                        Parser::reduce(&mut sequence, &table, rule, &keys, &source);
+                        reduced = Some((level, rule, key));
+                        break 'levels;
+                    }
+                }
             }
 
-            //println!("names: {:?}", names);
+            let (level, rule, key) = match reduced {
+                Some(reduced) => reduced,
+                None => break
+            };
 
-            //hasnt changed
-            if names == prev_ast {
-                break;
+            if let Some(trace) = trace.as_deref_mut() {
+                let index = sequence.keys().iter().position(|other| *other == key).unwrap();
+                trace.reduction(iteration, level.power, &level.label(), rule, index, &sequence.names());
             }
+
+            for key in sequence.around(key, table.longest) {
+                for (level, starts) in candidates.iter_mut().zip(&table.starts) {
+                    if starts[sequence.symbols[key]] {
+                        level.insert(key);
+                    }
+                }
+            }
+
+            iteration += 1;
         }
 
-        println!("{:?}", values);
+        let names = sequence.names();
+        let accepted = match &self.start {
+            Some(start) => names.len() == 1 && names[0] == *start,
+            None => names.len() == 1 && sequence.values[sequence.first].as_ref().is_some_and(|value| value.class_name != "TOKEN")
+        };
 
-        if values.len() != 1 {
-            panic!("syntaxt error in line: {line}")
+        if !accepted {
+            return Err(ParseError {
+                line: line.to_string(),
+                expected: self.start.clone().unwrap_or("a single node".to_string()),
+                names,
+                trees: vec![],
+                competing: 0
+            });
         }
 
-        return values[0].clone();
+        Ok(sequence.values[sequence.first].take().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar_test::{assert_parses, assert_rejects};
+
+    //+ and * with nothing between them, the tests insert levels afterwards
+    fn arithmetic() -> Parser {
+        let mut parser = Parser::new();
+
+        parser.insert_level("atom", LevelPosition::Power(300)).unwrap();
+        parser.insert_level("multiplicative", LevelPosition::Power(200)).unwrap();
+        parser.insert_level("additive", LevelPosition::Power(100)).unwrap();
+        parser.insert_level("statement", LevelPosition::Power(10)).unwrap();
+
+        parser.add_rule_at("atom", vec!["NUM"], "expr", "Num").unwrap();
+        parser.add_rule_at("multiplicative", vec!["expr", "STAR", "expr"], "expr", "Mul").unwrap();
+        parser.add_rule_at("additive", vec!["expr", "PLUS", "expr"], "expr", "Add").unwrap();
+        parser.add_rule_at("statement", vec!["expr", "SEMI"], "stmt", "ExprStmt").unwrap();
+        parser.set_start("stmt");
+
+        parser
+    }
+
+    #[test]
+    fn insert_level_after() {
+        let mut parser = arithmetic();
+        let power = parser.insert_level("modulo", LevelPosition::After("multiplicative")).unwrap();
+        parser.add_rule_at("modulo", vec!["expr", "PERCENT", "expr"], "expr", "Mod").unwrap();
+
+        assert!(100 < power && power < 200);
+        assert_parses(&parser, "1 + 2 % 3 * 4", "(Add 1 (Mod 2 (Mul 3 4)))");
+        assert_parses(&parser, "1 * 2 % 3 + 4", "(Add (Mod (Mul 1 2) 3) 4)");
+    }
+
+    #[test]
+    fn insert_level_before() {
+        let mut parser = arithmetic();
+        parser.insert_level("modulo", LevelPosition::Before("additive")).unwrap();
+        parser.add_rule_at("modulo", vec!["expr", "PERCENT", "expr"], "expr", "Mod").unwrap();
+
+        let names: Vec<String> = parser.levels.iter().map(|level| level.label()).collect();
+        assert_eq!(names, ["atom", "multiplicative", "modulo", "additive", "statement"]);
+        assert_parses(&parser, "1 + 2 % 3 * 4", "(Add 1 (Mod 2 (Mul 3 4)))");
+        assert_rejects(&parser, "1 % + 2");
+    }
+
+    //there is no power left between 101 and 100, the levels above are moved up
+    #[test]
+    fn insert_level_between_adjacent_powers() {
+        let mut parser = arithmetic();
+        parser.insert_level("tight", LevelPosition::Power(101)).unwrap();
+        parser.insert_level("between", LevelPosition::After("tight")).unwrap();
+
+        let names: Vec<String> = parser.levels.iter().map(|level| level.label()).collect();
+        assert_eq!(names, ["atom", "multiplicative", "tight", "between", "additive", "statement"]);
+        assert!(parser.levels.windows(2).all(|pair| pair[0].power > pair[1].power));
+    }
+
+    #[test]
+    fn rule_with_power_and_removed_level() {
+        let mut parser = arithmetic();
+        parser.add_rule_with_power(150, vec!["expr", "PERCENT", "expr"], "expr", "Mod").unwrap();
+        assert_parses(&parser, "1 + 2 % 3 * 4", "(Add 1 (Mod 2 (Mul 3 4)))");
+
+        assert!(parser.remove_level("multiplicative").is_some());
+        assert!(parser.remove_level("multiplicative").is_none());
+        assert_rejects(&parser, "1 * 2");
+        assert_eq!(parser.levels.len(), 4);
+    }
+
+    #[test]
+    fn unknown_level() {
+        let mut parser = arithmetic();
+
+        assert!(parser.insert_level("modulo", LevelPosition::After("missing")).is_err());
+        assert!(parser.insert_level("additive", LevelPosition::Power(150)).is_err());
+        assert!(parser.insert_level("other", LevelPosition::Power(200)).is_err());
+        assert!(parser.add_rule_at("missing", vec!["NUM"], "expr", "Num").is_err());
+        assert!(parser.set_assoc("missing", Assoc::Right).is_err());
+        assert_eq!(parser.levels.len(), 4);
+    }
+
+    #[test]
+    fn empty_rule() {
+        let mut parser = arithmetic();
+
+        assert!(parser.add_rule_at("atom", vec![], "expr", "Empty").is_err());
+        assert!(parser.add_rule_at("atom", vec!["!NUM"], "expr", "Empty").is_err());
+        assert!(parser.add_rule_with_power(150, vec![], "expr", "Empty").is_err());
+        assert_eq!(parser.levels.len(), 4);
+        assert_eq!(parser.levels[0].rules.len(), 1);
+    }
+
+    #[test]
+    fn group_needs_one_nonterminal() {
+        let mut parser = arithmetic();
+
+        assert!(parser.add_group_rule("atom", vec!["LPAR", "RPAR"], "expr").is_err());
+        assert!(parser.add_group_rule("atom", vec!["LPAR", "NUM", "RPAR"], "expr").is_err());
+        assert!(parser.add_group_rule("atom", vec!["LPAR", "expr", "COMMA", "expr", "RPAR"], "expr").is_err());
+        assert!(parser.levels[0].rules.len() == 1);
+
+        parser.add_group_rule("atom", vec!["LPAR", "expr", "RPAR"], "expr").unwrap();
+        assert_parses(&parser, "(1 + 2) * 3", "(Mul (Add 1 2) 3)");
     }
 }
\ No newline at end of file
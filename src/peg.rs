@@ -0,0 +1,383 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::lexer::Token;
+use crate::parser::{is_predicate, is_sequence, join, needs_parens, Assoc, Node, ParseError, Parser};
+
+struct FlatRule {
+    pattern: Vec<String>,
+    //pattern without the lookahead predicates, these are the children of the node
+    consumed: Vec<String>,
+    name: String,
+    class_name: String,
+    group: bool,
+    power: i32,
+    assoc: Assoc
+}
+
+impl FlatRule {
+    fn left_recursive(&self) -> bool {
+        self.consumed.len() > 1 && self.consumed[0] == self.name
+    }
+
+    //lowest power a child at this position may have without parentheses, precedence is
+    //only between nodes of the same nonterminal
+    fn child_min(&self, position: usize) -> i32 {
+        let length = self.consumed.len();
+
+        if length == 1 || (position != 0 && position != length - 1) || self.consumed[position] != self.name {
+            return i32::MIN;
+        }
+        if !needs_parens(self.power, self.assoc, position, length, self.power) {
+            return self.power;
+        }
+
+        self.power + 1
+    }
+}
+
+#[derive(Clone)]
+struct Tree {
+    node: Node,
+    power: i32
+}
+
+struct Peg<'a> {
+    rules: Vec<FlatRule>,
+    tokens: &'a Vec<Token>,
+    names: Vec<String>,
+    line: Arc<str>,
+    //packrat tables, every (nonterminal, position, min power) and (rule, position) is parsed once
+    memo: HashMap<(String, usize, i32), Option<(Tree, usize)>>,
+    rule_memo: HashMap<(usize, usize), Option<(Tree, usize)>>,
+    furthest: usize,
+    expected: Vec<String>
+}
+
+impl<'a> Peg<'a> {
+    fn new(parser: &Parser, tokens: &'a Vec<Token>, line: &str) -> Peg<'a> {
+        let mut rules = vec![];
+
+        for level in &parser.levels {
+            for rule in &level.rules {
+                rules.push(FlatRule {
+                    pattern: rule.pattern.clone(),
+                    consumed: rule.pattern.iter().filter(|symbol| !is_predicate(symbol)).cloned().collect(),
+                    name: rule.name.clone(),
+                    class_name: rule.class_name.clone(),
+                    group: rule.group,
+                    power: level.power,
+                    assoc: level.assoc
+                });
+            }
+        }
+
+        Peg {
+            rules,
+            tokens,
+            names: tokens.iter().map(|token| token.name()).collect(),
+            line: Arc::from(line),
+            memo: HashMap::new(),
+            rule_memo: HashMap::new(),
+            furthest: 0,
+            expected: vec![]
+        }
+    }
+
+    fn is_nonterminal(&self, symbol: &str) -> bool {
+        self.rules.iter().any(|rule| rule.name == symbol)
+    }
+
+    fn fail(&mut self, position: usize, symbol: &str) {
+        if position > self.furthest {
+            self.furthest = position;
+            self.expected.clear();
+        }
+        if position == self.furthest && !self.expected.contains(&symbol.to_string()) {
+            self.expected.push(symbol.to_string());
+        }
+    }
+
+    fn parse_symbol(&mut self, symbol: &str, position: usize, min_power: i32) -> Option<(Tree, usize)> {
+        if self.is_nonterminal(symbol) {
+            return self.parse_nonterminal(symbol, position, min_power);
+        }
+
+        if position < self.names.len() && self.names[position] == symbol {
+            return Some((Tree { node: Node::from_token(&self.tokens[position], position, &self.line), power: i32::MAX }, position + 1));
+        }
+
+        self.fail(position, symbol);
+        None
+    }
+
+    //ordered choice over the rules of `name`, left recursive rules are applied
+    //afterwards as operators, tightest level first
+    fn parse_nonterminal(&mut self, name: &str, position: usize, min_power: i32) -> Option<(Tree, usize)> {
+        let key = (name.to_string(), position, min_power);
+        if let Some(result) = self.memo.get(&key) {
+            return result.clone();
+        }
+        //guards against rules like a -> b, b -> a
+        self.memo.insert(key.clone(), None);
+
+        let mut left = None;
+
+        for index in 0..self.rules.len() {
+            let rule = &self.rules[index];
+            if rule.name != name || rule.left_recursive() || rule.power < min_power {
+                continue;
+            }
+
+            if let Some(result) = self.parse_rule(index, position) {
+                left = Some(result);
+                break;
+            }
+        }
+
+        let (mut left, mut end) = left?;
+
+        loop {
+            let mut progress = false;
+
+            for index in 0..self.rules.len() {
+                let rule = &self.rules[index];
+                if rule.name != name || !rule.left_recursive() || rule.power < min_power || left.power < rule.child_min(0) {
+                    continue;
+                }
+
+                if let Some((mut children, next)) = self.match_from(index, 1, end) {
+                    children.insert(0, left);
+                    left = self.build(index, children, position, next);
+                    end = next;
+                    progress = true;
+                    break;
+                }
+            }
+
+            if !progress {
+                break;
+            }
+        }
+
+        let result = Some((left, end));
+        self.memo.insert(key, result.clone());
+
+        result
+    }
+
+    fn parse_rule(&mut self, index: usize, position: usize) -> Option<(Tree, usize)> {
+        if let Some(result) = self.rule_memo.get(&(index, position)) {
+            return result.clone();
+        }
+
+        let result = self.match_from(index, 0, position).map(|(children, end)| (self.build(index, children, position, end), end));
+
+        self.rule_memo.insert((index, position), result.clone());
+
+        result
+    }
+
+    //matches the pattern of a rule from symbol `start` on
+    fn match_from(&mut self, index: usize, start: usize, mut position: usize) -> Option<(Vec<Tree>, usize)> {
+        let pattern = self.rules[index].pattern.clone();
+        let mut children = vec![];
+        let mut consumed = 0;
+
+        for symbol in &pattern {
+            if is_predicate(symbol) {
+                if consumed < start {
+                    continue;
+                }
+
+                let found = self.parse_symbol(&symbol[1..], position, i32::MIN).is_some();
+                if found != symbol.starts_with('&') {
+                    return None;
+                }
+                continue;
+            }
+
+            if consumed >= start {
+                let min_power = self.rules[index].child_min(consumed);
+                let (child, end) = self.parse_symbol(symbol, position, min_power)?;
+
+                children.push(child);
+                position = end;
+            }
+
+            consumed += 1;
+        }
+
+        Some((children, position))
+    }
+
+    fn build(&self, index: usize, children: Vec<Tree>, position: usize, end: usize) -> Tree {
+        let rule = &self.rules[index];
+
+        if rule.group {
+            let mut inner = children.iter().zip(rule.consumed.iter()).find(|(_, symbol)| self.is_nonterminal(symbol)).unwrap().0.node.clone();
+            inner.index = position;
+            inner.end = end;
+            return Tree { node: inner, power: rule.power };
+        }
+        if is_sequence(&rule.consumed, &rule.name) {
+            let children = children.into_iter().map(|child| child.node).collect();
+            return Tree { node: join(&rule.class_name, &rule.name, children, position, end, &self.line), power: rule.power };
+        }
+
+        Tree {
+            node: Node {
+                class_name: rule.class_name.clone(),
+                token_values: children.into_iter().map(|child| child.node).collect(),
+                token_names: rule.consumed.clone(),
+                index: position,
+                end,
+                line: self.line.clone(),
+                token: None
+            },
+            power: rule.power
+        }
+    }
+}
+
+//packrat parsing, rules of a nonterminal are tried in order and the first match wins,
+//`&NAME` and `!NAME` in a pattern check the next tokens without consuming them
+pub fn parse(parser: &Parser, tokens: &Vec<Token>, line: &str) -> Result<Node, ParseError> {
+    let mut peg = Peg::new(parser, tokens, line);
+
+    let start = match &parser.start {
+        Some(start) if peg.is_nonterminal(start) => start.clone(),
+        _ => return Err(ParseError {
+            line: line.to_string(),
+            expected: format!("a start symbol that has rules, got {:?} (see Parser::set_start)", parser.start),
+            names: peg.names.clone(),
+            trees: vec![],
+            competing: 0
+        })
+    };
+
+    if let Some((tree, end)) = peg.parse_symbol(&start, 0, i32::MIN) {
+        if end == peg.names.len() {
+            return Ok(tree.node);
+        }
+
+        peg.fail(end, "end of line");
+    }
+
+    Err(ParseError {
+        line: line.to_string(),
+        expected: peg.expected.join(" or "),
+        names: peg.names[peg.furthest.min(peg.names.len())..].to_vec(),
+        trees: vec![],
+        competing: 0
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar_test::{assert_parses, sexpr};
+    use crate::lexer;
+    use crate::parser::{LevelPosition, Mode};
+
+    //names and numbers with calls after them, `names` are the rules for a NAME
+    fn calls(names: &[(&[&str], &str)]) -> Parser {
+        let mut parser = Parser::new();
+        parser.set_mode(Mode::Peg);
+
+        parser.insert_level("atom", LevelPosition::Power(300)).unwrap();
+        parser.insert_level("postfix", LevelPosition::Power(250)).unwrap();
+        parser.insert_level("statement", LevelPosition::Power(10)).unwrap();
+
+        for (pattern, class_name) in names {
+            parser.add_rule_at("atom", pattern.to_vec(), "expr", class_name).unwrap();
+        }
+        parser.add_rule_at("atom", vec!["NUM"], "expr", "Num").unwrap();
+        parser.add_rule_at("postfix", vec!["expr", "LPAR", "expr", "RPAR"], "expr", "Call").unwrap();
+        parser.add_rule_at("statement", vec!["expr", "SEMI"], "stmt", "ExprStmt").unwrap();
+        parser.set_start("stmt");
+
+        parser
+    }
+
+    //the classes the names in the source were parsed as, sexpr leaves them out
+    fn name_classes(parser: &Parser, source: &str) -> Vec<String> {
+        fn collect(node: &Node, classes: &mut Vec<String>) {
+            if matches!(node.token_values.first().and_then(|child| child.token.as_ref()), Some(Token::NAME(_))) {
+                classes.push(node.class_name.clone());
+            }
+            for child in &node.token_values {
+                collect(child, classes);
+            }
+        }
+
+        let mut classes = vec![];
+        collect(&parser.parse(&lexer::lex(source), source).unwrap(), &mut classes);
+        classes
+    }
+
+    #[test]
+    fn positive_lookahead() {
+        let parser = calls(&[(&["NAME", "&LPAR"], "Function"), (&["NAME"], "Variable")]);
+
+        assert_parses(&parser, "f(g(x))", "(Call f (Call g x))");
+        assert_eq!(name_classes(&parser, "f(g(x))"), ["Function", "Function", "Variable"]);
+        assert_eq!(name_classes(&parser, "x"), ["Variable"]);
+
+        //without the lookahead the first rule takes every name
+        let parser = calls(&[(&["NAME"], "Function"), (&["NAME"], "Variable")]);
+        assert_eq!(name_classes(&parser, "f(x)"), ["Function", "Function"]);
+    }
+
+    #[test]
+    fn negative_lookahead() {
+        let parser = calls(&[(&["NAME", "!LPAR"], "Variable"), (&["NAME"], "Function")]);
+
+        assert_parses(&parser, "f(x)", "(Call f x)");
+        assert_eq!(name_classes(&parser, "f(x)"), ["Function", "Variable"]);
+        assert_eq!(name_classes(&parser, "x"), ["Variable"]);
+    }
+
+    //the lookahead and the rule after it parse expr at the same position, the second time
+    //it comes from the memo and the lookahead doesnt move the position
+    #[test]
+    fn lookahead_is_memoized() {
+        let mut parser = calls(&[(&["NAME"], "Name")]);
+        parser.add_rule_at("statement", vec!["!NUM", "expr", "COLON", "SEMI"], "stmt", "Label").unwrap();
+        parser.add_rule_at("statement", vec!["&expr", "expr", "LPAR", "RPAR", "SEMI"], "stmt", "Checked").unwrap();
+
+        let source = "f(1)()";
+        let tokens = lexer::lex(source);
+        let mut peg = Peg::new(&parser, &tokens, source);
+        let (tree, end) = peg.parse_symbol("stmt", 0, i32::MIN).unwrap();
+
+        assert_eq!(sexpr(&tree.node), "(Checked (Call f 1))");
+        assert_eq!((tree.node.index, end), (0, 7));
+        assert_eq!(peg.memo[&("expr".to_string(), 0, i32::MIN)].as_ref().map(|(_, end)| *end), Some(4));
+
+        //a NUM first rules out the label, the other rules still match
+        assert_parses(&parser, "1(2)()", "(Checked (Call 1 2))");
+        assert!(parser.parse(&lexer::lex("1:"), "1:").is_err());
+        assert_parses(&parser, "a:", "(Label a)");
+    }
+
+    #[test]
+    fn missing_start_symbol() {
+        let mut parser = calls(&[(&["NAME"], "Name")]);
+        parser.set_start("missing");
+        assert!(parser.parse(&lexer::lex("x"), "x").is_err());
+
+        parser.start = None;
+        assert!(parser.parse(&lexer::lex("x"), "x").is_err());
+    }
+
+    #[test]
+    fn predicates_only_in_peg_mode() {
+        for mode in [Mode::Rewrite, Mode::Earley] {
+            let mut parser = calls(&[(&["NAME", "&LPAR"], "Function"), (&["NAME"], "Variable")]);
+            parser.set_mode(mode);
+
+            let error = parser.parse(&lexer::lex("x"), "x").unwrap_err();
+            assert!(error.expected.contains("lookahead"), "{:?}: {error}", mode);
+        }
+    }
+}
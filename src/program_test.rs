@@ -0,0 +1,141 @@
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use crate::compiler;
+use crate::environment::{Capture, Environment};
+use crate::error::RuntimeError;
+use crate::lexer;
+use crate::parser::{Node, Parser};
+use crate::value::Value;
+use crate::vm;
+use crate::EVAL_STACK_SIZE;
+
+//what a program printed, with the error it stopped on at the end
+fn output(capture: &Capture, result: &Result<Value, RuntimeError>) -> (String, String) {
+    match result {
+        Ok(value) => (capture.text(), value.repr()),
+        Err(error) => (format!("{}{error}\n", capture.text()), "error".to_string())
+    }
+}
+
+//the tree walker recurses on the rust stack, so it gets a thread with a large one
+fn walk(ast: Node, input: String) -> (String, String) {
+    let walker = std::thread::Builder::new().stack_size(EVAL_STACK_SIZE).spawn(move || {
+        let capture = Capture::new();
+        let mut env = Environment::with_io(Box::new(Cursor::new(input)), Box::new(capture.clone()));
+        let result = ast.eval(&mut env);
+        output(&capture, &result)
+    });
+
+    match walker.map(|walker| walker.join()) {
+        Ok(Ok(output)) => output,
+        _ => ("the tree walker panicked\n".to_string(), "error".to_string())
+    }
+}
+
+fn run_vm(ast: &Node, input: String) -> (String, String) {
+    let program = compiler::compile(ast);
+    let capture = Capture::new();
+    let mut env = Environment::with_io(Box::new(Cursor::new(input)), Box::new(capture.clone()));
+    let result = vm::run(&program, &mut env);
+    output(&capture, &result)
+}
+
+//runs name.fufu with the tree walker and the vm, both have to print what name.out says and
+//evaluate to the same value. name.in is what input() reads, if it is there
+pub fn run_file(parser: &Parser, path: &Path) -> Result<(), String> {
+    let source = fs::read_to_string(path).map_err(|error| format!("{}: couldnt read file: {error}", path.display()))?;
+    let expected = fs::read_to_string(path.with_extension("out")).map_err(|error| format!("{}: couldnt read the .out file: {error}", path.display()))?;
+    let input = fs::read_to_string(path.with_extension("in")).unwrap_or_default();
+
+    let ast = parser.parse(&lexer::lex(&source), &source).map_err(|error| format!("{}: {error}", path.display()))?;
+
+    let (vm_output, vm_value) = run_vm(&ast, input.clone());
+    let (walker_output, walker_value) = walk(ast, input);
+
+    for (engine, output) in [("tree walker", &walker_output), ("vm", &vm_output)] {
+        if *output != expected {
+            return Err(format!("{}: the {engine} printed\n{output}expected\n{expected}", path.display()));
+        }
+    }
+    if walker_value != vm_value {
+        return Err(format!("{}: the tree walker gave {walker_value}, the vm {vm_value}", path.display()));
+    }
+
+    Ok(())
+}
+
+//every .fufu file in the directory, sorted by name
+pub fn run_dir(parser: &Parser, dir: &Path) -> (usize, Vec<String>) {
+    let mut paths = match fs::read_dir(dir) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).filter(|path| path.extension().is_some_and(|extension| extension == "fufu")).collect::<Vec<_>>(),
+        Err(error) => return (0, vec![format!("{}: couldnt read directory: {error}", dir.display())])
+    };
+    paths.sort();
+
+    let mut passed = 0;
+    let mut failures = vec![];
+
+    for path in paths {
+        match run_file(parser, &path) {
+            Ok(()) => passed += 1,
+            Err(error) => failures.push(error)
+        }
+    }
+
+    (passed, failures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Mode;
+
+    //the programs in tests/programs
+    #[test]
+    fn program_files() {
+        let parser = crate::grammar().unwrap();
+        unsafe {
+            lexer::AST_PRINT = true;
+        }
+
+        let (passed, failures) = run_dir(&parser, Path::new("tests/programs"));
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+        assert!(passed > 0);
+    }
+
+    //a node no rule makes, like from a grammar the compiler doesnt know, is an error and not a panic
+    #[test]
+    fn unknown_node() {
+        let parser = crate::grammar().unwrap();
+        let source = "x = 1; print(x + 2)";
+        let mut ast = parser.parse(&lexer::lex(source), source).unwrap();
+        ast.token_values[0].token_values[1].class_name = "Unknown".to_string();
+
+        let (vm_output, vm_value) = run_vm(&ast, String::new());
+        let (walker_output, walker_value) = walk(ast, String::new());
+        assert!(vm_output.starts_with("SyntaxError"), "{vm_output}");
+        assert_eq!((walker_output, walker_value), (vm_output, vm_value));
+    }
+
+    //a statement list is one flat node, so a long program doesnt recurse once per line. this
+    //runs on the small stack of the test thread, not the evaluator thread the cli uses
+    #[test]
+    fn long_program() {
+        let mut parser = crate::grammar().unwrap();
+        let source = format!("x = 0\n{}print(x)\n", "x = x + 1\n".repeat(10000));
+
+        for mode in [Mode::Rewrite, Mode::Earley, Mode::Peg] {
+            parser.set_mode(mode);
+            let ast = parser.parse(&lexer::lex(&source), &source).unwrap();
+
+            let (vm_output, _) = run_vm(&ast, String::new());
+            assert_eq!(vm_output, "10000\n");
+
+            let capture = Capture::new();
+            let mut env = Environment::with_io(Box::new(Cursor::new(String::new())), Box::new(capture.clone()));
+            ast.eval(&mut env).unwrap();
+            assert_eq!(capture.text(), "10000\n");
+        }
+    }
+}
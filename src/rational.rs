@@ -0,0 +1,187 @@
+use std::cmp::Ordering;
+use std::fmt::Display;
+use crate::bigint::BigInt;
+
+//an exact fraction, always in lowest terms with a positive denominator, so equal
+//fractions look the same
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numerator: BigInt,
+    denominator: BigInt
+}
+
+impl Rational {
+    //None if the denominator is zero
+    pub fn new(numerator: BigInt, denominator: BigInt) -> Option<Rational> {
+        if denominator.is_zero() {
+            return None;
+        }
+
+        let gcd = numerator.gcd(&denominator);
+        let (mut numerator, mut denominator) = (numerator.div_rem(&gcd).0, denominator.div_rem(&gcd).0);
+        if denominator.is_negative() {
+            numerator = numerator.neg();
+            denominator = denominator.neg();
+        }
+
+        Some(Rational {
+            numerator,
+            denominator
+        })
+    }
+
+    pub fn from_int(int: BigInt) -> Rational {
+        Rational {
+            numerator: int,
+            denominator: BigInt::from_i64(1)
+        }
+    }
+
+    //the exact value of the float, 0.1 is 3602879701896397/36028797018963968
+    pub fn from_f64(float: f64) -> Option<Rational> {
+        if !float.is_finite() {
+            return None;
+        }
+
+        let mut numerator = float;
+        let mut exponent = 0;
+        while numerator.fract() != 0.0 {
+            numerator *= 2.0;
+            exponent += 1;
+        }
+
+        Rational::new(BigInt::from_f64(numerator), BigInt::from_i64(1).shl(exponent))
+    }
+
+    //an int, a decimal like -1.25 or a fraction like 1/3
+    pub fn parse(text: &str) -> Option<Rational> {
+        if let Some((numerator, denominator)) = text.split_once('/') {
+            return Rational::new(BigInt::parse(numerator.trim())?, BigInt::parse(denominator.trim())?);
+        }
+
+        let (whole, fraction) = text.split_once('.').unwrap_or((text, ""));
+        if !fraction.chars().all(|char| char.is_ascii_digit()) || (whole.is_empty() && fraction.is_empty()) {
+            return None;
+        }
+
+        let sign = if whole.starts_with('-') { "-" } else { "" };
+        let digits = format!("{sign}{}{fraction}", whole.trim_start_matches('-'));
+        Rational::new(BigInt::parse(&digits)?, BigInt::from_i64(10).pow(fraction.len() as u32))
+    }
+
+    pub fn numerator(&self) -> &BigInt {
+        &self.numerator
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denominator.is_one()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numerator.is_zero()
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.numerator.to_f64() / self.denominator.to_f64()
+    }
+
+    pub fn add(&self, other: &Rational) -> Rational {
+        let numerator = self.numerator.mul(&other.denominator).add(&other.numerator.mul(&self.denominator));
+        Rational::new(numerator, self.denominator.mul(&other.denominator)).unwrap()
+    }
+
+    pub fn sub(&self, other: &Rational) -> Rational {
+        self.add(&Rational { numerator: other.numerator.neg(), denominator: other.denominator.clone() })
+    }
+
+    pub fn mul(&self, other: &Rational) -> Rational {
+        Rational::new(self.numerator.mul(&other.numerator), self.denominator.mul(&other.denominator)).unwrap()
+    }
+
+    //None when dividing by zero
+    pub fn div(&self, other: &Rational) -> Option<Rational> {
+        Rational::new(self.numerator.mul(&other.denominator), self.denominator.mul(&other.numerator))
+    }
+
+    //the largest int that isnt larger
+    pub fn floor(&self) -> BigInt {
+        self.numerator.div_floor(&self.denominator).0
+    }
+
+    //None for zero to a negative power, the exponent has to fit in 32 bits without its sign
+    pub fn pow(&self, exponent: i64) -> Option<Rational> {
+        let power = exponent.unsigned_abs() as u32;
+        let (numerator, denominator) = (self.numerator.pow(power), self.denominator.pow(power));
+
+        if exponent < 0 {
+            Rational::new(denominator, numerator)
+        } else {
+            Rational::new(numerator, denominator)
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+//a/b < c/d is a*d < c*b, the denominators are positive
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.numerator.mul(&other.denominator).cmp(&other.numerator.mul(&self.denominator))
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_integer() {
+            return write!(f, "{}", self.numerator);
+        }
+
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rational(text: &str) -> Rational {
+        Rational::parse(text).unwrap()
+    }
+
+    //lowest terms with the sign on the numerator
+    #[test]
+    fn normalization() {
+        assert_eq!(rational("2/4").to_string(), "1/2");
+        assert_eq!(rational("3/-6").to_string(), "-1/2");
+        assert_eq!(rational("-3/-6"), rational("1/2"));
+        assert_eq!(rational("0/5"), rational("0"));
+        assert_eq!(rational("-1.25").to_string(), "-5/4");
+        assert_eq!(rational("6/3").to_string(), "2");
+        assert!(rational("6/3").is_integer());
+        assert_eq!(Rational::parse("1/0"), None);
+        assert_eq!(Rational::parse("."), None);
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(rational("1/3").add(&rational("1/6")).to_string(), "1/2");
+        assert_eq!(rational("1/3").sub(&rational("1/2")).to_string(), "-1/6");
+        assert_eq!(rational("2/3").mul(&rational("3/4")).to_string(), "1/2");
+        assert_eq!(rational("1/2").div(&rational("0")), None);
+        assert_eq!(rational("-2/3").pow(-2).unwrap().to_string(), "9/4");
+        assert_eq!(rational("0").pow(-1), None);
+        assert_eq!(rational("-7/2").floor().to_string(), "-4");
+        assert!(rational("-1/2") < rational("-1/3"));
+    }
+
+    #[test]
+    fn exact_floats() {
+        assert_eq!(Rational::from_f64(0.1).unwrap().to_string(), "3602879701896397/36028797018963968");
+        assert_eq!(Rational::from_f64(-2.5).unwrap(), rational("-5/2"));
+        assert_eq!(Rational::from_f64(f64::NAN), None);
+    }
+}
@@ -38,75 +38,70 @@ pub fn read(endung: &str) -> Vec<String> {
         }
     };
 
-    let files_result = get_files_with_extension(current_dir, endung);
-    let files;
+    let files = match get_files_with_extension(current_dir, endung) {
+        Ok(files) => files,
+        Err(error) => panic!("couldnt get fufu files: {:?}", error)
+    };
     let mut file_path = "".to_string();
-    if files_result.is_err() {
-        panic!("couldnt get fufu files: {:?}", files_result.err());
-    }
-    else {
-        files = files_result.unwrap();
 
-        if files.len() == 0 {
-            panic!("no .fufu files found")
-        }
+    if files.is_empty() {
+        panic!("no .fufu files found")
+    }
 
-        let mut names: Vec<String> = vec![];
-        let mut paths: Vec<String> = vec![];
+    let mut names: Vec<String> = vec![];
+    let mut paths: Vec<String> = vec![];
 
-        for file_buf in files {
-            let path: String = file_buf.as_path().display().to_string();
-            paths.push(path.clone());
-            let path_names = &path.split(r"\".chars().collect::<Vec<char>>()[0]).collect::<Vec<&str>>();
-            let name = path_names.clone()[path_names.clone().len() - 1].split('.').collect::<Vec<&str>>()[0];
-            names.push(name.to_string());
+    for file_buf in &files {
+        let path: String = file_buf.as_path().display().to_string();
+        paths.push(path.clone());
+        let name = file_buf.file_stem().unwrap_or_default().to_str().unwrap_or_default();
+        names.push(name.to_string());
 
-            if name == "main" {
-                file_path = path;
-                println!("running main.{endung} ...\n-------------------------------------------------------------------------------------------\n");
-            }
+        if name == "main" {
+            file_path = path;
+            println!("running main.{endung} ...\n-------------------------------------------------------------------------------------------\n");
         }
+    }
 
-        if file_path.is_empty() {
-            println!("WARING: no main.{endung} program found\n-------------------------------------------------------------------------------------------");
+    if file_path.is_empty() {
+        println!("WARING: no main.{endung} program found\n-------------------------------------------------------------------------------------------");
 
-            if names.len() == 1 {
-                file_path = paths.clone()[0].clone();
-                println!("running {}.{}\n-------------------------------------------------------------------------------------------\n", names[0], endung)
+        if names.len() == 1 {
+            file_path = paths.clone()[0].clone();
+            println!("running {}.{}\n-------------------------------------------------------------------------------------------\n", names[0], endung)
 
-            } else {
-                println!("found {} .{endung} programs:", names.len());
-                for (name, path) in zip(names.clone(), paths.clone()) {
-                    println!("  {name}.{endung} - {path}");
+        } else {
+            println!("found {} .{endung} programs:", names.len());
+            for (name, path) in zip(names.clone(), paths.clone()) {
+                println!("  {name}.{endung} - {path}");
+            }
+            println!();
+            let idx;
+            loop {
+                let mut programm_name  = input("-------------------------------------------------------------------------------------------\nplease enter the name of the program that should be executed (exit to stop): ").trim_end().to_string();
+                if programm_name == "exit" {
+                    panic!("exited");
                 }
-                println!("");
-                let idx;
-                loop {
-                    let mut programm_name  = input("-------------------------------------------------------------------------------------------\nplease enter the name of the program that should be executed (exit to stop): ").trim_end().to_string();
-                    if programm_name == "exit" {
-                        panic!("exited");
-                    }
-                    if !programm_name.contains(&(".".to_owned() + endung)) {
-                        programm_name += &(".".to_owned() + endung);
-                    }
-
-                    if names.clone().contains(&programm_name.strip_suffix(&(".".to_owned() + endung)).unwrap().to_string()) {
-                        idx = names.iter().position(|x| x.clone() == programm_name.strip_suffix(&(".".to_owned() + endung)).unwrap().to_string()).unwrap();
-                        break;
-                    } else {
-                        println!("file: {} not found\n-------------------------------------------------------------------------------------------", programm_name);
-                    }
+                if !programm_name.contains(&(".".to_owned() + endung)) {
+                    programm_name += &(".".to_owned() + endung);
+                }
+
+                if names.clone().contains(&programm_name.strip_suffix(&(".".to_owned() + endung)).unwrap().to_string()) {
+                    idx = names.iter().position(|x| x.clone() == programm_name.strip_suffix(&(".".to_owned() + endung)).unwrap()).unwrap();
+                    break;
+                } else {
+                    println!("file: {} not found\n-------------------------------------------------------------------------------------------", programm_name);
                 }
-                
-                file_path = paths.clone()[idx].clone();
-                println!("-------------------------------------------------------------------------------------------\nrunning {}.{}\n-------------------------------------------------------------------------------------------\n", names[idx], endung)
             }
+            
+            file_path = paths.clone()[idx].clone();
+            println!("-------------------------------------------------------------------------------------------\nrunning {}.{}\n-------------------------------------------------------------------------------------------\n", names[idx], endung)
         }
     }
 
     let path = Path::new(&file_path);
 
-    let file = File::open(&path).expect("couldnt load file");
+    let file = File::open(path).expect("couldnt load file");
 
     let reader = io::BufReader::new(file);
 
@@ -0,0 +1,219 @@
+use std::fmt::Display;
+use crate::parser::Rule;
+
+//every step of the rewrite loop records the nodes it starts with and the rule it applied
+#[derive(Debug, Clone)]
+pub enum TraceEvent {
+    Iteration {
+        iteration: usize,
+        names: Vec<String>
+    },
+    Reduction {
+        iteration: usize,
+        precedence: i32,
+        level: String,
+        pattern: Vec<String>,
+        name: String,
+        class_name: String,
+        index: usize,
+        names: Vec<String>
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Trace {
+    pub line: String,
+    pub events: Vec<TraceEvent>
+}
+
+impl Trace {
+    pub fn new(line: &str) -> Trace {
+        Trace {
+            line: line.to_string(),
+            events: vec![]
+        }
+    }
+
+    pub fn iteration(&mut self, iteration: usize, names: &[String]) {
+        self.events.push(TraceEvent::Iteration {
+            iteration,
+            names: names.to_vec()
+        });
+    }
+
+    pub fn reduction(&mut self, iteration: usize, precedence: i32, level: &str, rule: &Rule, index: usize, names: &[String]) {
+        self.events.push(TraceEvent::Reduction {
+            iteration,
+            precedence,
+            level: level.to_string(),
+            pattern: rule.pattern.clone(),
+            name: rule.name.clone(),
+            class_name: rule.class_name.clone(),
+            index,
+            names: names.to_vec()
+        });
+    }
+
+    pub fn reductions(&self) -> usize {
+        self.events.iter().filter(|event| matches!(event, TraceEvent::Reduction { .. })).count()
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut events = vec![];
+
+        for event in &self.events {
+            events.push(match event {
+                TraceEvent::Iteration { iteration, names } => format!(
+                    "{{\"event\": \"iteration\", \"iteration\": {}, \"names\": {}}}",
+                    iteration, json_list(names)
+                ),
+                TraceEvent::Reduction { iteration, precedence, level, pattern, name, class_name, index, names } => format!(
+                    "{{\"event\": \"reduction\", \"iteration\": {}, \"precedence\": {}, \"level\": {}, \"rule\": {{\"pattern\": {}, \"name\": {}, \"class_name\": {}}}, \"index\": {}, \"names\": {}}}",
+                    iteration, precedence, json_string(level), json_list(pattern), json_string(name), json_string(class_name), index, json_list(names)
+                )
+            });
+        }
+
+        format!("{{\"line\": {}, \"events\": [{}]}}", json_string(&self.line), events.join(", "))
+    }
+}
+
+impl Display for Trace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "trace: {}", self.line)?;
+
+        for event in &self.events {
+            match event {
+                TraceEvent::Iteration { iteration, names } => {
+                    writeln!(f, "iteration {}: {}", iteration, names.join(" "))?
+                },
+                TraceEvent::Reduction { precedence, level, pattern, name, class_name, index, names, .. } => {
+                    writeln!(f, "  [precedence {} {}] {} -> {} ({}) at {}", precedence, level, pattern.join(" "), name, class_name, index)?;
+                    writeln!(f, "    {}", names.join(" "))?
+                }
+            }
+        }
+
+        write!(f, "{} reductions", self.reductions())
+    }
+}
+
+fn json_string(string: &str) -> String {
+    let mut output = "\"".to_string();
+
+    for char in string.chars() {
+        match char {
+            '"' => output += "\\\"",
+            '\\' => output += "\\\\",
+            '\n' => output += "\\n",
+            '\r' => output += "\\r",
+            '\t' => output += "\\t",
+            char if (char as u32) < 0x20 => output += &format!("\\u{:04x}", char as u32),
+            char => output.push(char)
+        }
+    }
+
+    output + "\""
+}
+
+fn json_list(strings: &[String]) -> String {
+    let items: Vec<String> = strings.iter().map(|string| json_string(string)).collect();
+
+    format!("[{}]", items.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer;
+    use crate::parser::{LevelPosition, Mode, Parser};
+
+    fn arithmetic() -> Parser {
+        let mut parser = Parser::new();
+
+        parser.insert_level("atom", LevelPosition::Power(300)).unwrap();
+        parser.insert_level("multiplicative", LevelPosition::Power(200)).unwrap();
+        parser.insert_level("additive", LevelPosition::Power(100)).unwrap();
+        parser.insert_level("statement", LevelPosition::Power(10)).unwrap();
+
+        parser.add_rule_at("atom", vec!["NUM"], "expr", "Num").unwrap();
+        parser.add_rule_at("multiplicative", vec!["expr", "STAR", "expr"], "expr", "Mul").unwrap();
+        parser.add_rule_at("additive", vec!["expr", "PLUS", "expr"], "expr", "Add").unwrap();
+        parser.add_rule_at("statement", vec!["expr", "SEMI"], "stmt", "ExprStmt").unwrap();
+        parser.set_start("stmt");
+
+        parser
+    }
+
+    fn traced(parser: &Parser, source: &str) -> Trace {
+        let (node, trace) = parser.parse_traced(&lexer::lex(source), source).unwrap();
+        assert!(node.is_ok());
+
+        trace
+    }
+
+    //a step before every reduction and one more that finds nothing to reduce
+    #[test]
+    fn step_per_reduction() {
+        let trace = traced(&arithmetic(), "1 + 2 * 3");
+
+        let classes: Vec<&str> = trace.events.iter().filter_map(|event| match event {
+            TraceEvent::Reduction { class_name, .. } => Some(class_name.as_str()),
+            TraceEvent::Iteration { .. } => None
+        }).collect();
+        assert_eq!(classes, ["Num", "Num", "Num", "Mul", "Add", "ExprStmt"]);
+        assert_eq!(trace.reductions(), 6);
+        assert_eq!(trace.events.len(), 13);
+
+        match &trace.events[0] {
+            TraceEvent::Iteration { iteration, names } => {
+                assert_eq!(*iteration, 0);
+                assert_eq!(names.join(" "), "NUM PLUS NUM STAR NUM SEMI");
+            },
+            other => panic!("expected an iteration, got {other:?}")
+        }
+
+        match &trace.events[7] {
+            TraceEvent::Reduction { iteration, precedence, level, pattern, index, names, .. } => {
+                assert_eq!(*iteration, 3);
+                assert_eq!(*precedence, 200);
+                assert_eq!(level, "multiplicative");
+                assert_eq!(pattern.join(" "), "expr STAR expr");
+                assert_eq!(*index, 2);
+                assert_eq!(names.join(" "), "expr PLUS expr SEMI");
+            },
+            other => panic!("expected a reduction, got {other:?}")
+        }
+    }
+
+    #[test]
+    fn display_and_json() {
+        let trace = traced(&arithmetic(), "1 * 2");
+
+        let text = trace.to_string();
+        assert!(text.starts_with("trace: 1 * 2\niteration 0: NUM STAR NUM SEMI\n"));
+        assert!(text.contains("  [precedence 200 multiplicative] expr STAR expr -> expr (Mul) at 0\n    expr SEMI\n"));
+        assert!(text.ends_with("4 reductions"));
+
+        let json = trace.to_json();
+        assert!(json.starts_with("{\"line\": \"1 * 2\", \"events\": [{\"event\": \"iteration\", \"iteration\": 0, \"names\": [\"NUM\", \"STAR\", \"NUM\", \"SEMI\"]}, "));
+        assert!(json.contains("{\"event\": \"reduction\", \"iteration\": 2, \"precedence\": 200, \"level\": \"multiplicative\", \"rule\": {\"pattern\": [\"expr\", \"STAR\", \"expr\"], \"name\": \"expr\", \"class_name\": \"Mul\"}, \"index\": 0, \"names\": [\"expr\", \"SEMI\"]}"));
+    }
+
+    #[test]
+    fn json_escapes() {
+        assert_eq!(json_string("say \"hi\"\\\n\t\u{1}"), "\"say \\\"hi\\\"\\\\\\n\\t\\u0001\"");
+        assert_eq!(json_list(&["a".to_string(), "b\"".to_string()]), "[\"a\", \"b\\\"\"]");
+    }
+
+    #[test]
+    fn only_rewrite_mode() {
+        let mut parser = arithmetic();
+        let tokens = lexer::lex("1 + 2");
+
+        for mode in [Mode::Earley, Mode::Peg] {
+            parser.set_mode(mode);
+            assert!(parser.parse_traced(&tokens, "1 + 2").is_err());
+        }
+    }
+}
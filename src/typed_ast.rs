@@ -0,0 +1,1991 @@
+//generated from the grammar by codegen::typed_ast, dont edit by hand
+//there is a view for every rule, eval and the compiler dont read all of their fields
+#![allow(dead_code)]
+
+use crate::lexer::Token;
+use crate::parser::Node;
+
+//a node of any nonterminal
+#[derive(Debug, Clone, Copy)]
+pub enum AnyNode<'a> {
+    Expr(ExprNode<'a>),
+    Stmt(StmtNode<'a>),
+    Entries(EntriesNode<'a>),
+    Args(ArgsNode<'a>),
+    Block(BlockNode<'a>),
+    Stmts(StmtsNode<'a>),
+    Orelse(OrelseNode<'a>),
+    Program(ProgramNode<'a>),
+}
+
+impl<'a> AnyNode<'a> {
+    pub fn from_node(node: &'a Node) -> Result<AnyNode<'a>, String> {
+        if let Ok(typed) = ExprNode::from_node(node) {
+            return Ok(AnyNode::Expr(typed));
+        }
+        if let Ok(typed) = StmtNode::from_node(node) {
+            return Ok(AnyNode::Stmt(typed));
+        }
+        if let Ok(typed) = EntriesNode::from_node(node) {
+            return Ok(AnyNode::Entries(typed));
+        }
+        if let Ok(typed) = ArgsNode::from_node(node) {
+            return Ok(AnyNode::Args(typed));
+        }
+        if let Ok(typed) = BlockNode::from_node(node) {
+            return Ok(AnyNode::Block(typed));
+        }
+        if let Ok(typed) = StmtsNode::from_node(node) {
+            return Ok(AnyNode::Stmts(typed));
+        }
+        if let Ok(typed) = OrelseNode::from_node(node) {
+            return Ok(AnyNode::Orelse(typed));
+        }
+        if let Ok(typed) = ProgramNode::from_node(node) {
+            return Ok(AnyNode::Program(typed));
+        }
+        Err(format!("no rule for node: {}", node.class_name))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ExprNode<'a> {
+    Name(NameRule<'a>),
+    Num(NumRule<'a>),
+    String(StringRule<'a>),
+    BoolTrue(BoolTrueRule<'a>),
+    BoolFalse(BoolFalseRule<'a>),
+    List(ListRule<'a>),
+    ListExpr(ListExprRule<'a>),
+    ListArgs(ListArgsRule<'a>),
+    MapLbrace(MapLbraceRule<'a>),
+    MapEntries(MapEntriesRule<'a>),
+    Print(PrintRule<'a>),
+    PrintExpr(PrintExprRule<'a>),
+    PrintArgs(PrintArgsRule<'a>),
+    Input(InputRule<'a>),
+    InputExpr(InputExprRule<'a>),
+    Index(IndexRule<'a>),
+    SliceExprColonExpr(SliceExprColonExprRule<'a>),
+    SliceExprColon(SliceExprColonRule<'a>),
+    SliceColonExpr(SliceColonExprRule<'a>),
+    SliceColon(SliceColonRule<'a>),
+    SliceExprColonExprColonExpr(SliceExprColonExprColonExprRule<'a>),
+    SliceExprColonColonExpr(SliceExprColonColonExprRule<'a>),
+    SliceColonExprColonExpr(SliceColonExprColonExprRule<'a>),
+    SliceColonColonExpr(SliceColonColonExprRule<'a>),
+    Call(CallRule<'a>),
+    CallExpr(CallExprRule<'a>),
+    CallArgs(CallArgsRule<'a>),
+    Attribute(AttributeRule<'a>),
+    Pow(PowRule<'a>),
+    Invert(InvertRule<'a>),
+    Mul(MulRule<'a>),
+    Div(DivRule<'a>),
+    FloorDiv(FloorDivRule<'a>),
+    Mod(ModRule<'a>),
+    Add(AddRule<'a>),
+    Minus(MinusRule<'a>),
+    LeftShift(LeftShiftRule<'a>),
+    RightShift(RightShiftRule<'a>),
+    BitAnd(BitAndRule<'a>),
+    BitXor(BitXorRule<'a>),
+    BitOr(BitOrRule<'a>),
+    Less(LessRule<'a>),
+    Greater(GreaterRule<'a>),
+    LessEqual(LessEqualRule<'a>),
+    GreaterEqual(GreaterEqualRule<'a>),
+    Equal(EqualRule<'a>),
+    NotEqual(NotEqualRule<'a>),
+    Not(NotRule<'a>),
+    And(AndRule<'a>),
+    Or(OrRule<'a>),
+    NamedExpr(NamedExprRule<'a>),
+}
+
+impl<'a> ExprNode<'a> {
+    pub fn from_node(node: &'a Node) -> Result<ExprNode<'a>, String> {
+        if node.class_name == "Name" && node.token_names == ["NAME"] {
+            return Ok(ExprNode::Name(NameRule::from_node(node)?));
+        }
+        if node.class_name == "Num" && node.token_names == ["NUM"] {
+            return Ok(ExprNode::Num(NumRule::from_node(node)?));
+        }
+        if node.class_name == "String" && node.token_names == ["STRING"] {
+            return Ok(ExprNode::String(StringRule::from_node(node)?));
+        }
+        if node.class_name == "Bool" && node.token_names == ["TRUE"] {
+            return Ok(ExprNode::BoolTrue(BoolTrueRule::from_node(node)?));
+        }
+        if node.class_name == "Bool" && node.token_names == ["FALSE"] {
+            return Ok(ExprNode::BoolFalse(BoolFalseRule::from_node(node)?));
+        }
+        if node.class_name == "List" && node.token_names == ["LSQB", "RSQB"] {
+            return Ok(ExprNode::List(ListRule::from_node(node)?));
+        }
+        if node.class_name == "List" && node.token_names == ["LSQB", "expr", "RSQB"] {
+            return Ok(ExprNode::ListExpr(ListExprRule::from_node(node)?));
+        }
+        if node.class_name == "List" && node.token_names == ["LSQB", "args", "RSQB"] {
+            return Ok(ExprNode::ListArgs(ListArgsRule::from_node(node)?));
+        }
+        if node.class_name == "Map" && node.token_names == ["LBRACE", "RBRACE"] {
+            return Ok(ExprNode::MapLbrace(MapLbraceRule::from_node(node)?));
+        }
+        if node.class_name == "Map" && node.token_names == ["entries", "RBRACE"] {
+            return Ok(ExprNode::MapEntries(MapEntriesRule::from_node(node)?));
+        }
+        if node.class_name == "Print" && node.token_names == ["PRINT", "LPAR", "RPAR"] {
+            return Ok(ExprNode::Print(PrintRule::from_node(node)?));
+        }
+        if node.class_name == "Print" && node.token_names == ["PRINT", "LPAR", "expr", "RPAR"] {
+            return Ok(ExprNode::PrintExpr(PrintExprRule::from_node(node)?));
+        }
+        if node.class_name == "Print" && node.token_names == ["PRINT", "LPAR", "args", "RPAR"] {
+            return Ok(ExprNode::PrintArgs(PrintArgsRule::from_node(node)?));
+        }
+        if node.class_name == "Input" && node.token_names == ["INPUT", "LPAR", "RPAR"] {
+            return Ok(ExprNode::Input(InputRule::from_node(node)?));
+        }
+        if node.class_name == "Input" && node.token_names == ["INPUT", "LPAR", "expr", "RPAR"] {
+            return Ok(ExprNode::InputExpr(InputExprRule::from_node(node)?));
+        }
+        if node.class_name == "Index" && node.token_names == ["expr", "LSQB", "expr", "RSQB"] {
+            return Ok(ExprNode::Index(IndexRule::from_node(node)?));
+        }
+        if node.class_name == "Slice" && node.token_names == ["expr", "LSQB", "expr", "COLON", "expr", "RSQB"] {
+            return Ok(ExprNode::SliceExprColonExpr(SliceExprColonExprRule::from_node(node)?));
+        }
+        if node.class_name == "Slice" && node.token_names == ["expr", "LSQB", "expr", "COLON", "RSQB"] {
+            return Ok(ExprNode::SliceExprColon(SliceExprColonRule::from_node(node)?));
+        }
+        if node.class_name == "Slice" && node.token_names == ["expr", "LSQB", "COLON", "expr", "RSQB"] {
+            return Ok(ExprNode::SliceColonExpr(SliceColonExprRule::from_node(node)?));
+        }
+        if node.class_name == "Slice" && node.token_names == ["expr", "LSQB", "COLON", "RSQB"] {
+            return Ok(ExprNode::SliceColon(SliceColonRule::from_node(node)?));
+        }
+        if node.class_name == "Slice" && node.token_names == ["expr", "LSQB", "expr", "COLON", "expr", "COLON", "expr", "RSQB"] {
+            return Ok(ExprNode::SliceExprColonExprColonExpr(SliceExprColonExprColonExprRule::from_node(node)?));
+        }
+        if node.class_name == "Slice" && node.token_names == ["expr", "LSQB", "expr", "COLON", "COLON", "expr", "RSQB"] {
+            return Ok(ExprNode::SliceExprColonColonExpr(SliceExprColonColonExprRule::from_node(node)?));
+        }
+        if node.class_name == "Slice" && node.token_names == ["expr", "LSQB", "COLON", "expr", "COLON", "expr", "RSQB"] {
+            return Ok(ExprNode::SliceColonExprColonExpr(SliceColonExprColonExprRule::from_node(node)?));
+        }
+        if node.class_name == "Slice" && node.token_names == ["expr", "LSQB", "COLON", "COLON", "expr", "RSQB"] {
+            return Ok(ExprNode::SliceColonColonExpr(SliceColonColonExprRule::from_node(node)?));
+        }
+        if node.class_name == "Call" && node.token_names == ["expr", "LPAR", "RPAR"] {
+            return Ok(ExprNode::Call(CallRule::from_node(node)?));
+        }
+        if node.class_name == "Call" && node.token_names == ["expr", "LPAR", "expr", "RPAR"] {
+            return Ok(ExprNode::CallExpr(CallExprRule::from_node(node)?));
+        }
+        if node.class_name == "Call" && node.token_names == ["expr", "LPAR", "args", "RPAR"] {
+            return Ok(ExprNode::CallArgs(CallArgsRule::from_node(node)?));
+        }
+        if node.class_name == "Attribute" && node.token_names == ["expr", "DOT", "expr"] {
+            return Ok(ExprNode::Attribute(AttributeRule::from_node(node)?));
+        }
+        if node.class_name == "Pow" && node.token_names == ["expr", "DOUBLESTAR", "expr"] {
+            return Ok(ExprNode::Pow(PowRule::from_node(node)?));
+        }
+        if node.class_name == "Invert" && node.token_names == ["TILDE", "expr"] {
+            return Ok(ExprNode::Invert(InvertRule::from_node(node)?));
+        }
+        if node.class_name == "Mul" && node.token_names == ["expr", "STAR", "expr"] {
+            return Ok(ExprNode::Mul(MulRule::from_node(node)?));
+        }
+        if node.class_name == "Div" && node.token_names == ["expr", "SLASH", "expr"] {
+            return Ok(ExprNode::Div(DivRule::from_node(node)?));
+        }
+        if node.class_name == "FloorDiv" && node.token_names == ["expr", "DOUBLESLASH", "expr"] {
+            return Ok(ExprNode::FloorDiv(FloorDivRule::from_node(node)?));
+        }
+        if node.class_name == "Mod" && node.token_names == ["expr", "PERCENT", "expr"] {
+            return Ok(ExprNode::Mod(ModRule::from_node(node)?));
+        }
+        if node.class_name == "Add" && node.token_names == ["expr", "PLUS", "expr"] {
+            return Ok(ExprNode::Add(AddRule::from_node(node)?));
+        }
+        if node.class_name == "Minus" && node.token_names == ["expr", "MINUS", "expr"] {
+            return Ok(ExprNode::Minus(MinusRule::from_node(node)?));
+        }
+        if node.class_name == "LeftShift" && node.token_names == ["expr", "LEFTSHIFT", "expr"] {
+            return Ok(ExprNode::LeftShift(LeftShiftRule::from_node(node)?));
+        }
+        if node.class_name == "RightShift" && node.token_names == ["expr", "RIGHTSHIFT", "expr"] {
+            return Ok(ExprNode::RightShift(RightShiftRule::from_node(node)?));
+        }
+        if node.class_name == "BitAnd" && node.token_names == ["expr", "AMPER", "expr"] {
+            return Ok(ExprNode::BitAnd(BitAndRule::from_node(node)?));
+        }
+        if node.class_name == "BitXor" && node.token_names == ["expr", "CIRCUMFLEX", "expr"] {
+            return Ok(ExprNode::BitXor(BitXorRule::from_node(node)?));
+        }
+        if node.class_name == "BitOr" && node.token_names == ["expr", "VBAR", "expr"] {
+            return Ok(ExprNode::BitOr(BitOrRule::from_node(node)?));
+        }
+        if node.class_name == "Less" && node.token_names == ["expr", "LESS", "expr"] {
+            return Ok(ExprNode::Less(LessRule::from_node(node)?));
+        }
+        if node.class_name == "Greater" && node.token_names == ["expr", "GREATER", "expr"] {
+            return Ok(ExprNode::Greater(GreaterRule::from_node(node)?));
+        }
+        if node.class_name == "LessEqual" && node.token_names == ["expr", "LESSEQUAL", "expr"] {
+            return Ok(ExprNode::LessEqual(LessEqualRule::from_node(node)?));
+        }
+        if node.class_name == "GreaterEqual" && node.token_names == ["expr", "GREATEREQUAL", "expr"] {
+            return Ok(ExprNode::GreaterEqual(GreaterEqualRule::from_node(node)?));
+        }
+        if node.class_name == "Equal" && node.token_names == ["expr", "EQEQUAL", "expr"] {
+            return Ok(ExprNode::Equal(EqualRule::from_node(node)?));
+        }
+        if node.class_name == "NotEqual" && node.token_names == ["expr", "NOTEQUAL", "expr"] {
+            return Ok(ExprNode::NotEqual(NotEqualRule::from_node(node)?));
+        }
+        if node.class_name == "Not" && node.token_names == ["NOT", "expr"] {
+            return Ok(ExprNode::Not(NotRule::from_node(node)?));
+        }
+        if node.class_name == "And" && node.token_names == ["expr", "AND", "expr"] {
+            return Ok(ExprNode::And(AndRule::from_node(node)?));
+        }
+        if node.class_name == "Or" && node.token_names == ["expr", "OR", "expr"] {
+            return Ok(ExprNode::Or(OrRule::from_node(node)?));
+        }
+        if node.class_name == "NamedExpr" && node.token_names == ["expr", "COLONEQUAL", "expr"] {
+            return Ok(ExprNode::NamedExpr(NamedExprRule::from_node(node)?));
+        }
+        Err(format!("not a expr node: {}", node.class_name))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum StmtNode<'a> {
+    Assign(AssignRule<'a>),
+    AugAssignPlusequal(AugAssignPlusequalRule<'a>),
+    AugAssignMinequal(AugAssignMinequalRule<'a>),
+    AugAssignStarequal(AugAssignStarequalRule<'a>),
+    AugAssignSlashequal(AugAssignSlashequalRule<'a>),
+    AugAssignDoubleslashequal(AugAssignDoubleslashequalRule<'a>),
+    AugAssignPercentequal(AugAssignPercentequalRule<'a>),
+    AugAssignDoublestarequal(AugAssignDoublestarequalRule<'a>),
+    AugAssignLeftshiftequal(AugAssignLeftshiftequalRule<'a>),
+    AugAssignRightshiftequal(AugAssignRightshiftequalRule<'a>),
+    AugAssignAmperequal(AugAssignAmperequalRule<'a>),
+    AugAssignCircumflexequal(AugAssignCircumflexequalRule<'a>),
+    AugAssignVbarequal(AugAssignVbarequalRule<'a>),
+    ExprStmt(ExprStmtRule<'a>),
+    Break(BreakRule<'a>),
+    Continue(ContinueRule<'a>),
+    If(IfRule<'a>),
+    IfOrelse(IfOrelseRule<'a>),
+    While(WhileRule<'a>),
+    ForExpr(ForExprRule<'a>),
+    ForArgs(ForArgsRule<'a>),
+    FunctionDef(FunctionDefRule<'a>),
+    FunctionDefRarrowExpr(FunctionDefRarrowExprRule<'a>),
+    Return(ReturnRule<'a>),
+    ReturnExpr(ReturnExprRule<'a>),
+}
+
+impl<'a> StmtNode<'a> {
+    pub fn from_node(node: &'a Node) -> Result<StmtNode<'a>, String> {
+        if node.class_name == "Assign" && node.token_names == ["expr", "EQUAL", "expr", "SEMI"] {
+            return Ok(StmtNode::Assign(AssignRule::from_node(node)?));
+        }
+        if node.class_name == "AugAssign" && node.token_names == ["expr", "PLUSEQUAL", "expr", "SEMI"] {
+            return Ok(StmtNode::AugAssignPlusequal(AugAssignPlusequalRule::from_node(node)?));
+        }
+        if node.class_name == "AugAssign" && node.token_names == ["expr", "MINEQUAL", "expr", "SEMI"] {
+            return Ok(StmtNode::AugAssignMinequal(AugAssignMinequalRule::from_node(node)?));
+        }
+        if node.class_name == "AugAssign" && node.token_names == ["expr", "STAREQUAL", "expr", "SEMI"] {
+            return Ok(StmtNode::AugAssignStarequal(AugAssignStarequalRule::from_node(node)?));
+        }
+        if node.class_name == "AugAssign" && node.token_names == ["expr", "SLASHEQUAL", "expr", "SEMI"] {
+            return Ok(StmtNode::AugAssignSlashequal(AugAssignSlashequalRule::from_node(node)?));
+        }
+        if node.class_name == "AugAssign" && node.token_names == ["expr", "DOUBLESLASHEQUAL", "expr", "SEMI"] {
+            return Ok(StmtNode::AugAssignDoubleslashequal(AugAssignDoubleslashequalRule::from_node(node)?));
+        }
+        if node.class_name == "AugAssign" && node.token_names == ["expr", "PERCENTEQUAL", "expr", "SEMI"] {
+            return Ok(StmtNode::AugAssignPercentequal(AugAssignPercentequalRule::from_node(node)?));
+        }
+        if node.class_name == "AugAssign" && node.token_names == ["expr", "DOUBLESTAREQUAL", "expr", "SEMI"] {
+            return Ok(StmtNode::AugAssignDoublestarequal(AugAssignDoublestarequalRule::from_node(node)?));
+        }
+        if node.class_name == "AugAssign" && node.token_names == ["expr", "LEFTSHIFTEQUAL", "expr", "SEMI"] {
+            return Ok(StmtNode::AugAssignLeftshiftequal(AugAssignLeftshiftequalRule::from_node(node)?));
+        }
+        if node.class_name == "AugAssign" && node.token_names == ["expr", "RIGHTSHIFTEQUAL", "expr", "SEMI"] {
+            return Ok(StmtNode::AugAssignRightshiftequal(AugAssignRightshiftequalRule::from_node(node)?));
+        }
+        if node.class_name == "AugAssign" && node.token_names == ["expr", "AMPEREQUAL", "expr", "SEMI"] {
+            return Ok(StmtNode::AugAssignAmperequal(AugAssignAmperequalRule::from_node(node)?));
+        }
+        if node.class_name == "AugAssign" && node.token_names == ["expr", "CIRCUMFLEXEQUAL", "expr", "SEMI"] {
+            return Ok(StmtNode::AugAssignCircumflexequal(AugAssignCircumflexequalRule::from_node(node)?));
+        }
+        if node.class_name == "AugAssign" && node.token_names == ["expr", "VBAREQUAL", "expr", "SEMI"] {
+            return Ok(StmtNode::AugAssignVbarequal(AugAssignVbarequalRule::from_node(node)?));
+        }
+        if node.class_name == "ExprStmt" && node.token_names == ["expr", "SEMI"] {
+            return Ok(StmtNode::ExprStmt(ExprStmtRule::from_node(node)?));
+        }
+        if node.class_name == "Break" && node.token_names == ["BREAK", "SEMI"] {
+            return Ok(StmtNode::Break(BreakRule::from_node(node)?));
+        }
+        if node.class_name == "Continue" && node.token_names == ["CONTINUE", "SEMI"] {
+            return Ok(StmtNode::Continue(ContinueRule::from_node(node)?));
+        }
+        if node.class_name == "If" && node.token_names == ["IF", "expr", "block", "SEMI"] {
+            return Ok(StmtNode::If(IfRule::from_node(node)?));
+        }
+        if node.class_name == "If" && node.token_names == ["IF", "expr", "block", "orelse", "SEMI"] {
+            return Ok(StmtNode::IfOrelse(IfOrelseRule::from_node(node)?));
+        }
+        if node.class_name == "While" && node.token_names == ["WHILE", "expr", "block", "SEMI"] {
+            return Ok(StmtNode::While(WhileRule::from_node(node)?));
+        }
+        if node.class_name == "For" && node.token_names == ["FOR", "expr", "IN", "expr", "block", "SEMI"] {
+            return Ok(StmtNode::ForExpr(ForExprRule::from_node(node)?));
+        }
+        if node.class_name == "For" && node.token_names == ["FOR", "args", "IN", "expr", "block", "SEMI"] {
+            return Ok(StmtNode::ForArgs(ForArgsRule::from_node(node)?));
+        }
+        if node.class_name == "FunctionDef" && node.token_names == ["FN", "expr", "block", "SEMI"] {
+            return Ok(StmtNode::FunctionDef(FunctionDefRule::from_node(node)?));
+        }
+        if node.class_name == "FunctionDef" && node.token_names == ["FN", "expr", "RARROW", "expr", "block", "SEMI"] {
+            return Ok(StmtNode::FunctionDefRarrowExpr(FunctionDefRarrowExprRule::from_node(node)?));
+        }
+        if node.class_name == "Return" && node.token_names == ["RETURN", "SEMI"] {
+            return Ok(StmtNode::Return(ReturnRule::from_node(node)?));
+        }
+        if node.class_name == "Return" && node.token_names == ["RETURN", "expr", "SEMI"] {
+            return Ok(StmtNode::ReturnExpr(ReturnExprRule::from_node(node)?));
+        }
+        Err(format!("not a stmt node: {}", node.class_name))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum EntriesNode<'a> {
+    EntriesLbrace(EntriesLbraceRule<'a>),
+    EntriesEntriesComma(EntriesEntriesCommaRule<'a>),
+}
+
+impl<'a> EntriesNode<'a> {
+    pub fn from_node(node: &'a Node) -> Result<EntriesNode<'a>, String> {
+        if node.class_name == "Entries" && node.token_names == ["LBRACE", "expr", "COLON", "expr"] {
+            return Ok(EntriesNode::EntriesLbrace(EntriesLbraceRule::from_node(node)?));
+        }
+        if node.class_name == "Entries" && node.token_names == ["entries", "COMMA", "expr", "COLON", "expr"] {
+            return Ok(EntriesNode::EntriesEntriesComma(EntriesEntriesCommaRule::from_node(node)?));
+        }
+        Err(format!("not a entries node: {}", node.class_name))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ArgsNode<'a> {
+    ArgsExpr(ArgsExprRule<'a>),
+    ArgsArgs(ArgsArgsRule<'a>),
+}
+
+impl<'a> ArgsNode<'a> {
+    pub fn from_node(node: &'a Node) -> Result<ArgsNode<'a>, String> {
+        if node.class_name == "Args" && node.token_names == ["expr", "COMMA", "expr"] {
+            return Ok(ArgsNode::ArgsExpr(ArgsExprRule::from_node(node)?));
+        }
+        if node.class_name == "Args" && node.token_names == ["args", "COMMA", "expr"] {
+            return Ok(ArgsNode::ArgsArgs(ArgsArgsRule::from_node(node)?));
+        }
+        Err(format!("not a args node: {}", node.class_name))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum BlockNode<'a> {
+    BlockStmts(BlockStmtsRule<'a>),
+    BlockSemi(BlockSemiRule<'a>),
+}
+
+impl<'a> BlockNode<'a> {
+    pub fn from_node(node: &'a Node) -> Result<BlockNode<'a>, String> {
+        if node.class_name == "Block" && node.token_names == ["LBRACE", "stmts", "RBRACE"] {
+            return Ok(BlockNode::BlockStmts(BlockStmtsRule::from_node(node)?));
+        }
+        if node.class_name == "Block" && node.token_names == ["LBRACE", "SEMI", "RBRACE"] {
+            return Ok(BlockNode::BlockSemi(BlockSemiRule::from_node(node)?));
+        }
+        Err(format!("not a block node: {}", node.class_name))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum StmtsNode<'a> {
+    Stmts(StmtsRule<'a>),
+    Stmt(StmtNode<'a>),
+}
+
+impl<'a> StmtsNode<'a> {
+    pub fn from_node(node: &'a Node) -> Result<StmtsNode<'a>, String> {
+        if node.class_name == "Stmts" && node.token_names.len() > 1 && node.token_names.iter().all(|name| name == "stmts") {
+            return Ok(StmtsNode::Stmts(StmtsRule::from_node(node)?));
+        }
+        if let Ok(inner) = StmtNode::from_node(node) {
+            return Ok(StmtsNode::Stmt(inner));
+        }
+        Err(format!("not a stmts node: {}", node.class_name))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum OrelseNode<'a> {
+    ElifOrelse(ElifOrelseRule<'a>),
+    Elif(ElifRule<'a>),
+    Else(ElseRule<'a>),
+}
+
+impl<'a> OrelseNode<'a> {
+    pub fn from_node(node: &'a Node) -> Result<OrelseNode<'a>, String> {
+        if node.class_name == "Elif" && node.token_names == ["ELIF", "expr", "block", "orelse"] {
+            return Ok(OrelseNode::ElifOrelse(ElifOrelseRule::from_node(node)?));
+        }
+        if node.class_name == "Elif" && node.token_names == ["ELIF", "expr", "block"] {
+            return Ok(OrelseNode::Elif(ElifRule::from_node(node)?));
+        }
+        if node.class_name == "Else" && node.token_names == ["ELSE", "block"] {
+            return Ok(OrelseNode::Else(ElseRule::from_node(node)?));
+        }
+        Err(format!("not a orelse node: {}", node.class_name))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ProgramNode<'a> {
+    Program(ProgramRule<'a>),
+}
+
+impl<'a> ProgramNode<'a> {
+    pub fn from_node(node: &'a Node) -> Result<ProgramNode<'a>, String> {
+        if node.class_name == "Program" && node.token_names == ["stmts"] {
+            return Ok(ProgramNode::Program(ProgramRule::from_node(node)?));
+        }
+        Err(format!("not a program node: {}", node.class_name))
+    }
+}
+
+//NAME -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct NameRule<'a> {
+    pub node: &'a Node,
+    pub name: &'a Token,
+}
+
+impl<'a> NameRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<NameRule<'a>, String> {
+        Ok(NameRule {
+            node,
+            name: node.token_values[0].token.as_ref().ok_or("expected a NAME token")?,
+        })
+    }
+}
+
+//NUM -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct NumRule<'a> {
+    pub node: &'a Node,
+    pub num: &'a Token,
+}
+
+impl<'a> NumRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<NumRule<'a>, String> {
+        Ok(NumRule {
+            node,
+            num: node.token_values[0].token.as_ref().ok_or("expected a NUM token")?,
+        })
+    }
+}
+
+//STRING -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct StringRule<'a> {
+    pub node: &'a Node,
+    pub string: &'a Token,
+}
+
+impl<'a> StringRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<StringRule<'a>, String> {
+        Ok(StringRule {
+            node,
+            string: node.token_values[0].token.as_ref().ok_or("expected a STRING token")?,
+        })
+    }
+}
+
+//TRUE -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct BoolTrueRule<'a> {
+    pub node: &'a Node,
+}
+
+impl<'a> BoolTrueRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<BoolTrueRule<'a>, String> {
+        Ok(BoolTrueRule {
+            node,
+        })
+    }
+}
+
+//FALSE -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct BoolFalseRule<'a> {
+    pub node: &'a Node,
+}
+
+impl<'a> BoolFalseRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<BoolFalseRule<'a>, String> {
+        Ok(BoolFalseRule {
+            node,
+        })
+    }
+}
+
+//LSQB RSQB -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct ListRule<'a> {
+    pub node: &'a Node,
+}
+
+impl<'a> ListRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<ListRule<'a>, String> {
+        Ok(ListRule {
+            node,
+        })
+    }
+}
+
+//LSQB expr RSQB -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct ListExprRule<'a> {
+    pub node: &'a Node,
+    pub expr: &'a Node,
+}
+
+impl<'a> ListExprRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<ListExprRule<'a>, String> {
+        Ok(ListExprRule {
+            node,
+            expr: &node.token_values[1],
+        })
+    }
+}
+
+//LSQB args RSQB -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct ListArgsRule<'a> {
+    pub node: &'a Node,
+    pub args: &'a Node,
+}
+
+impl<'a> ListArgsRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<ListArgsRule<'a>, String> {
+        Ok(ListArgsRule {
+            node,
+            args: &node.token_values[1],
+        })
+    }
+}
+
+//LBRACE RBRACE -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct MapLbraceRule<'a> {
+    pub node: &'a Node,
+}
+
+impl<'a> MapLbraceRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<MapLbraceRule<'a>, String> {
+        Ok(MapLbraceRule {
+            node,
+        })
+    }
+}
+
+//entries RBRACE -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct MapEntriesRule<'a> {
+    pub node: &'a Node,
+    pub entries: &'a Node,
+}
+
+impl<'a> MapEntriesRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<MapEntriesRule<'a>, String> {
+        Ok(MapEntriesRule {
+            node,
+            entries: &node.token_values[0],
+        })
+    }
+}
+
+//PRINT LPAR RPAR -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct PrintRule<'a> {
+    pub node: &'a Node,
+}
+
+impl<'a> PrintRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<PrintRule<'a>, String> {
+        Ok(PrintRule {
+            node,
+        })
+    }
+}
+
+//PRINT LPAR expr RPAR -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct PrintExprRule<'a> {
+    pub node: &'a Node,
+    pub expr: &'a Node,
+}
+
+impl<'a> PrintExprRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<PrintExprRule<'a>, String> {
+        Ok(PrintExprRule {
+            node,
+            expr: &node.token_values[2],
+        })
+    }
+}
+
+//PRINT LPAR args RPAR -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct PrintArgsRule<'a> {
+    pub node: &'a Node,
+    pub args: &'a Node,
+}
+
+impl<'a> PrintArgsRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<PrintArgsRule<'a>, String> {
+        Ok(PrintArgsRule {
+            node,
+            args: &node.token_values[2],
+        })
+    }
+}
+
+//INPUT LPAR RPAR -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct InputRule<'a> {
+    pub node: &'a Node,
+}
+
+impl<'a> InputRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<InputRule<'a>, String> {
+        Ok(InputRule {
+            node,
+        })
+    }
+}
+
+//INPUT LPAR expr RPAR -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct InputExprRule<'a> {
+    pub node: &'a Node,
+    pub expr: &'a Node,
+}
+
+impl<'a> InputExprRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<InputExprRule<'a>, String> {
+        Ok(InputExprRule {
+            node,
+            expr: &node.token_values[2],
+        })
+    }
+}
+
+//expr LSQB expr RSQB -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct IndexRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> IndexRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<IndexRule<'a>, String> {
+        Ok(IndexRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr LSQB expr COLON expr RSQB -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct SliceExprColonExprRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+    pub expr_2: &'a Node,
+}
+
+impl<'a> SliceExprColonExprRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<SliceExprColonExprRule<'a>, String> {
+        Ok(SliceExprColonExprRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+            expr_2: &node.token_values[4],
+        })
+    }
+}
+
+//expr LSQB expr COLON RSQB -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct SliceExprColonRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> SliceExprColonRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<SliceExprColonRule<'a>, String> {
+        Ok(SliceExprColonRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr LSQB COLON expr RSQB -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct SliceColonExprRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> SliceColonExprRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<SliceColonExprRule<'a>, String> {
+        Ok(SliceColonExprRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[3],
+        })
+    }
+}
+
+//expr LSQB COLON RSQB -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct SliceColonRule<'a> {
+    pub node: &'a Node,
+    pub expr: &'a Node,
+}
+
+impl<'a> SliceColonRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<SliceColonRule<'a>, String> {
+        Ok(SliceColonRule {
+            node,
+            expr: &node.token_values[0],
+        })
+    }
+}
+
+//expr LSQB expr COLON expr COLON expr RSQB -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct SliceExprColonExprColonExprRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+    pub expr_2: &'a Node,
+    pub expr_3: &'a Node,
+}
+
+impl<'a> SliceExprColonExprColonExprRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<SliceExprColonExprColonExprRule<'a>, String> {
+        Ok(SliceExprColonExprColonExprRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+            expr_2: &node.token_values[4],
+            expr_3: &node.token_values[6],
+        })
+    }
+}
+
+//expr LSQB expr COLON COLON expr RSQB -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct SliceExprColonColonExprRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+    pub expr_2: &'a Node,
+}
+
+impl<'a> SliceExprColonColonExprRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<SliceExprColonColonExprRule<'a>, String> {
+        Ok(SliceExprColonColonExprRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+            expr_2: &node.token_values[5],
+        })
+    }
+}
+
+//expr LSQB COLON expr COLON expr RSQB -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct SliceColonExprColonExprRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+    pub expr_2: &'a Node,
+}
+
+impl<'a> SliceColonExprColonExprRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<SliceColonExprColonExprRule<'a>, String> {
+        Ok(SliceColonExprColonExprRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[3],
+            expr_2: &node.token_values[5],
+        })
+    }
+}
+
+//expr LSQB COLON COLON expr RSQB -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct SliceColonColonExprRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> SliceColonColonExprRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<SliceColonColonExprRule<'a>, String> {
+        Ok(SliceColonColonExprRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[4],
+        })
+    }
+}
+
+//expr LPAR RPAR -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct CallRule<'a> {
+    pub node: &'a Node,
+    pub expr: &'a Node,
+}
+
+impl<'a> CallRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<CallRule<'a>, String> {
+        Ok(CallRule {
+            node,
+            expr: &node.token_values[0],
+        })
+    }
+}
+
+//expr LPAR expr RPAR -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct CallExprRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> CallExprRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<CallExprRule<'a>, String> {
+        Ok(CallExprRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr LPAR args RPAR -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct CallArgsRule<'a> {
+    pub node: &'a Node,
+    pub expr: &'a Node,
+    pub args: &'a Node,
+}
+
+impl<'a> CallArgsRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<CallArgsRule<'a>, String> {
+        Ok(CallArgsRule {
+            node,
+            expr: &node.token_values[0],
+            args: &node.token_values[2],
+        })
+    }
+}
+
+//expr DOT expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct AttributeRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> AttributeRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<AttributeRule<'a>, String> {
+        Ok(AttributeRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr DOUBLESTAR expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct PowRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> PowRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<PowRule<'a>, String> {
+        Ok(PowRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//TILDE expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct InvertRule<'a> {
+    pub node: &'a Node,
+    pub expr: &'a Node,
+}
+
+impl<'a> InvertRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<InvertRule<'a>, String> {
+        Ok(InvertRule {
+            node,
+            expr: &node.token_values[1],
+        })
+    }
+}
+
+//expr STAR expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct MulRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> MulRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<MulRule<'a>, String> {
+        Ok(MulRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr SLASH expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct DivRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> DivRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<DivRule<'a>, String> {
+        Ok(DivRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr DOUBLESLASH expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct FloorDivRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> FloorDivRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<FloorDivRule<'a>, String> {
+        Ok(FloorDivRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr PERCENT expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct ModRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> ModRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<ModRule<'a>, String> {
+        Ok(ModRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr PLUS expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct AddRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> AddRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<AddRule<'a>, String> {
+        Ok(AddRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr MINUS expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct MinusRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> MinusRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<MinusRule<'a>, String> {
+        Ok(MinusRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr LEFTSHIFT expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct LeftShiftRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> LeftShiftRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<LeftShiftRule<'a>, String> {
+        Ok(LeftShiftRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr RIGHTSHIFT expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct RightShiftRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> RightShiftRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<RightShiftRule<'a>, String> {
+        Ok(RightShiftRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr AMPER expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct BitAndRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> BitAndRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<BitAndRule<'a>, String> {
+        Ok(BitAndRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr CIRCUMFLEX expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct BitXorRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> BitXorRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<BitXorRule<'a>, String> {
+        Ok(BitXorRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr VBAR expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct BitOrRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> BitOrRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<BitOrRule<'a>, String> {
+        Ok(BitOrRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr LESS expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct LessRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> LessRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<LessRule<'a>, String> {
+        Ok(LessRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr GREATER expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct GreaterRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> GreaterRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<GreaterRule<'a>, String> {
+        Ok(GreaterRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr LESSEQUAL expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct LessEqualRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> LessEqualRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<LessEqualRule<'a>, String> {
+        Ok(LessEqualRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr GREATEREQUAL expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct GreaterEqualRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> GreaterEqualRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<GreaterEqualRule<'a>, String> {
+        Ok(GreaterEqualRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr EQEQUAL expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct EqualRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> EqualRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<EqualRule<'a>, String> {
+        Ok(EqualRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr NOTEQUAL expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct NotEqualRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> NotEqualRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<NotEqualRule<'a>, String> {
+        Ok(NotEqualRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//NOT expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct NotRule<'a> {
+    pub node: &'a Node,
+    pub expr: &'a Node,
+}
+
+impl<'a> NotRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<NotRule<'a>, String> {
+        Ok(NotRule {
+            node,
+            expr: &node.token_values[1],
+        })
+    }
+}
+
+//expr AND expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct AndRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> AndRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<AndRule<'a>, String> {
+        Ok(AndRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr OR expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct OrRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> OrRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<OrRule<'a>, String> {
+        Ok(OrRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr COLONEQUAL expr -> expr
+#[derive(Debug, Clone, Copy)]
+pub struct NamedExprRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> NamedExprRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<NamedExprRule<'a>, String> {
+        Ok(NamedExprRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr EQUAL expr SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct AssignRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> AssignRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<AssignRule<'a>, String> {
+        Ok(AssignRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr PLUSEQUAL expr SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct AugAssignPlusequalRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> AugAssignPlusequalRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<AugAssignPlusequalRule<'a>, String> {
+        Ok(AugAssignPlusequalRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr MINEQUAL expr SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct AugAssignMinequalRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> AugAssignMinequalRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<AugAssignMinequalRule<'a>, String> {
+        Ok(AugAssignMinequalRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr STAREQUAL expr SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct AugAssignStarequalRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> AugAssignStarequalRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<AugAssignStarequalRule<'a>, String> {
+        Ok(AugAssignStarequalRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr SLASHEQUAL expr SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct AugAssignSlashequalRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> AugAssignSlashequalRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<AugAssignSlashequalRule<'a>, String> {
+        Ok(AugAssignSlashequalRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr DOUBLESLASHEQUAL expr SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct AugAssignDoubleslashequalRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> AugAssignDoubleslashequalRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<AugAssignDoubleslashequalRule<'a>, String> {
+        Ok(AugAssignDoubleslashequalRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr PERCENTEQUAL expr SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct AugAssignPercentequalRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> AugAssignPercentequalRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<AugAssignPercentequalRule<'a>, String> {
+        Ok(AugAssignPercentequalRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr DOUBLESTAREQUAL expr SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct AugAssignDoublestarequalRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> AugAssignDoublestarequalRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<AugAssignDoublestarequalRule<'a>, String> {
+        Ok(AugAssignDoublestarequalRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr LEFTSHIFTEQUAL expr SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct AugAssignLeftshiftequalRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> AugAssignLeftshiftequalRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<AugAssignLeftshiftequalRule<'a>, String> {
+        Ok(AugAssignLeftshiftequalRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr RIGHTSHIFTEQUAL expr SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct AugAssignRightshiftequalRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> AugAssignRightshiftequalRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<AugAssignRightshiftequalRule<'a>, String> {
+        Ok(AugAssignRightshiftequalRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr AMPEREQUAL expr SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct AugAssignAmperequalRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> AugAssignAmperequalRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<AugAssignAmperequalRule<'a>, String> {
+        Ok(AugAssignAmperequalRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr CIRCUMFLEXEQUAL expr SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct AugAssignCircumflexequalRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> AugAssignCircumflexequalRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<AugAssignCircumflexequalRule<'a>, String> {
+        Ok(AugAssignCircumflexequalRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//expr VBAREQUAL expr SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct AugAssignVbarequalRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> AugAssignVbarequalRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<AugAssignVbarequalRule<'a>, String> {
+        Ok(AugAssignVbarequalRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//LBRACE expr COLON expr -> entries
+#[derive(Debug, Clone, Copy)]
+pub struct EntriesLbraceRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> EntriesLbraceRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<EntriesLbraceRule<'a>, String> {
+        Ok(EntriesLbraceRule {
+            node,
+            expr_0: &node.token_values[1],
+            expr_1: &node.token_values[3],
+        })
+    }
+}
+
+//entries COMMA expr COLON expr -> entries
+#[derive(Debug, Clone, Copy)]
+pub struct EntriesEntriesCommaRule<'a> {
+    pub node: &'a Node,
+    pub entries: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> EntriesEntriesCommaRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<EntriesEntriesCommaRule<'a>, String> {
+        Ok(EntriesEntriesCommaRule {
+            node,
+            entries: &node.token_values[0],
+            expr_0: &node.token_values[2],
+            expr_1: &node.token_values[4],
+        })
+    }
+}
+
+//expr COMMA expr -> args
+#[derive(Debug, Clone, Copy)]
+pub struct ArgsExprRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+}
+
+impl<'a> ArgsExprRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<ArgsExprRule<'a>, String> {
+        Ok(ArgsExprRule {
+            node,
+            expr_0: &node.token_values[0],
+            expr_1: &node.token_values[2],
+        })
+    }
+}
+
+//args COMMA expr -> args
+#[derive(Debug, Clone, Copy)]
+pub struct ArgsArgsRule<'a> {
+    pub node: &'a Node,
+    pub args: &'a Node,
+    pub expr: &'a Node,
+}
+
+impl<'a> ArgsArgsRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<ArgsArgsRule<'a>, String> {
+        Ok(ArgsArgsRule {
+            node,
+            args: &node.token_values[0],
+            expr: &node.token_values[2],
+        })
+    }
+}
+
+//expr SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct ExprStmtRule<'a> {
+    pub node: &'a Node,
+    pub expr: &'a Node,
+}
+
+impl<'a> ExprStmtRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<ExprStmtRule<'a>, String> {
+        Ok(ExprStmtRule {
+            node,
+            expr: &node.token_values[0],
+        })
+    }
+}
+
+//BREAK SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct BreakRule<'a> {
+    pub node: &'a Node,
+}
+
+impl<'a> BreakRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<BreakRule<'a>, String> {
+        Ok(BreakRule {
+            node,
+        })
+    }
+}
+
+//CONTINUE SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct ContinueRule<'a> {
+    pub node: &'a Node,
+}
+
+impl<'a> ContinueRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<ContinueRule<'a>, String> {
+        Ok(ContinueRule {
+            node,
+        })
+    }
+}
+
+//IF expr block SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct IfRule<'a> {
+    pub node: &'a Node,
+    pub expr: &'a Node,
+    pub block: &'a Node,
+}
+
+impl<'a> IfRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<IfRule<'a>, String> {
+        Ok(IfRule {
+            node,
+            expr: &node.token_values[1],
+            block: &node.token_values[2],
+        })
+    }
+}
+
+//IF expr block orelse SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct IfOrelseRule<'a> {
+    pub node: &'a Node,
+    pub expr: &'a Node,
+    pub block: &'a Node,
+    pub orelse: &'a Node,
+}
+
+impl<'a> IfOrelseRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<IfOrelseRule<'a>, String> {
+        Ok(IfOrelseRule {
+            node,
+            expr: &node.token_values[1],
+            block: &node.token_values[2],
+            orelse: &node.token_values[3],
+        })
+    }
+}
+
+//WHILE expr block SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct WhileRule<'a> {
+    pub node: &'a Node,
+    pub expr: &'a Node,
+    pub block: &'a Node,
+}
+
+impl<'a> WhileRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<WhileRule<'a>, String> {
+        Ok(WhileRule {
+            node,
+            expr: &node.token_values[1],
+            block: &node.token_values[2],
+        })
+    }
+}
+
+//FOR expr IN expr block SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct ForExprRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+    pub block: &'a Node,
+}
+
+impl<'a> ForExprRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<ForExprRule<'a>, String> {
+        Ok(ForExprRule {
+            node,
+            expr_0: &node.token_values[1],
+            expr_1: &node.token_values[3],
+            block: &node.token_values[4],
+        })
+    }
+}
+
+//FOR args IN expr block SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct ForArgsRule<'a> {
+    pub node: &'a Node,
+    pub args: &'a Node,
+    pub expr: &'a Node,
+    pub block: &'a Node,
+}
+
+impl<'a> ForArgsRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<ForArgsRule<'a>, String> {
+        Ok(ForArgsRule {
+            node,
+            args: &node.token_values[1],
+            expr: &node.token_values[3],
+            block: &node.token_values[4],
+        })
+    }
+}
+
+//FN expr block SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct FunctionDefRule<'a> {
+    pub node: &'a Node,
+    pub expr: &'a Node,
+    pub block: &'a Node,
+}
+
+impl<'a> FunctionDefRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<FunctionDefRule<'a>, String> {
+        Ok(FunctionDefRule {
+            node,
+            expr: &node.token_values[1],
+            block: &node.token_values[2],
+        })
+    }
+}
+
+//FN expr RARROW expr block SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct FunctionDefRarrowExprRule<'a> {
+    pub node: &'a Node,
+    pub expr_0: &'a Node,
+    pub expr_1: &'a Node,
+    pub block: &'a Node,
+}
+
+impl<'a> FunctionDefRarrowExprRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<FunctionDefRarrowExprRule<'a>, String> {
+        Ok(FunctionDefRarrowExprRule {
+            node,
+            expr_0: &node.token_values[1],
+            expr_1: &node.token_values[3],
+            block: &node.token_values[4],
+        })
+    }
+}
+
+//RETURN SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct ReturnRule<'a> {
+    pub node: &'a Node,
+}
+
+impl<'a> ReturnRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<ReturnRule<'a>, String> {
+        Ok(ReturnRule {
+            node,
+        })
+    }
+}
+
+//RETURN expr SEMI -> stmt
+#[derive(Debug, Clone, Copy)]
+pub struct ReturnExprRule<'a> {
+    pub node: &'a Node,
+    pub expr: &'a Node,
+}
+
+impl<'a> ReturnExprRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<ReturnExprRule<'a>, String> {
+        Ok(ReturnExprRule {
+            node,
+            expr: &node.token_values[1],
+        })
+    }
+}
+
+//LBRACE stmts RBRACE -> block
+#[derive(Debug, Clone, Copy)]
+pub struct BlockStmtsRule<'a> {
+    pub node: &'a Node,
+    pub stmts: &'a Node,
+}
+
+impl<'a> BlockStmtsRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<BlockStmtsRule<'a>, String> {
+        Ok(BlockStmtsRule {
+            node,
+            stmts: &node.token_values[1],
+        })
+    }
+}
+
+//LBRACE SEMI RBRACE -> block
+#[derive(Debug, Clone, Copy)]
+pub struct BlockSemiRule<'a> {
+    pub node: &'a Node,
+}
+
+impl<'a> BlockSemiRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<BlockSemiRule<'a>, String> {
+        Ok(BlockSemiRule {
+            node,
+        })
+    }
+}
+
+//stmts stmts -> stmts, any number of stmts
+#[derive(Debug, Clone, Copy)]
+pub struct StmtsRule<'a> {
+    pub node: &'a Node,
+    pub stmts: &'a [Node],
+}
+
+impl<'a> StmtsRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<StmtsRule<'a>, String> {
+        Ok(StmtsRule {
+            node,
+            stmts: &node.token_values,
+        })
+    }
+}
+
+//ELIF expr block orelse -> orelse
+#[derive(Debug, Clone, Copy)]
+pub struct ElifOrelseRule<'a> {
+    pub node: &'a Node,
+    pub expr: &'a Node,
+    pub block: &'a Node,
+    pub orelse: &'a Node,
+}
+
+impl<'a> ElifOrelseRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<ElifOrelseRule<'a>, String> {
+        Ok(ElifOrelseRule {
+            node,
+            expr: &node.token_values[1],
+            block: &node.token_values[2],
+            orelse: &node.token_values[3],
+        })
+    }
+}
+
+//ELIF expr block -> orelse
+#[derive(Debug, Clone, Copy)]
+pub struct ElifRule<'a> {
+    pub node: &'a Node,
+    pub expr: &'a Node,
+    pub block: &'a Node,
+}
+
+impl<'a> ElifRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<ElifRule<'a>, String> {
+        Ok(ElifRule {
+            node,
+            expr: &node.token_values[1],
+            block: &node.token_values[2],
+        })
+    }
+}
+
+//ELSE block -> orelse
+#[derive(Debug, Clone, Copy)]
+pub struct ElseRule<'a> {
+    pub node: &'a Node,
+    pub block: &'a Node,
+}
+
+impl<'a> ElseRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<ElseRule<'a>, String> {
+        Ok(ElseRule {
+            node,
+            block: &node.token_values[1],
+        })
+    }
+}
+
+//stmts -> program
+#[derive(Debug, Clone, Copy)]
+pub struct ProgramRule<'a> {
+    pub node: &'a Node,
+    pub stmts: &'a Node,
+}
+
+impl<'a> ProgramRule<'a> {
+    pub fn from_node(node: &'a Node) -> Result<ProgramRule<'a>, String> {
+        Ok(ProgramRule {
+            node,
+            stmts: &node.token_values[0],
+        })
+    }
+}
@@ -0,0 +1,244 @@
+use crate::lexer::{self, NumType, Token};
+use crate::parser::{fits, is_predicate, needs_parens, Assoc, Node, Parser, Rule};
+
+//turns a node back into source, using the rule patterns of the parser. fails for nodes no
+//rule of the parser makes
+pub fn unparse(parser: &Parser, node: &Node) -> Result<String, String> {
+    let mut pieces = vec![];
+    unparse_node(parser, node, &mut pieces)?;
+
+    let mut output = "".to_string();
+    for (index, piece) in pieces.iter().enumerate() {
+        if index > 0 && pieces[index - 1] != "(" && piece != ")" {
+            output += " ";
+        }
+        output += piece;
+    }
+
+    Ok(output)
+}
+
+pub fn token_text(token: &Token) -> String {
+    match token {
+        Token::STRING(string) => format!("\"{}\"", string.replace('\\', "\\\\").replace('"', "\\\"")),
+        other => other.value()
+    }
+}
+
+//the pattern is returned without lookahead predicates, like the children of the node
+fn find_rule(parser: &Parser, node: &Node) -> Option<(Vec<String>, i32, Assoc)> {
+    for level in &parser.levels {
+        for rule in &level.rules {
+            //a joined sequence has as many symbols as it has children
+            if !rule.group && rule.class_name == node.class_name && fits(&rule.pattern, &rule.name, &node.token_names) {
+                return Some((node.token_names.clone(), level.power, level.assoc));
+            }
+        }
+    }
+
+    None
+}
+
+//only groups around the same symbol like ( expr ), not ones like stmt -> stmts
+fn find_group<'a>(parser: &'a Parser, name: &str) -> Option<&'a Rule> {
+    parser.levels.iter().flat_map(|level| level.rules.iter()).find(|rule| rule.group && rule.name == name && rule.pattern.contains(&rule.name))
+}
+
+fn unparse_node(parser: &Parser, node: &Node, pieces: &mut Vec<String>) -> Result<(), String> {
+    if node.class_name == "TOKEN" {
+        pieces.push(token_text(node.token.as_ref().unwrap()));
+        return Ok(());
+    }
+
+    let (pattern, power, assoc) = find_rule(parser, node).ok_or(format!("no rule for node: {}", node.class_name))?;
+
+    for (position, child) in node.token_values.iter().enumerate() {
+        if child.class_name == "TOKEN" {
+            unparse_node(parser, child, pieces)?;
+            continue;
+        }
+
+        let (_, child_power, _) = find_rule(parser, child).ok_or(format!("no rule for node: {}", child.class_name))?;
+
+        let group = find_group(parser, &pattern[position]);
+
+        match group {
+            Some(group) if needs_parens(power, assoc, position, pattern.len(), child_power) => {
+                for symbol in &group.pattern {
+                    if *symbol == pattern[position] {
+                        unparse_node(parser, child, pieces)?;
+                    } else {
+                        pieces.push(token_text(&Token::from_name(symbol).unwrap()));
+                    }
+                }
+            },
+            _ => unparse_node(parser, child, pieces)?
+        }
+    }
+
+    Ok(())
+}
+
+//xorshift, good enough to generate expressions
+pub struct Rng {
+    state: u64
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed.max(1) }
+    }
+
+    pub fn next(&mut self, max: usize) -> usize {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+
+        (self.state % max as u64) as usize
+    }
+}
+
+fn is_nonterminal(parser: &Parser, symbol: &str) -> bool {
+    parser.levels.iter().flat_map(|level| level.rules.iter()).any(|rule| rule.name == symbol)
+}
+
+fn generate_terminal(symbol: &str, rng: &mut Rng) -> Token {
+    match symbol {
+        "NUM" => {
+            if rng.next(4) == 0 {
+                Token::NUM(format!("{}.5", rng.next(1000)), NumType::FLOAT)
+            } else {
+                Token::NUM(rng.next(100).to_string(), NumType::INT)
+            }
+        },
+        "STRING" => Token::STRING(["foo", "bar", "hallo welt", "", "say \"hi\"", "back\\slash\\"][rng.next(6)].to_string()),
+        "NAME" => Token::NAME(["a", "b", "foo", "x1"][rng.next(4)].to_string()),
+        other => match Token::from_name(other) {
+            Some(token) => token,
+            None => panic!("cant generate token: {other}")
+        }
+    }
+}
+
+//fully parenthesized tokens for a random derivation of `name`
+pub fn generate(parser: &Parser, name: &str, depth: usize, rng: &mut Rng) -> Vec<Token> {
+    let rules: Vec<&Rule> = parser.levels.iter().flat_map(|level| level.rules.iter()).filter(|rule| !rule.group && rule.name == name).collect();
+    let leaves: Vec<&Rule> = rules.iter().copied().filter(|rule| !rule.pattern.iter().any(|symbol| is_nonterminal(parser, symbol))).collect();
+
+    let rule = if depth == 0 && !leaves.is_empty() {
+        leaves[rng.next(leaves.len())]
+    } else {
+        rules[rng.next(rules.len())]
+    };
+
+    let mut tokens = vec![];
+
+    for symbol in &rule.pattern {
+        if is_predicate(symbol) {
+            continue;
+        }
+        if !is_nonterminal(parser, symbol) {
+            tokens.push(generate_terminal(symbol, rng));
+            continue;
+        }
+
+        let inner = generate(parser, symbol, depth.saturating_sub(1), rng);
+
+        match find_group(parser, symbol) {
+            Some(group) => {
+                for group_symbol in &group.pattern {
+                    if group_symbol == symbol {
+                        tokens.extend(inner.clone());
+                    } else {
+                        tokens.push(Token::from_name(group_symbol).unwrap());
+                    }
+                }
+            },
+            None => tokens.extend(inner)
+        }
+    }
+
+    tokens
+}
+
+//property check: parse(unparse(ast)) == ast for `count` generated expressions
+pub fn check_roundtrip(parser: &Parser, name: &str, count: usize, seed: u64) -> Result<usize, String> {
+    let mut rng = Rng::new(seed);
+
+    for _ in 0..count {
+        let tokens = generate(parser, name, 1 + rng.next(5), &mut rng);
+        let generated: Vec<String> = tokens.iter().map(token_text).collect();
+        let generated = generated.join(" ");
+
+        let ast = match parser.parse(&lexer::lex(&generated), &generated) {
+            Ok(ast) => ast,
+            Err(error) => return Err(format!("generated input didnt parse: {error}"))
+        };
+
+        let source = unparse(parser, &ast)?;
+        let reparsed = match parser.parse(&lexer::lex(&source), &source) {
+            Ok(reparsed) => reparsed,
+            Err(error) => return Err(format!("roundtrip failed for: {source}\n  {error}"))
+        };
+
+        if reparsed != ast {
+            return Err(format!("roundtrip failed for: {source}\n  ast: {:?}\n  reparsed: {:?}", ast, reparsed));
+        }
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::lex;
+    use crate::parser::{LevelPosition, Mode};
+
+    //+ and *, the unparser only knows the nodes of these rules
+    fn arithmetic() -> Parser {
+        let mut parser = Parser::new();
+
+        parser.insert_level("atom", LevelPosition::Power(300)).unwrap();
+        parser.insert_level("multiplicative", LevelPosition::Power(200)).unwrap();
+        parser.insert_level("additive", LevelPosition::Power(100)).unwrap();
+        parser.insert_level("statement", LevelPosition::Power(10)).unwrap();
+
+        parser.add_rule_at("atom", vec!["NUM"], "expr", "Num").unwrap();
+        parser.add_rule_at("multiplicative", vec!["expr", "STAR", "expr"], "expr", "Mul").unwrap();
+        parser.add_rule_at("additive", vec!["expr", "PLUS", "expr"], "expr", "Add").unwrap();
+        parser.add_rule_at("statement", vec!["expr", "SEMI"], "stmt", "ExprStmt").unwrap();
+        parser.set_start("stmt");
+
+        parser
+    }
+
+    #[test]
+    fn unparse_unknown_node() {
+        let parser = arithmetic();
+        let mut ast = parser.parse(&lex("1 + 2"), "1 + 2").unwrap();
+        assert_eq!(unparse(&parser, &ast), Ok("1 + 2 ;".to_string()));
+
+        ast.token_values[0].class_name = "Unknown".to_string();
+        assert_eq!(unparse(&parser, &ast), Err("no rule for node: Unknown".to_string()));
+    }
+
+    //parse(unparse(ast)) == ast for expressions generated from the fufu grammar
+    #[test]
+    fn roundtrip_generated_expressions() {
+        for mode in [Mode::Rewrite, Mode::Earley, Mode::Peg] {
+            let mut parser = crate::grammar().unwrap();
+            parser.set_mode(mode);
+
+            assert_eq!(check_roundtrip(&parser, "expr", 200, 42), Ok(200), "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn strings_are_escaped() {
+        for string in ["say \"hi\"", "back\\slash", "ends in \\", "\\\"", ""] {
+            let text = token_text(&Token::STRING(string.to_string()));
+            assert_eq!(lex(&text)[0], Token::STRING(string.to_string()), "{text}");
+        }
+    }
+}
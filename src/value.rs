@@ -0,0 +1,385 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use std::thread::LocalKey;
+use crate::bigint::BigInt;
+use crate::builtins::NativeResult;
+use crate::bytecode::Closure;
+use crate::environment::{Environment, Scope};
+use crate::parser::Node;
+use crate::rational::Rational;
+
+//lists and maps are shared references, a copy sees the changes of the original
+#[derive(Debug, Clone)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    //only for ints that dont fit in an Int, Value::int picks the right one
+    BigInt(BigInt),
+    Rational(Rational),
+    Float(f64),
+    Str(String),
+    List(Rc<RefCell<Vec<Value>>>),
+    Map(Rc<RefCell<Map>>),
+    Function(Rc<Function>),
+    //a function compiled for the vm
+    Closure(Rc<Closure>),
+    NativeFunction(NativeFunction)
+}
+
+//keeps the insertion order, so printing and iterating is the same every run
+#[derive(Debug, Clone, Default)]
+pub struct Map {
+    entries: Vec<(Value, Value)>,
+    index: HashMap<Value, usize>
+}
+
+#[derive(Debug)]
+pub struct Function {
+    pub name: String,
+    pub params: Vec<String>,
+    pub body: Node,
+    //the scope the function was defined in
+    pub closure: Rc<RefCell<Scope>>
+}
+
+#[derive(Debug, Clone)]
+pub struct NativeFunction {
+    pub name: String,
+    pub function: fn(&mut Environment, Vec<Value>) -> NativeResult,
+    //the value of a method like xs.append, it is passed before the arguments
+    pub receiver: Option<Box<Value>>
+}
+
+thread_local! {
+    //the lists and maps being printed and the pairs being compared further up the stack, one
+    //that contains itself is printed as [...] or {...} and equal to a copy of itself
+    static PRINTING: RefCell<Vec<usize>> = const { RefCell::new(vec![]) };
+    static COMPARING: RefCell<Vec<(usize, usize)>> = const { RefCell::new(vec![]) };
+}
+
+//runs visit with key on the stack, None if it already is on it
+fn enter<K: PartialEq, T>(stack: &'static LocalKey<RefCell<Vec<K>>>, key: K, visit: impl FnOnce() -> T) -> Option<T> {
+    if stack.with(|stack| stack.borrow().contains(&key)) {
+        return None;
+    }
+
+    stack.with(|stack| stack.borrow_mut().push(key));
+    let result = visit();
+    stack.with(|stack| stack.borrow_mut().pop());
+    Some(result)
+}
+
+impl Value {
+    pub fn list(values: Vec<Value>) -> Value {
+        Value::List(Rc::new(RefCell::new(values)))
+    }
+
+    pub fn map(map: Map) -> Value {
+        Value::Map(Rc::new(RefCell::new(map)))
+    }
+
+    pub fn int(int: BigInt) -> Value {
+        match int.to_i64() {
+            Some(int) => Value::Int(int),
+            None => Value::BigInt(int)
+        }
+    }
+
+    //ints of any size
+    pub fn integer(&self) -> Option<BigInt> {
+        match self {
+            Value::Int(int) => Some(BigInt::from_i64(*int)),
+            Value::BigInt(int) => Some(int.clone()),
+            _ => None
+        }
+    }
+
+    //the exact numbers, ints and rationals
+    pub fn rational(&self) -> Option<Rational> {
+        match self {
+            Value::Rational(rational) => Some(rational.clone()),
+            other => other.integer().map(Rational::from_int)
+        }
+    }
+
+    //every number, ints that are too large for a float are infinite
+    pub fn float(&self) -> Option<f64> {
+        match self {
+            Value::Float(float) => Some(*float),
+            Value::Rational(rational) => Some(rational.to_f64()),
+            Value::Int(int) => Some(*int as f64),
+            Value::BigInt(int) => Some(int.to_f64()),
+            _ => None
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "bool",
+            Value::Int(_) | Value::BigInt(_) => "int",
+            Value::Rational(_) => "rational",
+            Value::Float(_) => "float",
+            Value::Str(_) => "string",
+            Value::List(_) => "list",
+            Value::Map(_) => "map",
+            Value::Function(_) | Value::Closure(_) => "function",
+            Value::NativeFunction(_) => "native function"
+        }
+    }
+
+    //null, false, zero and empty strings, lists and maps are false, everything else is true
+    pub fn truthy(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Bool(bool) => *bool,
+            Value::Int(int) => *int != 0,
+            Value::BigInt(_) => true,
+            Value::Rational(rational) => !rational.is_zero(),
+            Value::Float(float) => *float != 0.0,
+            Value::Str(string) => !string.is_empty(),
+            Value::List(list) => !list.borrow().is_empty(),
+            Value::Map(map) => !map.borrow().is_empty(),
+            Value::Function(_) | Value::Closure(_) | Value::NativeFunction(_) => true
+        }
+    }
+
+    //numbers by value, strings and lists lexicographically, None if they cant be ordered
+    pub fn order(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Int(a), Value::Int(b)) => Some(a.cmp(b)),
+            (Value::Float(_), _) | (_, Value::Float(_)) => self.float()?.partial_cmp(&other.float()?),
+            (Value::Int(_) | Value::BigInt(_) | Value::Rational(_), _) => Some(self.rational()?.cmp(&other.rational()?)),
+            (Value::Str(a), Value::Str(b)) => Some(a.cmp(b)),
+            (Value::List(a), Value::List(b)) => {
+                for (item1, item2) in a.borrow().iter().zip(b.borrow().iter()) {
+                    match item1.order(item2)? {
+                        Ordering::Equal => continue,
+                        ordering => return Some(ordering)
+                    }
+                }
+                Some(a.borrow().len().cmp(&b.borrow().len()))
+            }
+            _ => None
+        }
+    }
+
+    //like Display, but strings are quoted, used for the items of lists and maps
+    pub fn repr(&self) -> String {
+        match self {
+            Value::Str(string) => format!("\"{}\"", string),
+            other => other.to_string()
+        }
+    }
+}
+
+impl Map {
+    pub fn new() -> Map {
+        Map::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, key: &Value) -> Option<&Value> {
+        self.index.get(key).map(|index| &self.entries[*index].1)
+    }
+
+    pub fn insert(&mut self, key: Value, value: Value) {
+        match self.index.get(&key) {
+            Some(index) => self.entries[*index].1 = value,
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    pub fn entries(&self) -> &Vec<(Value, Value)> {
+        &self.entries
+    }
+}
+
+//numbers compare by exact value, so 1 == 1.0 but 2**53 + 1 != 2.0**53, functions by identity
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Null, Value::Null) => true,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => a == b,
+            (Value::Int(int), Value::Float(float)) | (Value::Float(float), Value::Int(int)) => float.fract() == 0.0 && *float as i128 == *int as i128,
+            (Value::Float(float), exact) | (exact, Value::Float(float)) if exact.rational().is_some() => Rational::from_f64(*float).is_some_and(|float| exact.rational() == Some(float)),
+            (Value::Int(_) | Value::BigInt(_) | Value::Rational(_), _) if other.rational().is_some() => self.rational() == other.rational(),
+            (Value::Str(a), Value::Str(b)) => a == b,
+            (Value::List(a), Value::List(b)) => {
+                let pair = (Rc::as_ptr(a) as usize, Rc::as_ptr(b) as usize);
+                Rc::ptr_eq(a, b) || enter(&COMPARING, pair, || *a.borrow() == *b.borrow()).unwrap_or(true)
+            }
+            (Value::Map(a), Value::Map(b)) => {
+                let pair = (Rc::as_ptr(a) as usize, Rc::as_ptr(b) as usize);
+                Rc::ptr_eq(a, b) || enter(&COMPARING, pair, || *a.borrow() == *b.borrow()).unwrap_or(true)
+            }
+            (Value::Function(a), Value::Function(b)) => Rc::ptr_eq(a, b),
+            (Value::Closure(a), Value::Closure(b)) => Rc::ptr_eq(a, b),
+            (Value::NativeFunction(a), Value::NativeFunction(b)) => a.name == b.name,
+            _ => false
+        }
+    }
+}
+
+impl Eq for Value {}
+
+//has to agree with eq, an integral float or fraction hashes like the int
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Value::Null => 0.hash(state),
+            Value::Bool(bool) => bool.hash(state),
+            Value::Int(int) => int.hash(state),
+            //a BigInt that fits in an Int has to hash like it
+            Value::BigInt(int) => match int.to_i64() {
+                Some(int) => int.hash(state),
+                None => int.hash(state)
+            },
+            Value::Rational(rational) if rational.is_integer() => Value::int(rational.numerator().clone()).hash(state),
+            //a fraction equals a float if they are the same as floats
+            Value::Rational(rational) => rational.to_f64().to_bits().hash(state),
+            Value::Float(float) if float.fract() == 0.0 => Value::int(BigInt::from_f64(*float)).hash(state),
+            Value::Float(float) => float.to_bits().hash(state),
+            Value::Str(string) => string.hash(state),
+            //lists cant be keys, see builtins::hashable, and the items could contain the list
+            Value::List(list) => list.borrow().len().hash(state),
+            Value::Map(map) => map.borrow().len().hash(state),
+            Value::Function(function) => Rc::as_ptr(function).hash(state),
+            Value::Closure(closure) => Rc::as_ptr(closure).hash(state),
+            Value::NativeFunction(function) => function.name.hash(state)
+        }
+    }
+}
+
+//same entries in the same order
+impl PartialEq for Map {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+//like python: 3.0 instead of 3, and an exponent for very large and very small numbers
+fn float_text(float: f64) -> String {
+    if float.is_nan() {
+        return "nan".to_string();
+    }
+    if float.is_infinite() {
+        return if float > 0.0 { "inf" } else { "-inf" }.to_string();
+    }
+
+    let magnitude = float.abs();
+    if magnitude != 0.0 && !(1e-4..1e16).contains(&magnitude) {
+        let text = format!("{:e}", float);
+        let (mantissa, exponent) = text.split_once('e').unwrap();
+        let exponent: i32 = exponent.parse().unwrap();
+        return format!("{mantissa}e{}{:02}", if exponent < 0 { "-" } else { "+" }, exponent.abs());
+    }
+
+    let text = float.to_string();
+    if text.contains('.') { text } else { text + ".0" }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(bool) => write!(f, "{}", bool),
+            Value::Int(int) => write!(f, "{}", int),
+            Value::BigInt(int) => write!(f, "{}", int),
+            Value::Rational(rational) => write!(f, "{}", rational),
+            Value::Float(float) => write!(f, "{}", float_text(*float)),
+            Value::Str(string) => write!(f, "{}", string),
+            Value::List(list) => match enter(&PRINTING, Rc::as_ptr(list) as usize, || list.borrow().iter().map(|item| item.repr()).collect::<Vec<String>>()) {
+                Some(items) => write!(f, "[{}]", items.join(", ")),
+                None => write!(f, "[...]")
+            },
+            Value::Map(map) => match enter(&PRINTING, Rc::as_ptr(map) as usize, || map.borrow().entries.iter().map(|(key, value)| format!("{}: {}", key.repr(), value.repr())).collect::<Vec<String>>()) {
+                Some(items) => write!(f, "{{{}}}", items.join(", ")),
+                None => write!(f, "{{...}}")
+            },
+            Value::Function(function) => write!(f, "<fn {}>", function.name),
+            Value::Closure(closure) => write!(f, "<fn {}>", closure.code.name),
+            Value::NativeFunction(function) => write!(f, "<native fn {}>", function.name)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+
+    fn hash(value: &Value) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn rational(text: &str) -> Value {
+        Value::Rational(Rational::parse(text).unwrap())
+    }
+
+    //numbers of different types are equal when their values are, and then hash the same
+    #[test]
+    fn mixed_numbers() {
+        let big = BigInt::parse("100000000000000000000").unwrap();
+        let equal = [
+            (Value::Int(1), Value::Float(1.0)),
+            (Value::Int(3), rational("6/2")),
+            (Value::Float(0.5), rational("1/2")),
+            (Value::BigInt(big.clone()), Value::Float(1e20)),
+            (Value::BigInt(big.clone()), Value::Rational(Rational::from_int(big))),
+            (Value::BigInt(BigInt::from_i64(7)), Value::Int(7)),
+            (Value::Float(-0.0), Value::Int(0))
+        ];
+        for (value1, value2) in equal {
+            assert_eq!(value1, value2);
+            assert_eq!(hash(&value1), hash(&value2), "{value1:?} {value2:?}");
+        }
+
+        assert_ne!(Value::Float(0.1), rational("1/10"));
+        assert_ne!(Value::Int(i64::MAX), Value::Float(i64::MAX as f64));
+        assert_ne!(Value::Int(1), Value::Str("1".to_string()));
+        assert_ne!(Value::Float(f64::NAN), Value::Float(f64::NAN));
+    }
+
+    //a map finds a key by its value, whatever number type it was stored as
+    #[test]
+    fn map_keys() {
+        let mut map = Map::new();
+        map.insert(Value::Int(1), Value::Str("int".to_string()));
+        map.insert(Value::Float(1.0), Value::Str("float".to_string()));
+        map.insert(rational("1/2"), Value::Str("half".to_string()));
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&Value::Int(1)), Some(&Value::Str("float".to_string())));
+        assert_eq!(map.get(&Value::Float(0.5)), Some(&Value::Str("half".to_string())));
+        assert_eq!(map.get(&Value::Str("1".to_string())), None);
+    }
+
+    #[test]
+    fn truthiness() {
+        let falsy = [Value::Null, Value::Bool(false), Value::Int(0), Value::Float(0.0), rational("0"), Value::Str(String::new()), Value::list(vec![]), Value::map(Map::new())];
+        let truthy = [Value::Bool(true), Value::Int(-1), Value::Float(0.1), rational("1/3"), Value::Str("0".to_string()), Value::list(vec![Value::Null])];
+
+        assert!(falsy.iter().all(|value| !value.truthy()));
+        assert!(truthy.iter().all(|value| value.truthy()));
+    }
+}
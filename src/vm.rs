@@ -0,0 +1,461 @@
+use std::cell::RefCell;
+use std::io::{BufRead, Write};
+use std::rc::Rc;
+use crate::ast::MAX_DEPTH;
+use crate::builtins;
+use crate::bytecode::{Closure, Instruction, Locals, Place, Program};
+use crate::environment::Environment;
+use crate::error::{ErrorKind, RuntimeError};
+use crate::lexer::Span;
+use crate::operators::{self, COMPARISONS};
+use crate::value::{Map, Value};
+
+//a running call, the program itself is the first one
+struct Frame {
+    closure: Rc<Closure>,
+    locals: Locals,
+    stack: Vec<Value>,
+    //the items the for loops of the call still have to go through
+    iterators: Vec<std::vec::IntoIter<Value>>,
+    ip: usize
+}
+
+//runs the instructions in a loop instead of recursing, a call pushes a frame and a
+//return pops it. print, input and the native functions use the environment
+pub struct Vm<'a> {
+    program: &'a Program,
+    env: &'a mut Environment,
+    globals: Vec<Option<Value>>,
+    frames: Vec<Frame>
+}
+
+//the compiler keeps the stack balanced and the frames matched, so this is a bug in the
+//bytecode and not in the program, but it still ends the program with an error
+fn broken(message: &str) -> (ErrorKind, String) {
+    (ErrorKind::InternalError, format!("broken bytecode: {message}"))
+}
+
+fn pop(stack: &mut Vec<Value>) -> Result<Value, (ErrorKind, String)> {
+    stack.pop().ok_or_else(|| broken("the stack is empty"))
+}
+
+fn top(stack: &[Value]) -> Result<&Value, (ErrorKind, String)> {
+    stack.last().ok_or_else(|| broken("the stack is empty"))
+}
+
+//the count values on top of the stack, the lowest first
+fn take(stack: &mut Vec<Value>, count: usize) -> Result<Vec<Value>, (ErrorKind, String)> {
+    match stack.len().checked_sub(count) {
+        Some(start) => Ok(stack.split_off(start)),
+        None => Err(broken("the stack is empty"))
+    }
+}
+
+fn constant_text(value: &Value) -> String {
+    match value {
+        Value::Str(string) => string.clone(),
+        other => other.to_string()
+    }
+}
+
+impl<'a> Vm<'a> {
+    pub fn new(program: &'a Program, env: &'a mut Environment) -> Vm<'a> {
+        let mut globals = vec![None; program.globals.len()];
+        for function in builtins::globals() {
+            if let Some(slot) = program.globals.iter().position(|name| *name == function.name) {
+                globals[slot] = Some(Value::NativeFunction(function));
+            }
+        }
+
+        let closure = Closure {
+            code: program.code.clone(),
+            captured: vec![]
+        };
+
+        Vm {
+            program,
+            env,
+            globals,
+            frames: vec![Frame {
+                locals: Rc::new(RefCell::new(vec![None; program.code.locals.len()])),
+                closure: Rc::new(closure),
+                stack: vec![],
+                iterators: vec![],
+                ip: 0
+            }]
+        }
+    }
+
+    //the value of the last statement of the program, like ast::Program
+    pub fn run(&mut self) -> Result<Value, RuntimeError> {
+        loop {
+            match self.step() {
+                Ok(Some(value)) => return Ok(value),
+                Ok(None) => {}
+                Err((kind, message)) => return Err(self.error(kind, &message))
+            }
+        }
+    }
+
+    //located at the instruction that was running
+    fn error(&self, kind: ErrorKind, message: &str) -> RuntimeError {
+        //there is no frame left only when the program returned, which isnt an error
+        let span = match self.frames.last() {
+            Some(frame) => frame.closure.code.spans[frame.ip - 1],
+            None => Span { start: 0, end: 0 }
+        };
+        RuntimeError {
+            kind,
+            message: message.to_string(),
+            line: self.program.source.clone(),
+            span
+        }
+    }
+
+    //runs one instruction, Some when the program returned
+    fn step(&mut self) -> Result<Option<Value>, (ErrorKind, String)> {
+        let depth = self.frames.len();
+        let frame = self.frames.last_mut().ok_or_else(|| broken("no function is running"))?;
+        let code = &frame.closure.code;
+        let instruction = *code.instructions.get(frame.ip).ok_or_else(|| broken("ran past the last instruction"))?;
+        frame.ip += 1;
+        let stack = &mut frame.stack;
+
+        match instruction {
+            Instruction::Constant(index) => stack.push(code.constants[index].clone()),
+            Instruction::Null => stack.push(Value::Null),
+            Instruction::Pop => {
+                pop(stack)?;
+            }
+            Instruction::Dup => stack.push(top(stack)?.clone()),
+            Instruction::DupTwo => {
+                let pair = take(stack, 2)?;
+                stack.extend(pair.iter().cloned());
+                stack.extend(pair);
+            }
+            Instruction::Swap => {
+                let pair = take(stack, 2)?;
+                stack.extend(pair.into_iter().rev());
+            }
+            Instruction::RotateThree => {
+                let top = pop(stack)?;
+                let below = take(stack, 2)?;
+                stack.push(top);
+                stack.extend(below);
+            }
+            Instruction::Load(index) => {
+                let lookup = &code.lookups[index];
+                let mut found = None;
+                for place in &lookup.places {
+                    found = match *place {
+                        Place::Local(slot) => frame.locals.borrow()[slot].clone(),
+                        Place::Enclosing(depth, slot) => frame.closure.captured[depth].borrow()[slot].clone(),
+                        Place::Global(slot) => self.globals[slot].clone()
+                    };
+                    if found.is_some() {
+                        break;
+                    }
+                }
+
+                match found {
+                    Some(value) => stack.push(value),
+                    None => return Err((ErrorKind::UndefinedName, format!("name {} is not defined", lookup.name)))
+                }
+            }
+            Instruction::StoreLocal(slot) => frame.locals.borrow_mut()[slot] = Some(pop(stack)?),
+            Instruction::StoreGlobal(slot) => self.globals[slot] = Some(pop(stack)?),
+            Instruction::Add | Instruction::Minus | Instruction::Mul | Instruction::Div | Instruction::FloorDiv
+            | Instruction::Mod | Instruction::Pow | Instruction::LeftShift | Instruction::RightShift
+            | Instruction::BitAnd | Instruction::BitXor | Instruction::BitOr | Instruction::InPlaceAdd => {
+                let value2 = pop(stack)?;
+                let value1 = pop(stack)?;
+                let result = match instruction {
+                    Instruction::Add => operators::add(value1, value2),
+                    Instruction::Minus => operators::minus(value1, value2),
+                    Instruction::Mul => operators::mul(value1, value2),
+                    Instruction::Div => operators::div(value1, value2),
+                    Instruction::FloorDiv => operators::floor_div(value1, value2),
+                    Instruction::Mod => operators::modulo(value1, value2),
+                    Instruction::Pow => operators::pow(value1, value2),
+                    Instruction::LeftShift => operators::bitwise("LeftShift", value1, value2),
+                    Instruction::RightShift => operators::bitwise("RightShift", value1, value2),
+                    Instruction::BitAnd => operators::bitwise("BitAnd", value1, value2),
+                    Instruction::BitXor => operators::bitwise("BitXor", value1, value2),
+                    Instruction::BitOr => operators::bitwise("BitOr", value1, value2),
+                    _ => operators::augmented("Add", value1, value2)
+                };
+                stack.push(result?);
+            }
+            Instruction::Invert => {
+                let value = pop(stack)?;
+                stack.push(operators::invert(value)?);
+            }
+            Instruction::Not => {
+                let value = pop(stack)?;
+                stack.push(Value::Bool(!value.truthy()));
+            }
+            Instruction::Compare(index) => {
+                let value2 = pop(stack)?;
+                let value1 = pop(stack)?;
+                stack.push(Value::Bool(operators::compare(COMPARISONS[index], &value1, &value2)?));
+            }
+            Instruction::Index => {
+                let index = pop(stack)?;
+                let target = pop(stack)?;
+                stack.push(operators::item(&target, &index)?);
+            }
+            Instruction::Slice => {
+                let step = pop(stack)?;
+                let end = pop(stack)?;
+                let start = pop(stack)?;
+                let target = pop(stack)?;
+                stack.push(operators::slice(&target, &start, &end, &step)?);
+            }
+            Instruction::Attribute(name) => {
+                let value = pop(stack)?;
+                stack.push(operators::attribute(&value, &constant_text(&code.constants[name]))?);
+            }
+            Instruction::StoreIndex => {
+                let index = pop(stack)?;
+                let container = pop(stack)?;
+                let value = pop(stack)?;
+                operators::store_item(&container, index, value)?;
+            }
+            Instruction::StoreAttribute(name) => {
+                let container = pop(stack)?;
+                let value = pop(stack)?;
+                operators::store_attribute(&container, &constant_text(&code.constants[name]), value)?;
+            }
+            Instruction::BuildList(count) => {
+                let values = take(stack, count)?;
+                stack.push(Value::list(values));
+            }
+            Instruction::BuildMap(count) => {
+                let mut map = Map::new();
+                let mut values = take(stack, 2 * count)?.into_iter();
+                while let (Some(key), Some(value)) = (values.next(), values.next()) {
+                    map.insert(key, value);
+                }
+                stack.push(Value::map(map));
+            }
+            Instruction::Hashable => builtins::hashable(top(stack)?)?,
+            Instruction::Jump(target) => frame.ip = target,
+            Instruction::JumpIfFalse(target) => {
+                if !pop(stack)?.truthy() {
+                    frame.ip = target;
+                }
+            }
+            Instruction::JumpIfFalseOrPop(target) => match top(stack)?.truthy() {
+                false => frame.ip = target,
+                true => {
+                    pop(stack)?;
+                }
+            },
+            Instruction::JumpIfTrueOrPop(target) => match top(stack)?.truthy() {
+                true => frame.ip = target,
+                false => {
+                    pop(stack)?;
+                }
+            },
+            Instruction::GetIter => {
+                let iterable = pop(stack)?;
+                frame.iterators.push(operators::items(&iterable)?.into_iter());
+            }
+            Instruction::ForIter(target) => match frame.iterators.last_mut().ok_or_else(|| broken("no loop is running"))?.next() {
+                Some(item) => stack.push(item),
+                None => {
+                    frame.iterators.pop();
+                    frame.ip = target;
+                }
+            },
+            Instruction::PopIterator => {
+                frame.iterators.pop();
+            }
+            Instruction::Items => {
+                let value = pop(stack)?;
+                stack.push(Value::list(operators::items(&value)?));
+            }
+            Instruction::Unpack(count) => {
+                let values = match pop(stack)? {
+                    Value::List(list) => list.borrow().clone(),
+                    other => vec![other]
+                };
+                stack.extend(operators::unpack(values, count)?.into_iter().rev());
+            }
+            Instruction::Print(count) => {
+                let values = take(stack, count)?;
+                let text: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+                writeln!(self.env.writer, "{}", text.join(" ")).map_err(|error| (ErrorKind::IoError, format!("couldnt print: {error}")))?;
+                stack.push(Value::Null);
+            }
+            Instruction::Input(prompt) => {
+                if prompt {
+                    let prompt = pop(stack)?;
+                    write!(self.env.writer, "{prompt}").and_then(|_| self.env.writer.flush()).map_err(|error| (ErrorKind::IoError, format!("couldnt print: {error}")))?;
+                }
+
+                let mut line = String::new();
+                self.env.reader.read_line(&mut line).map_err(|error| (ErrorKind::IoError, format!("couldnt read input: {error}")))?;
+                stack.push(Value::Str(line.trim_end_matches(['\n', '\r']).to_string()));
+            }
+            Instruction::Raise(kind, message) => return Err((kind, constant_text(&code.constants[message]))),
+            Instruction::MakeFunction(index) => {
+                //the program isnt a function, its variables are the globals
+                let mut captured = vec![];
+                if depth > 1 {
+                    captured.push(frame.locals.clone());
+                    captured.extend(frame.closure.captured.iter().cloned());
+                }
+
+                let closure = Closure {
+                    code: code.functions[index].clone(),
+                    captured
+                };
+                stack.push(Value::Closure(Rc::new(closure)));
+            }
+            Instruction::Call(count) => {
+                let arguments = take(stack, count)?;
+                let callee = pop(stack)?;
+                return self.call(callee, arguments).map(|_| None);
+            }
+            Instruction::Return => {
+                let value = pop(stack)?;
+                self.frames.pop();
+
+                match self.frames.last_mut() {
+                    Some(caller) => caller.stack.push(value),
+                    None => return Ok(Some(value))
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    //a function of the program gets a new frame, a native one runs right away
+    fn call(&mut self, callee: Value, arguments: Vec<Value>) -> Result<(), (ErrorKind, String)> {
+        let closure = match callee {
+            Value::Closure(closure) => closure,
+            Value::NativeFunction(function) => {
+                let mut arguments = arguments;
+                if let Some(receiver) = function.receiver {
+                    arguments.insert(0, *receiver);
+                }
+                let result = (function.function)(self.env, arguments)?;
+                self.frames.last_mut().ok_or_else(|| broken("no function is running"))?.stack.push(result);
+                return Ok(());
+            }
+            other => return Err((ErrorKind::TypeError, format!("{} is not callable", other.type_name())))
+        };
+
+        let code = &closure.code;
+        if arguments.len() != code.params {
+            return Err((ErrorKind::TypeError, format!("{}() takes {} arguments but {} were given", code.name, code.params, arguments.len())));
+        }
+        //the program is a frame too, but not a call
+        if self.frames.len() > MAX_DEPTH {
+            return Err((ErrorKind::RecursionError, format!("maximum recursion depth of {MAX_DEPTH} exceeded")));
+        }
+
+        let mut locals: Vec<Option<Value>> = arguments.into_iter().map(Some).collect();
+        locals.resize(code.locals.len(), None);
+
+        self.frames.push(Frame {
+            locals: Rc::new(RefCell::new(locals)),
+            closure,
+            stack: vec![],
+            iterators: vec![],
+            ip: 0
+        });
+
+        Ok(())
+    }
+}
+
+pub fn run(program: &Program, env: &mut Environment) -> Result<Value, RuntimeError> {
+    Vm::new(program, env).run()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytecode::Code;
+    use crate::compiler;
+    use crate::lexer;
+
+    //a program of only the given instructions, with 1 2 3 as constants
+    fn program(instructions: Vec<Instruction>) -> Program {
+        let code = Code {
+            name: "<program>".to_string(),
+            spans: vec![Span { start: 0, end: 0 }; instructions.len()],
+            instructions,
+            constants: vec![Value::Int(1), Value::Int(2), Value::Int(3)],
+            ..Code::default()
+        };
+        Program { code: Rc::new(code), globals: vec![], source: "".into() }
+    }
+
+    fn stack(vm: &Vm) -> Vec<Value> {
+        vm.frames[0].stack.clone()
+    }
+
+    #[test]
+    fn stack_effects() {
+        let program = program(vec![
+            Instruction::Constant(0), Instruction::Constant(1), Instruction::Constant(2),
+            Instruction::RotateThree, Instruction::Swap, Instruction::DupTwo, Instruction::Dup, Instruction::Pop, Instruction::Add
+        ]);
+        let mut env = Environment::new();
+        let mut vm = Vm::new(&program, &mut env);
+        let int = |ints: &[i64]| ints.iter().map(|int| Value::Int(*int)).collect::<Vec<Value>>();
+
+        for _ in 0..3 {
+            vm.step().unwrap();
+        }
+        assert_eq!(stack(&vm), int(&[1, 2, 3]));
+        vm.step().unwrap();
+        assert_eq!(stack(&vm), int(&[3, 1, 2]));
+        vm.step().unwrap();
+        assert_eq!(stack(&vm), int(&[3, 2, 1]));
+        vm.step().unwrap();
+        assert_eq!(stack(&vm), int(&[3, 2, 1, 2, 1]));
+        vm.step().unwrap();
+        assert_eq!(stack(&vm), int(&[3, 2, 1, 2, 1, 1]));
+        vm.step().unwrap();
+        vm.step().unwrap();
+        assert_eq!(stack(&vm), int(&[3, 2, 1, 3]));
+    }
+
+    //an instruction that needs more values than there are is an error and not a panic
+    #[test]
+    fn empty_stack() {
+        let program = program(vec![Instruction::Constant(0), Instruction::Swap]);
+        let mut env = Environment::new();
+
+        let error = run(&program, &mut env).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::InternalError);
+        assert_eq!(error.message, "broken bytecode: the stack is empty");
+    }
+
+    //every statement leaves the stack like it found it, when the program returns only its
+    //value is left
+    #[test]
+    fn statements_keep_the_stack_balanced() {
+        let parser = crate::grammar().unwrap();
+        let source = "x = [1, 2]; for i in x { if i > 1 { break } }; y = {\"a\": 1}; y[\"a\"] += 2; x[0] = y[\"a\"]; x";
+        let ast = parser.parse(&lexer::lex(source), source).unwrap();
+        let program = compiler::compile(&ast);
+        let mut env = Environment::new();
+        let mut vm = Vm::new(&program, &mut env);
+
+        loop {
+            let frame = &vm.frames[0];
+            if frame.closure.code.instructions[frame.ip] == Instruction::Return {
+                assert_eq!(frame.stack.len(), 1);
+                break;
+            }
+            vm.step().unwrap();
+        }
+        assert_eq!(vm.run().unwrap().repr(), "[3, 2]");
+    }
+}